@@ -16,7 +16,7 @@ use bsp::pin_alias;
 use hal::clock::GenericClockController;
 use hal::delay::Delay;
 use hal::prelude::*;
-use hal::pwm::Pwm3;
+use hal::pwm::{Pwm3, TC3Pinout};
 use pac::{CorePeripherals, Peripherals};
 
 #[entry]
@@ -33,13 +33,14 @@ fn main() -> ! {
     let mut delay = Delay::new(core.SYST, &mut clocks);
     let pins = bsp::Pins::new(peripherals.PORT);
 
-    let _d5: bsp::D5Pwm = pin_alias!(pins.d5_pwm).into();
+    let d5 = TC3Pinout::Pa15(pin_alias!(pins.d5_pwm));
 
     let gclk0 = clocks.gclk0();
     let mut pwm3 = Pwm3::new(
         &clocks.tcc2_tc3(&gclk0).unwrap(),
         1.khz(),
         peripherals.TC3,
+        d5,
         &mut peripherals.PM,
     );
     let max_duty = pwm3.get_max_duty();
@@ -0,0 +1,40 @@
+//! Output a 3MHz clock on pin D5 (PA16, GCLK_IO[2])
+
+#![no_std]
+#![no_main]
+
+use feather_m4 as bsp;
+#[cfg(not(feature = "use_semihosting"))]
+use panic_halt as _;
+#[cfg(feature = "use_semihosting")]
+use panic_semihosting as _;
+
+use bsp::entry;
+use bsp::hal;
+use hal::clock::{GclkOutConfig, GenericClockController};
+use hal::pac::gclk::genctrl::SRC_A::DPLL0;
+use hal::pac::gclk::pchctrl::GEN_A::GCLK2;
+use hal::pac::Peripherals;
+
+#[entry]
+fn main() -> ! {
+    let mut peripherals = Peripherals::take().unwrap();
+    let mut clocks = GenericClockController::with_external_32kosc(
+        peripherals.GCLK,
+        &mut peripherals.MCLK,
+        &mut peripherals.OSC32KCTRL,
+        &mut peripherals.OSCCTRL,
+        &mut peripherals.NVMCTRL,
+    );
+    let pins = bsp::Pins::new(peripherals.PORT);
+
+    // 120MHz / 40 = 3MHz
+    let gclk2 = clocks
+        .configure_gclk_divider_and_source(GCLK2, 40, DPLL0, false)
+        .unwrap();
+    let _clock_out = clocks
+        .enable_gclk_out(gclk2, pins.d5, GclkOutConfig::new())
+        .ok()
+        .unwrap();
+    loop {}
+}
@@ -11,7 +11,7 @@ use panic_halt as _;
 use panic_semihosting as _;
 
 use bsp::entry;
-use hal::clock::GenericClockController;
+use hal::clock::{GenericClockController, RtcClockSource};
 use hal::pac::{interrupt, CorePeripherals, Peripherals, RTC};
 use hal::prelude::*;
 use hal::rtc;
@@ -28,7 +28,7 @@ fn main() -> ! {
     // Configure all of our peripherals/clocks
     let mut peripherals = Peripherals::take().unwrap();
     let mut core = CorePeripherals::take().unwrap();
-    let _clocks = GenericClockController::with_internal_32kosc(
+    let mut clocks = GenericClockController::with_internal_32kosc(
         peripherals.GCLK,
         &mut peripherals.MCLK,
         &mut peripherals.OSC32KCTRL,
@@ -36,9 +36,14 @@ fn main() -> ! {
         &mut peripherals.NVMCTRL,
     );
 
-    // Configure the RTC. a 1024 Hz clock is configured for us when enabling our
-    // main clock
-    let timer = rtc::Rtc::count32_mode(peripherals.RTC, 1024.hz(), &mut peripherals.MCLK);
+    // Configure the RTC from the 1024 Hz output of the OSCULP32K
+    let rtc_token = clocks.take_rtc_clock_token().unwrap();
+    let rtc_clock = clocks
+        .enable_rtc_clock(rtc_token, RtcClockSource::ULP1K)
+        .ok()
+        .unwrap();
+    let timer =
+        rtc::Rtc::count32_mode_with_clock(peripherals.RTC, rtc_clock, &mut peripherals.MCLK);
     let mut sleeping_delay = SleepingDelay::new(timer, &INTERRUPT_FIRED);
 
     // We can use the RTC in standby for maximum power savings
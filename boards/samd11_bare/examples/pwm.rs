@@ -15,7 +15,7 @@ use hal::clock::GenericClockController;
 use hal::delay::Delay;
 use hal::gpio::*;
 use hal::prelude::*;
-use hal::pwm::{Channel, Pwm0};
+use hal::pwm::{Channel, Pwm0, TCC0Pinout};
 use pac::{CorePeripherals, Peripherals};
 
 #[entry]
@@ -33,13 +33,14 @@ fn main() -> ! {
     let pins = bsp::Pins::new(peripherals.PORT);
 
     let _d1: Pin<_, AlternateF> = pins.d1.into_mode();
-    let _d14: Pin<_, AlternateF> = pins.d14.into_mode();
+    let d14 = TCC0Pinout::Pa4(pins.d14);
 
     let gclk0 = clocks.gclk0();
     let mut pwm0 = Pwm0::new(
         &clocks.tcc0(&gclk0).unwrap(),
         1.khz(),
         peripherals.TCC0,
+        d14,
         &mut peripherals.PM,
     );
     let max_duty = pwm0.get_max_duty();
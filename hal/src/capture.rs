@@ -0,0 +1,654 @@
+//! # Input capture
+//!
+//! [`Capture`] measures the period and the pulse width of a signal on an EIC
+//! pin, with the period and pulse width capture modes of the TC and TCC
+//! peripherals:
+//!
+//! * the EIC generates an event on each edge of the signal, which is routed to
+//!   the timer through an EVSYS channel, on the asynchronous path so that the
+//!   channel needs no clock;
+//! * on the edge starting a period, the timer captures the period into one
+//!   compare/capture channel and restarts from zero;
+//! * on the opposite edge, it captures the pulse width into the other channel.
+//!
+//! With [`Order::PeriodPulseWidth`], a period starts on a rising edge and the
+//! pulse width is the time the signal stays high. [`Order::PulseWidthPeriod`]
+//! starts a period on a falling edge, and measures the time the signal stays
+//! low.
+//!
+//! The timer is either a TC [`Counter`](crate::tc::Counter), in any size, or a
+//! TCC wrapped in a [`TccTimer`]. The resolution and the longest measurable
+//! period are set by the frequency of the timer: a period longer than the
+//! range of the counter is reported as [`Error::Overflow`].
+//!
+//! The EIC pin must be configured to generate events, with a sense on both
+//! edges:
+//!
+//! ```no_run
+//! # use atsamd_hal::capture::*;
+//! # use atsamd_hal::clock::GenericClockController;
+//! # use atsamd_hal::eic::{self, pin::*};
+//! # use atsamd_hal::gpio::Pins;
+//! # use atsamd_hal::pac::Peripherals;
+//! # use atsamd_hal::tc::{self, Counter};
+//! let mut peripherals = Peripherals::take().unwrap();
+//! let mut clocks = GenericClockController::with_external_32kosc(
+//!     peripherals.GCLK,
+//!     &mut peripherals.MCLK,
+//!     &mut peripherals.OSC32KCTRL,
+//!     &mut peripherals.OSCCTRL,
+//!     &mut peripherals.NVMCTRL,
+//! );
+//! let gclk0 = clocks.gclk0();
+//! let pins = Pins::new(peripherals.PORT);
+//!
+//! let eic_clock = clocks.eic(&gclk0).unwrap();
+//! let mut eic = eic::init_with_ulp32k(&mut peripherals.MCLK, eic_clock, peripherals.EIC);
+//! let mut input = pins.pa16.into_floating_ei();
+//! input.sense(&mut eic, Sense::BOTH);
+//! input.enable_event(&mut eic);
+//! let _eic = eic.finalize();
+//!
+//! let clock = clocks.tc0_tc1(&gclk0).unwrap();
+//! let counter = Counter::count16(
+//!     peripherals.TC0,
+//!     &clock,
+//!     &mut peripherals.MCLK,
+//!     tc::Config::new().prescaler(tc::Prescaler::DIV8),
+//! );
+//! let mut capture = Capture::new(
+//!     counter,
+//!     &mut peripherals.EVSYS,
+//!     &mut peripherals.MCLK,
+//!     0,
+//!     &input,
+//!     Order::PeriodPulseWidth,
+//! );
+//! let measurement = nb::block!(capture.read()).unwrap();
+//! let period = measurement.period();
+//! ```
+
+use bitflags::bitflags;
+
+use crate::clock::{ClockId, PeripheralClock, Reclock};
+use crate::eic::pin::ExternalInterrupt;
+use crate::evsys;
+use crate::pac::EVSYS;
+use crate::tcc::{self, Prescaler};
+use crate::time::{Hertz, Nanoseconds};
+use crate::typelevel::Sealed;
+
+#[cfg(all(feature = "unproven", feature = "dma"))]
+use crate::dmac::{
+    self,
+    channel::{AnyChannel, Busy, CallbackStatus, Channel, InterruptFlags, Ready},
+    transfer::BufferPair,
+    Beat, Buffer, Transfer, TriggerAction, TriggerSource,
+};
+
+#[cfg(feature = "min-samd51g")]
+use crate::pac::MCLK as Apb;
+#[cfg(any(feature = "samd11", feature = "samd21"))]
+use crate::pac::PM as Apb;
+
+bitflags! {
+    /// Capture interrupt bit flags
+    ///
+    /// The binary format of the underlying bits matches the `INTFLAG` bits of
+    /// a TC. They are translated from and to the `INTFLAG` bits of a TCC.
+    pub struct Flags: u8 {
+        /// The counter overflowed, since no period started within its range
+        const OVF = 0x01;
+        /// A capture was lost, since the channel held a value not read yet
+        const ERR = 0x02;
+        /// Capture on channel 0
+        const MC0 = 0x10;
+        /// Capture on channel 1
+        const MC1 = 0x20;
+    }
+}
+
+/// Order of the period and pulse width captures
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Order {
+    /// A period starts on a rising edge, and is captured into the channel 0.
+    /// The high time is captured into the channel 1.
+    PeriodPulseWidth,
+    /// A period starts on a falling edge, and is captured into the channel 1.
+    /// The low time is captured into the channel 0.
+    PulseWidthPeriod,
+}
+
+impl Order {
+    /// Compare/capture channel holding the period
+    #[inline]
+    fn period_channel(self) -> usize {
+        match self {
+            Order::PeriodPulseWidth => 0,
+            Order::PulseWidthPeriod => 1,
+        }
+    }
+}
+
+/// Value captured by a [`Capture`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Value {
+    /// The period of the signal
+    Period,
+    /// The pulse width of the signal
+    PulseWidth,
+}
+
+/// Capture errors
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// The counter overflowed: the signal is stopped, or its period is longer
+    /// than the range of the counter
+    Overflow,
+    /// A capture was overwritten before it was read
+    Lost,
+}
+
+//==============================================================================
+// Timer
+//==============================================================================
+
+/// A timer that can capture the period and pulse width of a signal
+///
+/// This is implemented for the TC [`Counter`](crate::tc::Counter) and for
+/// [`TccTimer`].
+pub trait Timer: Sealed {
+    /// Type of the captured values
+    type Word: Copy + Into<u32>;
+
+    /// EVSYS user of the event input
+    #[doc(hidden)]
+    const EVSYS_USER: u8;
+
+    /// DMA triggers of the compare/capture channels
+    #[cfg(all(feature = "unproven", feature = "dma"))]
+    #[doc(hidden)]
+    const DMA_TRIGGERS: [TriggerSource; 2];
+
+    /// Frequency of the counter
+    #[doc(hidden)]
+    fn capture_freq(&self) -> Hertz;
+
+    /// Configure both channels in capture mode, enable the event input with
+    /// the period and pulse width action, and start the counter
+    #[doc(hidden)]
+    fn start_capture(&mut self, order: Order);
+
+    /// Stop the counter and disable the event input
+    #[doc(hidden)]
+    fn stop_capture(&mut self);
+
+    /// Read the compare/capture `channel`
+    #[doc(hidden)]
+    fn captured(&self, channel: usize) -> Self::Word;
+
+    /// Address of the compare/capture `channel`
+    #[doc(hidden)]
+    fn captured_ptr(&self, channel: usize) -> *mut Self::Word;
+
+    #[doc(hidden)]
+    fn capture_flags(&self) -> Flags;
+
+    #[doc(hidden)]
+    fn clear_capture_flags(&mut self, flags: Flags);
+
+    #[doc(hidden)]
+    fn enable_capture_interrupts(&mut self, flags: Flags);
+
+    #[doc(hidden)]
+    fn disable_capture_interrupts(&mut self, flags: Flags);
+}
+
+//==============================================================================
+// TCC
+//==============================================================================
+
+/// TCC `INTFLAG` bits matching the [`Flags`]
+const TCC_OVF: u32 = 1 << 0;
+const TCC_ERR: u32 = 1 << 3;
+const TCC_MC0: u32 = 1 << 16;
+const TCC_MC1: u32 = 1 << 17;
+
+impl Flags {
+    fn from_tcc(bits: u32) -> Self {
+        let mut flags = Flags::empty();
+        flags.set(Flags::OVF, bits & TCC_OVF != 0);
+        flags.set(Flags::ERR, bits & TCC_ERR != 0);
+        flags.set(Flags::MC0, bits & TCC_MC0 != 0);
+        flags.set(Flags::MC1, bits & TCC_MC1 != 0);
+        flags
+    }
+
+    fn to_tcc(self) -> u32 {
+        let mut bits = 0;
+        if self.contains(Flags::OVF) {
+            bits |= TCC_OVF;
+        }
+        if self.contains(Flags::ERR) {
+            bits |= TCC_ERR;
+        }
+        if self.contains(Flags::MC0) {
+            bits |= TCC_MC0;
+        }
+        if self.contains(Flags::MC1) {
+            bits |= TCC_MC1;
+        }
+        bits
+    }
+}
+
+/// A TCC used as the timer of a [`Capture`]
+///
+/// Only the compare/capture channels 0 and 1 and the event input 1 are used.
+pub struct TccTimer<I: tcc::Instance> {
+    tcc: I,
+    freq: Hertz,
+    prescaler: Prescaler,
+}
+
+impl<I: tcc::Instance> TccTimer<I> {
+    /// Reset `tcc` and count at the frequency of `clock` divided by
+    /// `prescaler`
+    pub fn new(tcc: I, clock: &I::Clock, apb: &mut Apb, prescaler: Prescaler) -> Self {
+        I::enable_apb(apb, true);
+        let timer = Self {
+            tcc,
+            freq: I::clock_freq(clock),
+            prescaler,
+        };
+        timer.reset();
+        timer
+            .regs()
+            .ctrla
+            .write(|w| w.prescaler().variant(prescaler));
+        timer
+    }
+
+    #[inline]
+    fn regs(&self) -> &tcc::RegisterBlock {
+        self.tcc.register_block()
+    }
+
+    fn reset(&self) {
+        let regs = self.regs();
+        regs.ctrla.modify(|_, w| w.enable().clear_bit());
+        while regs.syncbusy.read().enable().bit_is_set() {}
+        regs.ctrla.write(|w| w.swrst().set_bit());
+        while regs.syncbusy.read().swrst().bit_is_set() {}
+    }
+
+    fn set_enabled(&mut self, enable: bool) {
+        let regs = self.regs();
+        regs.ctrla.modify(|_, w| w.enable().bit(enable));
+        while regs.syncbusy.read().enable().bit_is_set() {}
+    }
+
+    /// Returns the frequency of the counter, i.e. the peripheral clock
+    /// divided by the prescaler
+    pub fn freq(&self) -> Hertz {
+        Hertz(self.freq.0 / tcc::prescaler_divider(self.prescaler.into()))
+    }
+
+    /// Reset and disable the TCC, and return the underlying instance
+    pub fn free(self, apb: &mut Apb) -> I {
+        self.reset();
+        I::enable_apb(apb, false);
+        self.tcc
+    }
+}
+
+impl<I: tcc::Instance> Sealed for TccTimer<I> {}
+
+impl<I: tcc::Instance> Timer for TccTimer<I> {
+    type Word = u32;
+
+    // The event input 1 has the period and pulse width capture actions
+    const EVSYS_USER: u8 = I::EVSYS_USER + 1;
+
+    #[cfg(all(feature = "unproven", feature = "dma"))]
+    const DMA_TRIGGERS: [TriggerSource; 2] = I::DMA_TRIGGERS;
+
+    #[inline]
+    fn capture_freq(&self) -> Hertz {
+        self.freq()
+    }
+
+    fn start_capture(&mut self, order: Order) {
+        self.set_enabled(false);
+        let regs = self.regs();
+        regs.ctrla.modify(|_, w| {
+            w.cpten0().set_bit();
+            w.cpten1().set_bit()
+        });
+        // PWP only swaps the channels; the falling edge starting a period
+        // comes from the inverted event
+        regs.evctrl.write(|w| {
+            match order {
+                Order::PeriodPulseWidth => w.evact1().ppw().tcinv1().clear_bit(),
+                Order::PulseWidthPeriod => w.evact1().pwp().tcinv1().set_bit(),
+            };
+            w.tcei1().set_bit()
+        });
+        self.set_enabled(true);
+    }
+
+    fn stop_capture(&mut self) {
+        self.set_enabled(false);
+        self.regs().evctrl.reset();
+    }
+
+    #[inline]
+    fn captured(&self, channel: usize) -> u32 {
+        self.regs().cc()[channel].read().cc().bits()
+    }
+
+    #[inline]
+    fn captured_ptr(&self, channel: usize) -> *mut u32 {
+        self.regs().cc()[channel].as_ptr() as *mut _
+    }
+
+    #[inline]
+    fn capture_flags(&self) -> Flags {
+        Flags::from_tcc(self.regs().intflag.read().bits())
+    }
+
+    #[inline]
+    fn clear_capture_flags(&mut self, flags: Flags) {
+        self.regs()
+            .intflag
+            .write(|w| unsafe { w.bits(flags.to_tcc()) });
+    }
+
+    #[inline]
+    fn enable_capture_interrupts(&mut self, flags: Flags) {
+        self.regs()
+            .intenset
+            .write(|w| unsafe { w.bits(flags.to_tcc()) });
+    }
+
+    #[inline]
+    fn disable_capture_interrupts(&mut self, flags: Flags) {
+        self.regs()
+            .intenclr
+            .write(|w| unsafe { w.bits(flags.to_tcc()) });
+    }
+}
+
+impl<I: tcc::Instance> Reclock for TccTimer<I> {
+    #[inline]
+    fn clock_id(&self) -> ClockId {
+        <I::Clock as PeripheralClock>::ID
+    }
+
+    /// Store the new clock frequency, used to convert the captured values
+    #[inline]
+    fn reclock(&mut self, freq: Hertz) {
+        self.freq = freq;
+    }
+
+    #[inline]
+    fn channel_users(&self) -> u8 {
+        I::CHANNEL_USERS
+    }
+}
+
+//==============================================================================
+// Capture
+//==============================================================================
+
+/// A period and pulse width measurement, in ticks of the timer
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Measurement {
+    period: u32,
+    pulse_width: u32,
+    freq: Hertz,
+}
+
+impl Measurement {
+    /// Returns the period, in ticks of the timer
+    #[inline]
+    pub fn period_ticks(&self) -> u32 {
+        self.period
+    }
+
+    /// Returns the pulse width, in ticks of the timer
+    #[inline]
+    pub fn pulse_width_ticks(&self) -> u32 {
+        self.pulse_width
+    }
+
+    /// Returns the period
+    ///
+    /// The value saturates at `u32::MAX` nanoseconds, about 4.3 s.
+    #[inline]
+    pub fn period(&self) -> Nanoseconds {
+        ticks_to_ns(self.period, self.freq)
+    }
+
+    /// Returns the pulse width
+    ///
+    /// The value saturates at `u32::MAX` nanoseconds, about 4.3 s.
+    #[inline]
+    pub fn pulse_width(&self) -> Nanoseconds {
+        ticks_to_ns(self.pulse_width, self.freq)
+    }
+}
+
+/// Convert a number of ticks at `freq` to nanoseconds
+fn ticks_to_ns(ticks: u32, freq: Hertz) -> Nanoseconds {
+    let ns = ticks as u64 * 1_000_000_000 / freq.0 as u64;
+    Nanoseconds(if ns > u32::MAX as u64 {
+        u32::MAX
+    } else {
+        ns as u32
+    })
+}
+
+/// Period and pulse width capture, see the [module documentation](self)
+pub struct Capture<T: Timer> {
+    timer: T,
+    channel: usize,
+    order: Order,
+    #[cfg(all(feature = "unproven", feature = "dma"))]
+    dma_value: Value,
+}
+
+impl<T: Timer> Capture<T> {
+    /// Route the events of `source` to `timer` through the EVSYS `channel`,
+    /// and start capturing
+    ///
+    /// # Panics
+    ///
+    /// Panics if `channel` is not an EVSYS channel of the device.
+    pub fn new(
+        mut timer: T,
+        evsys: &mut EVSYS,
+        apb: &mut Apb,
+        channel: usize,
+        source: &impl ExternalInterrupt,
+        order: Order,
+    ) -> Self {
+        assert!(channel < evsys::NUM_CHANNELS);
+        evsys::enable_apb(apb);
+        evsys::connect(
+            evsys,
+            channel,
+            evsys::EXTINT_GENERATOR + source.id() as u8,
+            T::EVSYS_USER,
+        );
+        timer.start_capture(order);
+        Self {
+            timer,
+            channel,
+            order,
+            #[cfg(all(feature = "unproven", feature = "dma"))]
+            dma_value: Value::Period,
+        }
+    }
+
+    /// Returns the frequency of the timer, i.e. the resolution of the
+    /// captures
+    #[inline]
+    pub fn freq(&self) -> Hertz {
+        self.timer.capture_freq()
+    }
+
+    /// Returns the compare/capture channel holding `value`
+    #[inline]
+    fn value_channel(&self, value: Value) -> usize {
+        match value {
+            Value::Period => self.order.period_channel(),
+            Value::PulseWidth => 1 - self.order.period_channel(),
+        }
+    }
+
+    /// Returns the last captured `value`, in ticks of the timer
+    ///
+    /// This also clears the corresponding `MCx` flag.
+    #[inline]
+    pub fn ticks(&self, value: Value) -> u32 {
+        self.timer.captured(self.value_channel(value)).into()
+    }
+
+    /// Returns the last captured `value`
+    ///
+    /// The value saturates at `u32::MAX` nanoseconds, about 4.3 s.
+    #[inline]
+    pub fn nanoseconds(&self, value: Value) -> Nanoseconds {
+        ticks_to_ns(self.ticks(value), self.freq())
+    }
+
+    /// Read a new measurement
+    ///
+    /// Returns `WouldBlock` until both the period and the pulse width were
+    /// captured since the last read. The overflow and lost capture flags are
+    /// cleared when they are reported.
+    pub fn read(&mut self) -> nb::Result<Measurement, Error> {
+        let flags = self.read_flags();
+        if flags.contains(Flags::ERR) {
+            self.clear_flags(Flags::ERR | Flags::MC0 | Flags::MC1);
+            return Err(nb::Error::Other(Error::Lost));
+        }
+        if flags.contains(Flags::OVF) {
+            self.clear_flags(Flags::OVF);
+            return Err(nb::Error::Other(Error::Overflow));
+        }
+        if !flags.contains(Flags::MC0 | Flags::MC1) {
+            return Err(nb::Error::WouldBlock);
+        }
+        // Reading the channels clears the MCx flags
+        Ok(Measurement {
+            period: self.ticks(Value::Period),
+            pulse_width: self.ticks(Value::PulseWidth),
+            freq: self.freq(),
+        })
+    }
+
+    /// Read the interrupt flags
+    #[inline]
+    pub fn read_flags(&self) -> Flags {
+        self.timer.capture_flags()
+    }
+
+    /// Clear the interrupt flags
+    #[inline]
+    pub fn clear_flags(&mut self, flags: Flags) {
+        self.timer.clear_capture_flags(flags);
+    }
+
+    /// Enable the interrupts
+    #[inline]
+    pub fn enable_interrupts(&mut self, flags: Flags) {
+        self.timer.enable_capture_interrupts(flags);
+    }
+
+    /// Disable the interrupts
+    #[inline]
+    pub fn disable_interrupts(&mut self, flags: Flags) {
+        self.timer.disable_capture_interrupts(flags);
+    }
+
+    /// Stop capturing, disconnect the EVSYS channel, and return the timer
+    pub fn free(mut self, evsys: &mut EVSYS) -> T {
+        self.timer.stop_capture();
+        evsys::disconnect(evsys, self.channel, T::EVSYS_USER);
+        self.timer
+    }
+}
+
+//==============================================================================
+// DMA
+//==============================================================================
+
+#[cfg(all(feature = "unproven", feature = "dma"))]
+unsafe impl<T> Buffer for Capture<T>
+where
+    T: Timer,
+    T::Word: Beat,
+{
+    type Beat = T::Word;
+
+    #[inline]
+    fn dma_ptr(&mut self) -> *mut Self::Beat {
+        self.timer.captured_ptr(self.value_channel(self.dma_value))
+    }
+
+    #[inline]
+    fn incrementing(&self) -> bool {
+        false
+    }
+
+    #[inline]
+    fn buffer_len(&self) -> usize {
+        1
+    }
+}
+
+#[cfg(all(feature = "unproven", feature = "dma"))]
+impl<T> Capture<T>
+where
+    T: Timer,
+    T::Word: Beat,
+{
+    /// Transform the [`Capture`] into a DMA [`Transfer`], copying each
+    /// captured `value`, in ticks, into `buf`
+    ///
+    /// The [`read`](Self::read) method is not usable during the transfer,
+    /// since the DMA clears the capture flags of `value`.
+    #[inline]
+    pub fn capture_with_dma<Ch, B, W>(
+        mut self,
+        value: Value,
+        buf: B,
+        mut channel: Ch,
+        waker: W,
+    ) -> Transfer<Channel<Ch::Id, Busy>, BufferPair<Self, B>, W>
+    where
+        Ch: AnyChannel<Status = Ready>,
+        B: Buffer<Beat = T::Word> + 'static,
+        W: FnOnce(CallbackStatus) + 'static,
+    {
+        self.dma_value = value;
+        let trigger = T::DMA_TRIGGERS[self.value_channel(value)];
+
+        channel
+            .as_mut()
+            .enable_interrupts(InterruptFlags::new().with_tcmpl(true));
+
+        #[cfg(feature = "min-samd51g")]
+        let trigger_action = TriggerAction::BURST;
+
+        #[cfg(any(feature = "samd11", feature = "samd21"))]
+        let trigger_action = TriggerAction::BEAT;
+
+        // SAFETY: This is safe because of the `'static` bound check for `B`,
+        // and the fact that the buffer length of a `Capture` is always 1.
+        let xfer = unsafe { dmac::Transfer::new_unchecked(channel, self, buf, false) };
+        xfer.with_waker(waker).begin(trigger, trigger_action)
+    }
+}
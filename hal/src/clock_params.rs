@@ -0,0 +1,402 @@
+//! helper structs to calculate GCLK divider & DPLL ratio settings.
+//!
+//! The solvers are pure functions of the source and target frequencies and of
+//! the chip limits, described by [`GclkLimits`] and [`DpllLimits`]. Each
+//! clock module provides the limits of its chip family.
+use core::ops::RangeInclusive;
+
+use crate::time::Hertz;
+
+/// Errors returned by the solvers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClockParamsError {
+    /// The source or target frequency is zero
+    ZeroFrequency,
+    /// No legal setting keeps the reference and output frequencies within the
+    /// limits
+    OutOfBounds,
+}
+
+/// Limits of a GCLK generator
+#[derive(Debug, Clone, Copy)]
+pub struct GclkLimits {
+    /// Width of the `DIV` field
+    pub div_bits: u8,
+    /// Maximum output frequency of the generator
+    pub max_freq: Hertz,
+}
+
+/// Divider settings of a GCLK generator
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GclkParams {
+    /// Value of the `DIV` field
+    pub div: u16,
+    /// Value of the `DIVSEL` bit; the source is divided by `DIV` when clear,
+    /// and by `2^(DIV + 1)` when set
+    pub divsel: bool,
+    /// Achieved output frequency
+    pub freq: Hertz,
+    /// Absolute difference between the achieved and target frequencies
+    pub error: Hertz,
+}
+
+impl GclkParams {
+    /// calculates the divider that brings `src_freq` closest to `target`.
+    ///
+    /// Linear dividers are preferred over power of two dividers with the same
+    /// result.
+    pub fn new<S, T>(src_freq: S, target: T, limits: &GclkLimits) -> Result<Self, ClockParamsError>
+    where
+        S: Into<Hertz>,
+        T: Into<Hertz>,
+    {
+        let src = src_freq.into().0;
+        let target = target.into().0;
+        if src == 0 || target == 0 {
+            return Err(ClockParamsError::ZeroFrequency);
+        }
+        if target > limits.max_freq.0 {
+            return Err(ClockParamsError::OutOfBounds);
+        }
+
+        let max_div = (1_u32 << limits.div_bits) - 1;
+        let nearest = ((src + target / 2) / target).clamp(1, max_div);
+        let linear = [nearest - 1, nearest, nearest + 1]
+            .into_iter()
+            .filter(|d| (1..=max_div).contains(d))
+            .map(|d| (d, false, d));
+        let exponential = (0..=limits.div_bits as u32).map(|d| (d, true, 2_u32 << d));
+
+        let mut best: Option<Self> = None;
+        for (div, divsel, factor) in linear.chain(exponential) {
+            let freq = src / factor;
+            if freq == 0 || freq > limits.max_freq.0 {
+                continue;
+            }
+            let error = abs_diff(freq, target);
+            if best.map_or(true, |b| error < b.error.0) {
+                best = Some(Self {
+                    div: div as u16,
+                    divsel,
+                    freq: Hertz(freq),
+                    error: Hertz(error),
+                });
+            }
+        }
+        best.ok_or(ClockParamsError::OutOfBounds)
+    }
+
+    /// Returns the division factor applied to the source
+    pub fn factor(&self) -> u32 {
+        if self.divsel {
+            2 << self.div
+        } else {
+            (self.div as u32).max(1)
+        }
+    }
+
+    /// Returns the output frequency of a generator dividing `src_freq` with
+    /// these settings
+    pub fn output_freq(&self, src_freq: impl Into<Hertz>) -> Hertz {
+        Hertz(src_freq.into().0 / self.factor())
+    }
+}
+
+/// Limits of a DPLL
+#[derive(Debug, Clone)]
+pub struct DpllLimits {
+    /// Largest value of the `LDR` field
+    pub max_ldr: u16,
+    /// Number of `LDRFRAC` steps per unit of the ratio
+    pub ldrfrac_steps: u8,
+    /// Largest value of the XOSC reference `DIV` field
+    pub max_xosc_div: u16,
+    /// Allowed range of the reference frequency
+    pub ref_freq: RangeInclusive<u32>,
+    /// Allowed range of the output frequency
+    pub out_freq: RangeInclusive<u32>,
+}
+
+/// Ratio settings of a DPLL
+///
+/// The output frequency is `f_ref * (LDR + 1 + LDRFRAC / steps)`, where
+/// `f_ref` is the reference frequency, divided by `2 * (xosc_div + 1)` for an
+/// XOSC reference.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DpllParams {
+    /// Value of the `LDR` field
+    pub ldr: u16,
+    /// Value of the `LDRFRAC` field
+    pub ldrfrac: u8,
+    /// Value of the XOSC reference `DIV` field, zero for other references
+    pub xosc_div: u16,
+    /// Achieved output frequency
+    pub freq: Hertz,
+    /// Absolute difference between the achieved and target frequencies
+    pub error: Hertz,
+}
+
+impl DpllParams {
+    /// calculates the ratio that brings the reference `ref_freq` closest to
+    /// `target`.
+    ///
+    /// Use this for XOSC32K and GCLK references, which are not divided.
+    pub fn new<R, T>(ref_freq: R, target: T, limits: &DpllLimits) -> Result<Self, ClockParamsError>
+    where
+        R: Into<Hertz>,
+        T: Into<Hertz>,
+    {
+        let target = target.into().0;
+        Self::solve_ratio(ref_freq.into().0, target, limits).map(|(ldr, ldrfrac, freq)| Self {
+            ldr,
+            ldrfrac,
+            xosc_div: 0,
+            freq: Hertz(freq),
+            error: Hertz(abs_diff(freq, target)),
+        })
+    }
+
+    /// calculates the XOSC divider and ratio that bring the XOSC frequency
+    /// `xosc_freq` closest to `target`.
+    ///
+    /// The smallest divider achieving the lowest error is chosen.
+    pub fn new_xosc<X, T>(
+        xosc_freq: X,
+        target: T,
+        limits: &DpllLimits,
+    ) -> Result<Self, ClockParamsError>
+    where
+        X: Into<Hertz>,
+        T: Into<Hertz>,
+    {
+        let xosc = xosc_freq.into().0;
+        let target = target.into().0;
+        if xosc == 0 || target == 0 {
+            return Err(ClockParamsError::ZeroFrequency);
+        }
+        let mut best: Option<Self> = None;
+        for xosc_div in 0..=limits.max_xosc_div {
+            let ref_freq = xosc / (2 * (xosc_div as u32 + 1));
+            if ref_freq < *limits.ref_freq.start() {
+                break;
+            }
+            if let Ok((ldr, ldrfrac, freq)) = Self::solve_ratio(ref_freq, target, limits) {
+                let error = abs_diff(freq, target);
+                if best.map_or(true, |b| error < b.error.0) {
+                    best = Some(Self {
+                        ldr,
+                        ldrfrac,
+                        xosc_div,
+                        freq: Hertz(freq),
+                        error: Hertz(error),
+                    });
+                }
+            }
+        }
+        best.ok_or(ClockParamsError::OutOfBounds)
+    }
+
+    /// Returns `(LDR, LDRFRAC, output frequency)` for a reference frequency
+    fn solve_ratio(
+        ref_freq: u32,
+        target: u32,
+        limits: &DpllLimits,
+    ) -> Result<(u16, u8, u32), ClockParamsError> {
+        if ref_freq == 0 || target == 0 {
+            return Err(ClockParamsError::ZeroFrequency);
+        }
+        if !limits.ref_freq.contains(&ref_freq) || !limits.out_freq.contains(&target) {
+            return Err(ClockParamsError::OutOfBounds);
+        }
+
+        // Work in units of 1 / steps of the ratio
+        let steps = limits.ldrfrac_steps as u64;
+        let fref = ref_freq as u64;
+        let min = steps.max((*limits.out_freq.start() as u64 * steps + fref - 1) / fref);
+        let max = ((limits.max_ldr as u64 + 1) * steps + steps - 1)
+            .min(*limits.out_freq.end() as u64 * steps / fref);
+        if min > max {
+            return Err(ClockParamsError::OutOfBounds);
+        }
+        let n = ((target as u64 * steps + fref / 2) / fref).clamp(min, max);
+
+        let ldr = (n / steps - 1) as u16;
+        let ldrfrac = (n % steps) as u8;
+        let freq = (fref * n / steps) as u32;
+        Ok((ldr, ldrfrac, freq))
+    }
+}
+
+fn abs_diff(a: u32, b: u32) -> u32 {
+    if a > b {
+        a - b
+    } else {
+        b - a
+    }
+}
+
+/// Output settings of a generator on its GCLK_IO pin
+#[derive(Clone, Copy, Debug)]
+pub struct GclkOutConfig {
+    pub(crate) output_enable: bool,
+    pub(crate) off_value: bool,
+    pub(crate) improve_duty_cycle: bool,
+}
+
+impl Default for GclkOutConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GclkOutConfig {
+    /// Output the clock, driving the pin low while the generator is off,
+    /// and keep the duty cycle of odd dividers unchanged
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            output_enable: true,
+            off_value: false,
+            improve_duty_cycle: false,
+        }
+    }
+
+    /// Output the clock on the pin (`GENCTRL.OE`); when disabled, the pin is
+    /// driven to the off value
+    #[inline]
+    pub fn output_enable(mut self, enable: bool) -> Self {
+        self.output_enable = enable;
+        self
+    }
+
+    /// Set the level of the pin while the generator or its output is off
+    /// (`GENCTRL.OOV`)
+    #[inline]
+    pub fn off_value(mut self, high: bool) -> Self {
+        self.off_value = high;
+        self
+    }
+
+    /// Get a 50/50 duty cycle for odd dividers (`GENCTRL.IDC`)
+    ///
+    /// This applies to the generator itself, so to every peripheral it feeds.
+    #[inline]
+    pub fn improve_duty_cycle(mut self, improve: bool) -> Self {
+        self.improve_duty_cycle = improve;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::clock_params::*;
+    use crate::time::U32Ext;
+
+    fn samd21_dpll() -> DpllLimits {
+        DpllLimits {
+            max_ldr: 0xFFF,
+            ldrfrac_steps: 16,
+            max_xosc_div: 0x7FF,
+            ref_freq: 32_000..=2_000_000,
+            out_freq: 48_000_000..=96_000_000,
+        }
+    }
+
+    fn samd51_dpll() -> DpllLimits {
+        DpllLimits {
+            max_ldr: 0x1FFF,
+            ldrfrac_steps: 32,
+            max_xosc_div: 0x7FF,
+            ref_freq: 32_000..=3_200_000,
+            out_freq: 96_000_000..=200_000_000,
+        }
+    }
+
+    const GCLK: GclkLimits = GclkLimits {
+        div_bits: 8,
+        max_freq: Hertz(200_000_000),
+    };
+
+    #[test]
+    fn gclk_exact_linear_divider() {
+        let p = GclkParams::new(48.mhz(), 8.mhz(), &GCLK).unwrap();
+        assert_eq!((p.div, p.divsel), (6, false));
+        assert_eq!(p.freq, Hertz(8_000_000));
+        assert_eq!(p.error, Hertz(0));
+        assert_eq!(p.factor(), 6);
+    }
+
+    #[test]
+    fn gclk_large_divider_uses_divsel() {
+        // 48 MHz / 512 = 93.75 kHz needs more than 8 linear bits
+        let p = GclkParams::new(48.mhz(), 93_750.hz(), &GCLK).unwrap();
+        assert_eq!((p.div, p.divsel), (8, true));
+        assert_eq!(p.factor(), 512);
+        assert_eq!(p.error, Hertz(0));
+    }
+
+    #[test]
+    fn gclk_output_freq_is_divided() {
+        let p = GclkParams::new(48.mhz(), 12.mhz(), &GCLK).unwrap();
+        assert_eq!(p.output_freq(48.mhz()), Hertz(12_000_000));
+        let p = GclkParams::new(48.mhz(), 93_750.hz(), &GCLK).unwrap();
+        assert_eq!(p.output_freq(48.mhz()), Hertz(93_750));
+    }
+
+    #[test]
+    fn gclk_rejects_out_of_bounds() {
+        let limits = GclkLimits {
+            div_bits: 8,
+            max_freq: Hertz(120_000_000),
+        };
+        assert_eq!(
+            GclkParams::new(200.mhz(), 150.mhz(), &limits),
+            Err(ClockParamsError::OutOfBounds)
+        );
+        assert_eq!(
+            GclkParams::new(0.hz(), 1.mhz(), &limits),
+            Err(ClockParamsError::ZeroFrequency)
+        );
+    }
+
+    #[test]
+    fn dpll_samd21_48mhz_from_32k() {
+        let p = DpllParams::new(32_768.hz(), 48.mhz(), &samd21_dpll()).unwrap();
+        // 32768 * (1463 + 1 + 14/16) = 48_001_024
+        assert_eq!((p.ldr, p.ldrfrac), (1463, 14));
+        assert_eq!(p.freq, Hertz(48_001_024));
+        assert_eq!(p.error, Hertz(1024));
+    }
+
+    #[test]
+    fn dpll_samd21_48mhz_from_16mhz_xosc() {
+        let p = DpllParams::new_xosc(16.mhz(), 48.mhz(), &samd21_dpll()).unwrap();
+        assert_eq!(p.error, Hertz(0));
+        assert_eq!(p.freq, Hertz(48_000_000));
+        // 16 MHz / 2 / (xosc_div + 1) must be a valid reference
+        let ref_freq = 16_000_000 / (2 * (p.xosc_div as u32 + 1));
+        assert!(samd21_dpll().ref_freq.contains(&ref_freq));
+        assert_eq!(p.xosc_div, 3);
+        assert_eq!((p.ldr, p.ldrfrac), (23, 0));
+    }
+
+    #[test]
+    fn dpll_samd51_120mhz_from_12mhz_xosc() {
+        let p = DpllParams::new_xosc(12.mhz(), 120.mhz(), &samd51_dpll()).unwrap();
+        assert_eq!(p.freq, Hertz(120_000_000));
+        assert_eq!(p.xosc_div, 1);
+        assert_eq!((p.ldr, p.ldrfrac), (39, 0));
+    }
+
+    #[test]
+    fn dpll_rejects_out_of_bounds() {
+        assert_eq!(
+            DpllParams::new(32_768.hz(), 48.mhz(), &samd51_dpll()),
+            Err(ClockParamsError::OutOfBounds)
+        );
+        assert_eq!(
+            DpllParams::new(4.mhz(), 120.mhz(), &samd51_dpll()),
+            Err(ClockParamsError::OutOfBounds)
+        );
+    }
+}
@@ -5,7 +5,7 @@ use cortex_m::peripheral::SYST;
 
 use crate::clock::GenericClockController;
 use crate::ehal::blocking::delay::{DelayMs, DelayUs};
-use crate::time::Hertz;
+use crate::time::{Hertz, Microseconds};
 
 /// System timer (SysTick) as a delay provider
 pub struct Delay {
@@ -28,6 +28,11 @@ impl Delay {
     pub fn free(self) -> SYST {
         self.syst
     }
+
+    /// Delay for `duration`, e.g. a `fugit` duration
+    pub fn delay(&mut self, duration: impl Into<Microseconds>) {
+        self.delay_us(duration.into().0);
+    }
 }
 
 impl DelayMs<u32> for Delay {
@@ -87,3 +92,11 @@ impl DelayUs<u8> for Delay {
         self.delay_us(us as u32)
     }
 }
+
+#[cfg(feature = "ehal-1")]
+impl crate::ehal_1::delay::DelayNs for Delay {
+    /// Delay for `ns` nanoseconds, rounded up to the next microsecond
+    fn delay_ns(&mut self, ns: u32) {
+        DelayUs::delay_us(self, ns / 1_000 + u32::from(ns % 1_000 != 0));
+    }
+}
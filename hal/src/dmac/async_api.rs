@@ -0,0 +1,45 @@
+//! Interrupt handling for the async DMA transfers
+
+use super::NUM_CHANNELS;
+use crate::pac::DMAC;
+use crate::waker::WakerCell;
+
+/// Channel interrupts enabled by [`Channel::transfer_future`]
+///
+/// [`Channel::transfer_future`]: super::Channel::transfer_future
+const CHINT_MASK: u8 = 0x03;
+
+#[allow(clippy::declare_interior_mutable_const)]
+const NEW_WAKER: WakerCell = WakerCell::new();
+pub(super) static DMA_WAKERS: [WakerCell; NUM_CHANNELS] = [NEW_WAKER; NUM_CHANNELS];
+
+/// Wake the tasks waiting on a DMA channel
+///
+/// This must be called from the `DMAC` interrupt handler(s). The interrupts of
+/// the channels with a pending interrupt are disabled; their flags are
+/// checked and cleared by the woken task.
+pub fn on_interrupt() {
+    // SAFETY: only the interrupt enable registers are written, and CHID is
+    // restored before returning
+    let dmac = unsafe { &*DMAC::ptr() };
+    let pending = dmac.intstatus.read().bits();
+    for (id, waker) in DMA_WAKERS.iter().enumerate() {
+        if pending & (1 << id) == 0 {
+            continue;
+        }
+        #[cfg(any(feature = "samd11", feature = "samd21"))]
+        {
+            let old_id = dmac.chid.read().id().bits();
+            unsafe {
+                dmac.chid.write(|w| w.id().bits(id as u8));
+                dmac.chintenclr.write(|w| w.bits(CHINT_MASK));
+                dmac.chid.write(|w| w.id().bits(old_id));
+            }
+        }
+        #[cfg(feature = "min-samd51g")]
+        dmac.channel[id]
+            .chintenclr
+            .write(|w| unsafe { w.bits(CHINT_MASK) });
+        waker.wake();
+    }
+}
@@ -38,6 +38,15 @@ use crate::typelevel::{Is, Sealed};
 use core::marker::PhantomData;
 use modular_bitfield::prelude::*;
 
+#[cfg(feature = "async")]
+use super::{
+    async_api::DMA_WAKERS,
+    transfer::{check_buffer_pair, fill_descriptor},
+    Buffer, Error,
+};
+#[cfg(feature = "async")]
+use core::{future::poll_fn, sync::atomic, task::Poll};
+
 mod reg;
 
 use reg::RegisterBlock;
@@ -249,6 +258,16 @@ impl<Id: ChId> Channel<Id, Ready> {
         trig_src: TriggerSource,
         trig_act: TriggerAction,
     ) -> Channel<Id, Busy> {
+        self._start_private(trig_src, trig_act);
+
+        Channel {
+            regs: self.regs,
+            _status: PhantomData,
+        }
+    }
+
+    #[inline]
+    fn _start_private(&mut self, trig_src: TriggerSource, trig_act: TriggerAction) {
         // Configure the trigger source and trigger action
         // SAFETY: This is actually safe because we are writing the correct enum value
         // (imported from the PAC) into the register
@@ -273,11 +292,79 @@ impl<Id: ChId> Channel<Id, Ready> {
         if trig_src == TriggerSource::DISABLE {
             self._trigger_private();
         }
+    }
 
-        Channel {
-            regs: self.regs,
-            _status: PhantomData,
-        }
+    /// Transfer `source` to `destination` using the specified trigger source,
+    /// and wait for the transfer to complete asynchronously.
+    ///
+    /// The DMAC interrupt handler must call
+    /// [`dmac::on_interrupt`](super::on_interrupt). The transfer is stopped if
+    /// the returned future is dropped before completion.
+    ///
+    /// # Safety
+    ///
+    /// The buffers must stay valid until the transfer is complete or stopped.
+    /// In particular, the returned future must not be leaked with
+    /// [`core::mem::forget`] while the transfer is in progress.
+    #[cfg(feature = "async")]
+    pub async unsafe fn transfer_future<S, D>(
+        &mut self,
+        mut source: S,
+        mut destination: D,
+        trig_src: TriggerSource,
+        trig_act: TriggerAction,
+    ) -> Result<(), Error>
+    where
+        S: Buffer,
+        D: Buffer<Beat = S::Beat>,
+    {
+        check_buffer_pair(&source, &destination)?;
+        fill_descriptor(Id::USIZE, &mut source, &mut destination, false);
+
+        let flags = InterruptFlags::new().with_tcmpl(true).with_terr(true);
+        self.check_and_clear_interrupts(flags);
+
+        // Make sure the descriptor is written before the channel is enabled
+        atomic::fence(atomic::Ordering::Release);
+        self._start_private(trig_src, trig_act);
+
+        let channel = StopOnDrop(self);
+        poll_fn(|cx| {
+            DMA_WAKERS[Id::USIZE].register(cx.waker());
+            let status = channel.0.check_and_clear_interrupts(flags);
+            if status.tcmpl() {
+                Poll::Ready(Ok(()))
+            } else if status.terr() {
+                Poll::Ready(Err(Error::TransferError))
+            } else {
+                channel.0.enable_interrupts(flags);
+                Poll::Pending
+            }
+        })
+        .await
+    }
+
+    /// Stop the transfer in progress, if any
+    #[cfg(feature = "async")]
+    #[inline]
+    fn _stop_private(&mut self) {
+        self.regs.chctrla.modify(|_, w| w.enable().clear_bit());
+        while self.regs.chctrla.read().enable().bit_is_set() {}
+    }
+}
+
+/// Stop the transfer of an async channel, when its future completes or is
+/// dropped
+#[cfg(feature = "async")]
+struct StopOnDrop<'a, Id: ChId>(&'a mut Channel<Id, Ready>);
+
+#[cfg(feature = "async")]
+impl<Id: ChId> Drop for StopOnDrop<'_, Id> {
+    #[inline]
+    fn drop(&mut self) {
+        self.0
+            .disable_interrupts(InterruptFlags::new().with_tcmpl(true).with_terr(true));
+        self.0._stop_private();
     }
 }
 
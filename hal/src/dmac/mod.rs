@@ -247,7 +247,18 @@
 //! }
 //! ```
 //! [RTIC]: https://rtic.rs
+#![cfg_attr(
+    feature = "async",
+    doc = "
+# Async transfers
 
+With the `async` feature, [`Channel::transfer_future`] starts a transfer and
+waits for its completion asynchronously. The `DMAC` interrupt handler(s) must
+call [`on_interrupt`], which wakes the tasks waiting on the channels with a
+pending interrupt. Channels used with [`Transfer`] callbacks should not be
+mixed with `on_interrupt`.
+"
+)]
 // This is necessary until modular_bitfield fixes all their identity_op warnings
 #![allow(clippy::identity_op)]
 
@@ -257,7 +268,7 @@ pub use channel::*;
 pub use dma_controller::*;
 pub use transfer::*;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 /// Runtime errors that may occur when dealing with DMA transfers.
 pub enum Error {
     /// Supplied buffers both have lengths > 1 beat, but not equal to each other
@@ -271,6 +282,9 @@ pub enum Error {
 
     /// Operation is not valid in the current state of the object.
     InvalidState,
+
+    /// The DMAC reported a bus error during the transfer.
+    TransferError,
 }
 
 /// Result for DMAC operations
@@ -394,3 +408,8 @@ static mut DESCRIPTOR_SECTION: [DmacDescriptor; NUM_CHANNELS] = [DEFAULT_DESCRIP
 pub mod channel;
 pub mod dma_controller;
 pub mod transfer;
+
+#[cfg(feature = "async")]
+mod async_api;
+#[cfg(feature = "async")]
+pub use async_api::on_interrupt;
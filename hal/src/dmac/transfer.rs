@@ -340,7 +340,7 @@ where
         destination: D,
         circular: bool,
     ) -> Result<Transfer<C, BufferPair<S, D>>> {
-        Self::check_buffer_pair(&source, &destination)?;
+        check_buffer_pair(&source, &destination)?;
 
         // SAFETY: The safety checks are done by the function signature and the buffer
         // length verification
@@ -348,82 +348,85 @@ where
     }
 }
 
-impl<S, D, C, W> Transfer<C, BufferPair<S, D>, W>
-where
+/// Check that the lengths of the `source` and `destination` buffers are
+/// compatible
+#[inline]
+pub(super) fn check_buffer_pair<S: Buffer, D: Buffer>(source: &S, destination: &D) -> Result<()> {
+    let src_len = source.buffer_len();
+    let dst_len = destination.buffer_len();
+
+    if src_len > 1 && dst_len > 1 && src_len != dst_len {
+        Err(Error::LengthMismatch)
+    } else {
+        Ok(())
+    }
+}
+
+/// Write the descriptor of the channel `id` for a transfer from `source` to
+/// `destination`
+#[inline]
+pub(super) unsafe fn fill_descriptor<S, D>(
+    id: usize,
+    source: &mut S,
+    destination: &mut D,
+    circular: bool,
+) where
     S: Buffer,
     D: Buffer<Beat = S::Beat>,
-    C: AnyChannel,
 {
-    #[inline]
-    fn check_buffer_pair(source: &S, destination: &D) -> Result<()> {
-        let src_len = source.buffer_len();
-        let dst_len = destination.buffer_len();
-
-        if src_len > 1 && dst_len > 1 && src_len != dst_len {
-            Err(Error::LengthMismatch)
-        } else {
-            Ok(())
-        }
-    }
-
-    #[inline]
-    unsafe fn fill_descriptor(source: &mut S, destination: &mut D, circular: bool) {
-        let id = <C as AnyChannel>::Id::USIZE;
-
-        // Enable support for circular transfers. If circular_xfer is true,
-        // we set the address of the "next" block descriptor to actually
-        // be the same address as the current block descriptor.
-        // Otherwise we set it to NULL, which terminates the transaction.
-        // TODO: Enable support for linked lists (?)
-        let descaddr = if circular {
-            // SAFETY This is safe as we are only reading the descriptor's address,
-            // and not actually writing any data to it. We also assume the descriptor
-            // will never be moved.
-            &mut DESCRIPTOR_SECTION[id] as *mut _
-        } else {
-            null_mut()
-        };
+    // Enable support for circular transfers. If circular_xfer is true,
+    // we set the address of the "next" block descriptor to actually
+    // be the same address as the current block descriptor.
+    // Otherwise we set it to NULL, which terminates the transaction.
+    // TODO: Enable support for linked lists (?)
+    let descaddr = if circular {
+        // SAFETY This is safe as we are only reading the descriptor's address,
+        // and not actually writing any data to it. We also assume the descriptor
+        // will never be moved.
+        &mut DESCRIPTOR_SECTION[id] as *mut _
+    } else {
+        null_mut()
+    };
 
-        let src_ptr = source.dma_ptr();
-        let src_inc = source.incrementing();
-        let src_len = source.buffer_len();
-
-        let dst_ptr = destination.dma_ptr();
-        let dst_inc = destination.incrementing();
-        let dst_len = destination.buffer_len();
-
-        let length = core::cmp::max(src_len, dst_len);
-
-        // Channel::xfer_complete() tests the channel enable bit, which indicates
-        // that a transfer has completed iff the blockact field in btctrl is not
-        // set to SUSPEND.  We implicitly leave blockact set to NOACT here; if
-        // that changes Channel::xfer_complete() may need to be modified.
-        let btctrl = BlockTransferControl::new()
-            .with_srcinc(src_inc)
-            .with_dstinc(dst_inc)
-            .with_beatsize(S::Beat::BEATSIZE)
-            .with_valid(true);
-
-        let xfer_descriptor = DmacDescriptor {
-            // Next descriptor address:  0x0 terminates the transaction (no linked list),
-            // any other address points to the next block descriptor
-            descaddr,
-            // Source address: address of the last beat transfer source in block
-            srcaddr: src_ptr as *mut _,
-            // Destination address: address of the last beat transfer destination in block
-            dstaddr: dst_ptr as *mut _,
-            // Block transfer count: number of beats in block transfer
-            btcnt: length as u16,
-            // Block transfer control: Datasheet  section 19.8.2.1 p.329
-            btctrl,
-        };
+    let src_ptr = source.dma_ptr();
+    let src_inc = source.incrementing();
+    let src_len = source.buffer_len();
+
+    let dst_ptr = destination.dma_ptr();
+    let dst_inc = destination.incrementing();
+    let dst_len = destination.buffer_len();
+
+    let length = core::cmp::max(src_len, dst_len);
+
+    // Channel::xfer_complete() tests the channel enable bit, which indicates
+    // that a transfer has completed iff the blockact field in btctrl is not
+    // set to SUSPEND.  We implicitly leave blockact set to NOACT here; if
+    // that changes Channel::xfer_complete() may need to be modified.
+    let btctrl = BlockTransferControl::new()
+        .with_srcinc(src_inc)
+        .with_dstinc(dst_inc)
+        .with_beatsize(S::Beat::BEATSIZE)
+        .with_valid(true);
+
+    let xfer_descriptor = DmacDescriptor {
+        // Next descriptor address:  0x0 terminates the transaction (no linked list),
+        // any other address points to the next block descriptor
+        descaddr,
+        // Source address: address of the last beat transfer source in block
+        srcaddr: src_ptr as *mut _,
+        // Destination address: address of the last beat transfer destination in block
+        dstaddr: dst_ptr as *mut _,
+        // Block transfer count: number of beats in block transfer
+        btcnt: length as u16,
+        // Block transfer control: Datasheet  section 19.8.2.1 p.329
+        btctrl,
+    };
 
-        // SAFETY this is safe as long as we ONLY write to the descriptor
-        // belonging to OUR channel. We assume this is the only place
-        // in the entire library that this section or the array
-        // will be written to.
-        DESCRIPTOR_SECTION[id] = xfer_descriptor;
-    }
+    // SAFETY this is safe as long as we ONLY write to the descriptor
+    // belonging to OUR channel. We assume this is the only place
+    // in the entire library that this section or the array
+    // will be written to.
+    DESCRIPTOR_SECTION[id] = xfer_descriptor;
 }
 
 impl<C, S, D> Transfer<C, BufferPair<S, D>>
@@ -453,7 +456,12 @@ where
         mut destination: D,
         circular: bool,
     ) -> Transfer<C, BufferPair<S, D>> {
-        Self::fill_descriptor(&mut source, &mut destination, circular);
+        fill_descriptor(
+            <C as AnyChannel>::Id::USIZE,
+            &mut source,
+            &mut destination,
+            circular,
+        );
 
         let buffers = BufferPair {
             source,
@@ -625,7 +633,7 @@ where
     /// mismatched or if the previous transfer has not yet completed.
     #[inline]
     pub fn recycle(&mut self, mut source: S, mut destination: D) -> Result<(S, D)> {
-        Self::check_buffer_pair(&source, &destination)?;
+        check_buffer_pair(&source, &destination)?;
 
         if !self.complete() {
             return Err(Error::InvalidState);
@@ -633,7 +641,12 @@ where
 
         // Circular transfers won't ever complete, so never re-fill as one
         unsafe {
-            Self::fill_descriptor(&mut source, &mut destination, false);
+            fill_descriptor(
+                <C as AnyChannel>::Id::USIZE,
+                &mut source,
+                &mut destination,
+                false,
+            );
         }
 
         let new_buffers = BufferPair {
@@ -655,7 +668,7 @@ where
     /// mismatched or if the previous transfer has not yet completed.
     #[inline]
     pub fn recycle_source(&mut self, mut destination: D) -> Result<D> {
-        Self::check_buffer_pair(&self.buffers.source, &destination)?;
+        check_buffer_pair(&self.buffers.source, &destination)?;
 
         if !self.complete() {
             return Err(Error::InvalidState);
@@ -663,7 +676,12 @@ where
 
         // Circular transfers won't ever complete, so never re-fill as one
         unsafe {
-            Self::fill_descriptor(&mut self.buffers.source, &mut destination, false);
+            fill_descriptor(
+                <C as AnyChannel>::Id::USIZE,
+                &mut self.buffers.source,
+                &mut destination,
+                false,
+            );
         }
 
         let old_destination = core::mem::replace(&mut self.buffers.destination, destination);
@@ -680,7 +698,7 @@ where
     /// mismatched or if the previous transfer has not yet completed.
     #[inline]
     pub fn recycle_destination(&mut self, mut source: S) -> Result<S> {
-        Self::check_buffer_pair(&source, &self.buffers.destination)?;
+        check_buffer_pair(&source, &self.buffers.destination)?;
 
         if !self.complete() {
             return Err(Error::InvalidState);
@@ -688,7 +706,12 @@ where
 
         // Circular transfers won't ever complete, so never re-fill as one
         unsafe {
-            Self::fill_descriptor(&mut source, &mut self.buffers.destination, false);
+            fill_descriptor(
+                <C as AnyChannel>::Id::USIZE,
+                &mut source,
+                &mut self.buffers.destination,
+                false,
+            );
         }
 
         let old_source = core::mem::replace(&mut self.buffers.source, source);
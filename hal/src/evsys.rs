@@ -0,0 +1,74 @@
+//! Minimal EVSYS routing, shared by the drivers using peripheral events
+//!
+//! Only the asynchronous path is used, so that a channel needs no clock.
+
+use crate::pac::EVSYS;
+
+#[cfg(feature = "min-samd51g")]
+use crate::pac::MCLK as Apb;
+#[cfg(any(feature = "samd11", feature = "samd21"))]
+use crate::pac::PM as Apb;
+
+/// Number of EVSYS channels
+#[cfg(feature = "samd11")]
+pub(crate) const NUM_CHANNELS: usize = 6;
+#[cfg(feature = "samd21")]
+pub(crate) const NUM_CHANNELS: usize = 12;
+#[cfg(feature = "min-samd51g")]
+pub(crate) const NUM_CHANNELS: usize = 32;
+
+/// EVSYS generator of the EIC `EXTINT0` event, the following lines have
+/// consecutive generators
+#[cfg(any(feature = "samd11", feature = "samd21"))]
+pub(crate) const EXTINT_GENERATOR: u8 = 0x0C;
+#[cfg(feature = "min-samd51g")]
+pub(crate) const EXTINT_GENERATOR: u8 = 0x12;
+
+#[inline]
+pub(crate) fn enable_apb(apb: &mut Apb) {
+    #[cfg(feature = "min-samd51g")]
+    apb.apbbmask.modify(|_, w| w.evsys_().set_bit());
+    #[cfg(any(feature = "samd11", feature = "samd21"))]
+    apb.apbcmask.modify(|_, w| w.evsys_().set_bit());
+}
+
+/// Connect the `generator` to the `user` through the EVSYS `channel`, on the
+/// asynchronous path
+pub(crate) fn connect(evsys: &mut EVSYS, channel: usize, generator: u8, user: u8) {
+    #[cfg(feature = "min-samd51g")]
+    {
+        evsys.channel[channel].channel.write(|w| unsafe {
+            w.evgen().bits(generator);
+            w.path().asynchronous()
+        });
+        evsys.user[user as usize].write(|w| unsafe { w.channel().bits(channel as u8 + 1) });
+    }
+    #[cfg(any(feature = "samd11", feature = "samd21"))]
+    {
+        evsys.channel.write(|w| unsafe {
+            w.channel().bits(channel as u8);
+            w.evgen().bits(generator);
+            w.path().asynchronous()
+        });
+        evsys.user.write(|w| unsafe {
+            w.user().bits(user);
+            w.channel().bits(channel as u8 + 1)
+        });
+    }
+}
+
+/// Disconnect the `user` and the EVSYS `channel`
+pub(crate) fn disconnect(evsys: &mut EVSYS, channel: usize, user: u8) {
+    #[cfg(feature = "min-samd51g")]
+    {
+        evsys.user[user as usize].reset();
+        evsys.channel[channel].channel.reset();
+    }
+    #[cfg(any(feature = "samd11", feature = "samd21"))]
+    {
+        evsys.user.write(|w| unsafe { w.user().bits(user) });
+        evsys
+            .channel
+            .write(|w| unsafe { w.channel().bits(channel as u8) });
+    }
+}
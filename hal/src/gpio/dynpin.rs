@@ -112,7 +112,6 @@ pub enum DynAlternate {
     E,
     F,
     G,
-    #[cfg(any(feature = "samd21", feature = "min-samd51g"))]
     H,
     #[cfg(feature = "min-samd51g")]
     I,
@@ -183,9 +182,7 @@ macro_rules! dyn_alternate {
     };
 }
 
-dyn_alternate!(B, C, D, E, F, G);
-#[cfg(any(feature = "samd21", feature = "min-samd51g"))]
-dyn_alternate!(H);
+dyn_alternate!(B, C, D, E, F, G, H);
 #[cfg(feature = "min-samd51g")]
 dyn_alternate!(I, J, K, L, M, N);
 
@@ -254,6 +251,7 @@ impl DynRegisters {
 ///
 /// [`DynPin`]s are not tracked and verified at compile-time, so run-time
 /// operations are fallible. This `enum` represents the corresponding errors.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Error {
     /// The pin did not have the correct ID or mode for the requested operation
     InvalidPinType,
@@ -552,3 +550,60 @@ impl StatefulOutputPin for DynPin {
         self._is_set_low()
     }
 }
+
+//==============================================================================
+// Embedded HAL 1.0 traits
+//==============================================================================
+
+#[cfg(feature = "ehal-1")]
+impl crate::ehal_1::digital::Error for Error {
+    #[inline]
+    fn kind(&self) -> crate::ehal_1::digital::ErrorKind {
+        crate::ehal_1::digital::ErrorKind::Other
+    }
+}
+
+#[cfg(feature = "ehal-1")]
+impl crate::ehal_1::digital::ErrorType for DynPin {
+    type Error = Error;
+}
+
+#[cfg(feature = "ehal-1")]
+impl crate::ehal_1::digital::OutputPin for DynPin {
+    #[inline]
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self._set_high()
+    }
+    #[inline]
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self._set_low()
+    }
+}
+
+#[cfg(feature = "ehal-1")]
+impl crate::ehal_1::digital::InputPin for DynPin {
+    #[inline]
+    fn is_high(&mut self) -> Result<bool, Self::Error> {
+        self._is_high()
+    }
+    #[inline]
+    fn is_low(&mut self) -> Result<bool, Self::Error> {
+        self._is_low()
+    }
+}
+
+#[cfg(feature = "ehal-1")]
+impl crate::ehal_1::digital::StatefulOutputPin for DynPin {
+    #[inline]
+    fn is_set_high(&mut self) -> Result<bool, Self::Error> {
+        self._is_set_high()
+    }
+    #[inline]
+    fn is_set_low(&mut self) -> Result<bool, Self::Error> {
+        self._is_set_low()
+    }
+    #[inline]
+    fn toggle(&mut self) -> Result<(), Self::Error> {
+        self._toggle()
+    }
+}
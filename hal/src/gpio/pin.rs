@@ -339,10 +339,7 @@ macro_rules! alternate {
     };
 }
 
-alternate!(B, C, D, E, F, G);
-
-#[cfg(any(feature = "samd21", feature = "min-samd51g"))]
-alternate!(H);
+alternate!(B, C, D, E, F, G, H);
 
 #[cfg(feature = "min-samd51g")]
 alternate!(I, J, K, L, M, N);
@@ -749,7 +746,6 @@ impl_core_convert_from!(
     AlternateE,
     AlternateF,
     AlternateG,
-    #[cfg(any(feature = "samd21", feature = "min-samd51g"))]
     AlternateH,
     #[cfg(feature = "min-samd51g")]
     AlternateI,
@@ -979,6 +975,105 @@ where
     }
 }
 
+//==============================================================================
+//  Embedded HAL 1.0 traits
+//==============================================================================
+
+#[cfg(feature = "ehal-1")]
+impl<I, M> crate::ehal_1::digital::ErrorType for Pin<I, M>
+where
+    I: PinId,
+    M: PinMode,
+{
+    type Error = Infallible;
+}
+
+#[cfg(feature = "ehal-1")]
+impl<I, C> crate::ehal_1::digital::OutputPin for Pin<I, Output<C>>
+where
+    I: PinId,
+    C: OutputConfig,
+{
+    #[inline]
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self._set_high();
+        Ok(())
+    }
+    #[inline]
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self._set_low();
+        Ok(())
+    }
+}
+
+#[cfg(feature = "ehal-1")]
+impl<I> crate::ehal_1::digital::InputPin for Pin<I, ReadableOutput>
+where
+    I: PinId,
+{
+    #[inline]
+    fn is_high(&mut self) -> Result<bool, Self::Error> {
+        Ok(self._is_high())
+    }
+    #[inline]
+    fn is_low(&mut self) -> Result<bool, Self::Error> {
+        Ok(self._is_low())
+    }
+}
+
+#[cfg(feature = "ehal-1")]
+impl<I, C> crate::ehal_1::digital::InputPin for Pin<I, Input<C>>
+where
+    I: PinId,
+    C: InputConfig,
+{
+    #[inline]
+    fn is_high(&mut self) -> Result<bool, Self::Error> {
+        Ok(self._is_high())
+    }
+    #[inline]
+    fn is_low(&mut self) -> Result<bool, Self::Error> {
+        Ok(self._is_low())
+    }
+}
+
+#[cfg(feature = "ehal-1")]
+impl<I, C> crate::ehal_1::digital::InputPin for Pin<I, Interrupt<C>>
+where
+    I: PinId,
+    C: InterruptConfig,
+{
+    #[inline]
+    fn is_high(&mut self) -> Result<bool, Self::Error> {
+        Ok(self._is_high())
+    }
+    #[inline]
+    fn is_low(&mut self) -> Result<bool, Self::Error> {
+        Ok(self._is_low())
+    }
+}
+
+#[cfg(feature = "ehal-1")]
+impl<I, C> crate::ehal_1::digital::StatefulOutputPin for Pin<I, Output<C>>
+where
+    I: PinId,
+    C: OutputConfig,
+{
+    #[inline]
+    fn is_set_high(&mut self) -> Result<bool, Self::Error> {
+        Ok(self._is_set_high())
+    }
+    #[inline]
+    fn is_set_low(&mut self) -> Result<bool, Self::Error> {
+        Ok(self._is_set_low())
+    }
+    #[inline]
+    fn toggle(&mut self) -> Result<(), Self::Error> {
+        self._toggle();
+        Ok(())
+    }
+}
+
 //==============================================================================
 //  Pin definitions
 //==============================================================================
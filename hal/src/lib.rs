@@ -1,6 +1,17 @@
 #![no_std]
+// The `async` feature requires Rust 1.75, above the MSRV of the crate
+#![cfg_attr(feature = "async", allow(clippy::incompatible_msrv))]
 
 pub use embedded_hal as ehal;
+#[cfg(feature = "ehal-1")]
+pub use embedded_hal_1 as ehal_1;
+#[cfg(feature = "async")]
+pub use embedded_hal_async;
+#[cfg(feature = "ehal-1")]
+pub use embedded_io;
+#[cfg(feature = "async")]
+pub use embedded_io_async;
+pub use fugit;
 
 pub use paste;
 
@@ -82,9 +93,14 @@ macro_rules! dbgprint {
     ($($arg:tt)*) => {{}};
 }
 
+#[cfg(feature = "device")]
+pub mod capture;
+pub mod clock_params;
 #[cfg(feature = "device")]
 pub mod delay;
 #[cfg(feature = "device")]
+mod evsys;
+#[cfg(feature = "device")]
 pub mod gpio;
 #[cfg(feature = "device")]
 pub mod prelude;
@@ -93,9 +109,15 @@ pub mod rtc;
 #[cfg(feature = "device")]
 pub mod sercom;
 pub mod sleeping_delay;
+#[cfg(feature = "device")]
+pub mod tc;
+#[cfg(feature = "device")]
+pub mod tcc;
 pub mod time;
 pub mod timer_params;
 pub mod timer_traits;
+#[cfg(all(feature = "async", feature = "device"))]
+mod waker;
 
 #[cfg(all(feature = "unproven", feature = "dma"))]
 pub mod dmac;
@@ -2,6 +2,8 @@
 pub use crate::eic::pin::EicPin;
 pub use crate::time::U32Ext as _atsamd21_hal_time_U32Ext;
 pub use crate::timer_traits::InterruptDrivenTimer as _atsamd_hal_timer_traits_InterruptDrivenTimer;
+pub use fugit::ExtU32 as _fugit_ExtU32;
+pub use fugit::RateExtU32 as _fugit_RateExtU32;
 
 // embedded-hal doesn’t yet have v2 in its prelude, so we need to
 // export it ourselves
@@ -22,6 +22,8 @@ use rtic_monotonic::Monotonic;
 
 // SAMx5x imports
 #[cfg(feature = "min-samd51g")]
+use crate::clock::RtcClock;
+#[cfg(feature = "min-samd51g")]
 use crate::pac::{
     rtc::mode0::ctrla::PRESCALER_A, rtc::mode0::CTRLA as MODE0_CTRLA,
     rtc::mode2::CTRLA as MODE2_CTRLA, MCLK as PM,
@@ -77,6 +79,50 @@ pub enum Count32Mode {}
 impl RtcMode for Count32Mode {}
 impl Sealed for Count32Mode {}
 
+/// Alarms of the clock/calendar mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Alarm {
+    Alarm0,
+    #[cfg(feature = "min-samd51g")]
+    Alarm1,
+}
+
+/// Fields of the [`Datetime`] compared against the clock to trigger an alarm,
+/// i.e. how often the alarm repeats.
+///
+/// The discriminants are the values of the `MASK.SEL` field.
+///
+/// `MASK.SEL` can't select an alarm every second. On SAMx5x chips, the
+/// periodic interval 7 flag (`PER7` of `MODE2.INTFLAG`) is set every second in
+/// the clock mode, which runs from a 1.024 kHz clock. On SAMD11/SAMD21, use the
+/// 32-bit counter mode with a 1 s timeout instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum AlarmRepeat {
+    /// The alarm is disabled
+    Off = 0,
+    /// Match the seconds, triggering every minute
+    EveryMinute = 1,
+    /// Match the minutes and seconds, triggering every hour
+    EveryHour = 2,
+    /// Match the time of day, triggering every day
+    EveryDay = 3,
+    /// Match the day of the month and time, triggering every month
+    EveryMonth = 4,
+    /// Match the month, day and time, triggering every year
+    EveryYear = 5,
+    /// Match the whole [`Datetime`], triggering once
+    Once = 6,
+}
+
+/// Compare registers of the 32-bit counter mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compare {
+    Compare0,
+    #[cfg(feature = "min-samd51g")]
+    Compare1,
+}
+
 #[cfg(feature = "sdmmc")]
 impl From<Datetime> for Timestamp {
     fn from(clock: Datetime) -> Timestamp {
@@ -95,6 +141,8 @@ impl From<Datetime> for Timestamp {
 pub struct Rtc<Mode: RtcMode> {
     rtc: RTC,
     rtc_clock_freq: Hertz,
+    #[cfg(feature = "min-samd51g")]
+    rtc_clock: Option<RtcClock>,
     _mode: PhantomData<Mode>,
 }
 
@@ -150,18 +198,16 @@ impl<Mode: RtcMode> Rtc<Mode> {
         self.sync();
     }
 
-    fn create(rtc: RTC, rtc_clock_freq: Hertz) -> Self {
-        Self {
-            rtc,
-            rtc_clock_freq,
+    fn into_mode<M: RtcMode>(self) -> Rtc<M> {
+        Rtc {
+            rtc: self.rtc,
+            rtc_clock_freq: self.rtc_clock_freq,
+            #[cfg(feature = "min-samd51g")]
+            rtc_clock: self.rtc_clock,
             _mode: PhantomData,
         }
     }
 
-    fn into_mode<M: RtcMode>(self) -> Rtc<M> {
-        Rtc::create(self.rtc, self.rtc_clock_freq)
-    }
-
     /// Reonfigures the peripheral for 32bit counter mode.
     pub fn into_count32_mode(mut self) -> Rtc<Count32Mode> {
         self.enable(false);
@@ -222,17 +268,64 @@ impl<Mode: RtcMode> Rtc<Mode> {
     pub fn free(self) -> RTC {
         self.rtc
     }
+
+    /// Releases the RTC resource, and the clock it was created with, if any
+    #[cfg(feature = "min-samd51g")]
+    pub fn free_with_clock(self) -> (RTC, Option<RtcClock>) {
+        (self.rtc, self.rtc_clock)
+    }
+}
+
+/// Number of RTC backup registers
+#[cfg(feature = "min-samd51g")]
+pub const NUM_BACKUP_REGISTERS: usize = 8;
+
+/// The RTC backup registers (`BKUP[0..7]`) are retained in every sleep mode,
+/// including BACKUP, and across resets other than a power-on reset. They are
+/// not affected by the RTC mode nor by a software reset of the RTC.
+#[cfg(feature = "min-samd51g")]
+impl<Mode: RtcMode> Rtc<Mode> {
+    /// Returns the value of the backup register `index`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is not lower than [`NUM_BACKUP_REGISTERS`].
+    #[inline]
+    pub fn backup_register(&self, index: usize) -> u32 {
+        self.mode0().bkup[index].read().bits()
+    }
+
+    /// Set the value of the backup register `index`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is not lower than [`NUM_BACKUP_REGISTERS`].
+    #[inline]
+    pub fn set_backup_register(&mut self, index: usize, value: u32) {
+        self.mode0().bkup[index].write(|w| unsafe { w.bits(value) });
+    }
+
+    /// Returns the values of all the backup registers
+    pub fn backup_registers(&self) -> [u32; NUM_BACKUP_REGISTERS] {
+        let mut values = [0; NUM_BACKUP_REGISTERS];
+        for (index, value) in values.iter_mut().enumerate() {
+            *value = self.backup_register(index);
+        }
+        values
+    }
 }
 
 impl Rtc<Count32Mode> {
     /// Configures the RTC in 32-bit counter mode with no prescaler (default
     /// state after reset) and the counter initialized to zero.
-    pub fn count32_mode(rtc: RTC, rtc_clock_freq: Hertz, pm: &mut PM) -> Self {
+    pub fn count32_mode(rtc: RTC, rtc_clock_freq: impl Into<Hertz>, pm: &mut PM) -> Self {
         pm.apbamask.modify(|_, w| w.rtc_().set_bit());
 
         let mut new_rtc = Self {
             rtc,
-            rtc_clock_freq,
+            rtc_clock_freq: rtc_clock_freq.into(),
+            #[cfg(feature = "min-samd51g")]
+            rtc_clock: None,
             _mode: PhantomData,
         };
 
@@ -241,6 +334,15 @@ impl Rtc<Count32Mode> {
         new_rtc
     }
 
+    /// Configures the RTC in 32-bit counter mode, clocked by the output
+    /// selected in `rtc_clock`.
+    #[cfg(feature = "min-samd51g")]
+    pub fn count32_mode_with_clock(rtc: RTC, rtc_clock: RtcClock, pm: &mut PM) -> Self {
+        let mut new_rtc = Self::count32_mode(rtc, rtc_clock.freq(), pm);
+        new_rtc.rtc_clock = Some(rtc_clock);
+        new_rtc
+    }
+
     /// Returns the internal counter value.
     #[inline]
     pub fn count32(&self) -> u32 {
@@ -268,6 +370,66 @@ impl Rtc<Count32Mode> {
         self.enable(true);
     }
 
+    /// Sets the value of a compare register. The `CMPn` flag is raised when the
+    /// counter reaches `value`.
+    ///
+    /// The counter keeps running past the compare value, unless the
+    /// [`CountDown`] implementation enabled clearing it on match.
+    #[inline]
+    pub fn set_compare(&mut self, compare: Compare, value: u32) {
+        self.mode0().comp[compare as usize].write(|w| unsafe { w.comp().bits(value) });
+        self.sync();
+    }
+
+    /// Returns the value of a compare register.
+    #[inline]
+    pub fn compare(&self, compare: Compare) -> u32 {
+        self.mode0().comp[compare as usize].read().bits()
+    }
+
+    /// Enables the interrupt of a compare register.
+    #[inline]
+    pub fn enable_compare_interrupt(&mut self, compare: Compare) {
+        self.mode0().intenset.write(|w| match compare {
+            Compare::Compare0 => w.cmp0().set_bit(),
+            #[cfg(feature = "min-samd51g")]
+            Compare::Compare1 => w.cmp1().set_bit(),
+        });
+    }
+
+    /// Disables the interrupt of a compare register.
+    #[inline]
+    pub fn disable_compare_interrupt(&mut self, compare: Compare) {
+        self.mode0().intenclr.write(|w| match compare {
+            Compare::Compare0 => w.cmp0().set_bit(),
+            #[cfg(feature = "min-samd51g")]
+            Compare::Compare1 => w.cmp1().set_bit(),
+        });
+    }
+
+    /// Returns `true` if the counter matched the compare register since the
+    /// flag was last cleared.
+    #[inline]
+    pub fn is_compare_matched(&self, compare: Compare) -> bool {
+        let flags = self.mode0().intflag.read();
+        match compare {
+            Compare::Compare0 => flags.cmp0().bit_is_set(),
+            #[cfg(feature = "min-samd51g")]
+            Compare::Compare1 => flags.cmp1().bit_is_set(),
+        }
+    }
+
+    /// Clears the `CMPn` flag of a compare register, acknowledging its
+    /// interrupt.
+    #[inline]
+    pub fn clear_compare(&mut self, compare: Compare) {
+        self.mode0().intflag.write(|w| match compare {
+            Compare::Compare0 => w.cmp0().set_bit(),
+            #[cfg(feature = "min-samd51g")]
+            Compare::Compare1 => w.cmp1().set_bit(),
+        });
+    }
+
     /// This resets the internal counter and sets the prescaler to match the
     /// provided timeout. You should configure the prescaler using the longest
     /// timeout you plan to measure.
@@ -300,10 +462,21 @@ impl Rtc<Count32Mode> {
 }
 
 impl Rtc<ClockMode> {
-    pub fn clock_mode(rtc: RTC, rtc_clock_freq: Hertz, pm: &mut PM) -> Self {
+    pub fn clock_mode(rtc: RTC, rtc_clock_freq: impl Into<Hertz>, pm: &mut PM) -> Self {
         Rtc::count32_mode(rtc, rtc_clock_freq, pm).into_clock_mode()
     }
 
+    /// Configures the RTC in clock/calendar mode, clocked by the output
+    /// selected in `rtc_clock`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rtc_clock` is not a 1 kHz output.
+    #[cfg(feature = "min-samd51g")]
+    pub fn clock_mode_with_clock(rtc: RTC, rtc_clock: RtcClock, pm: &mut PM) -> Self {
+        Rtc::count32_mode_with_clock(rtc, rtc_clock, pm).into_clock_mode()
+    }
+
     /// Returns the current clock/calendar value.
     pub fn current_time(&self) -> Datetime {
         // synchronize this read on SAMD11/21. SAMx5x is automatically synchronized
@@ -335,6 +508,104 @@ impl Rtc<ClockMode> {
     }
 }
 
+/// Writes `$time` and `$repeat` to an alarm and its mask register
+macro_rules! set_alarm {
+    ($mode2:expr, $alarm:ident, $mask:ident, $time:expr, $repeat:expr) => {
+        $mode2.$alarm.write(|w| unsafe {
+            w.second()
+                .bits($time.seconds)
+                .minute()
+                .bits($time.minutes)
+                .hour()
+                .bits($time.hours)
+                .day()
+                .bits($time.day)
+                .month()
+                .bits($time.month)
+                .year()
+                .bits($time.year)
+        });
+        $mode2
+            .$mask
+            .write(|w| unsafe { w.sel().bits($repeat as u8) });
+    };
+}
+
+impl Rtc<ClockMode> {
+    /// Sets an alarm to trigger when the clock matches the fields of `time`
+    /// selected by `repeat`.
+    ///
+    /// For instance, an alarm at `hours: 7, minutes: 30, seconds: 0` with
+    /// [`AlarmRepeat::EveryDay`] triggers at 07:30:00 every day, regardless
+    /// of the date fields of `time`. The `ALARMn` flag stays set until cleared
+    /// by [`clear_alarm`](Self::clear_alarm).
+    pub fn set_alarm(&mut self, alarm: Alarm, time: Datetime, repeat: AlarmRepeat) {
+        let mode2 = self.mode2();
+        match alarm {
+            Alarm::Alarm0 => {
+                set_alarm!(mode2, alarm0, mask0, time, repeat);
+            }
+            #[cfg(feature = "min-samd51g")]
+            Alarm::Alarm1 => {
+                set_alarm!(mode2, alarm1, mask1, time, repeat);
+            }
+        }
+        self.sync();
+    }
+
+    /// Disables an alarm. Its flag is not cleared.
+    #[inline]
+    pub fn disable_alarm(&mut self, alarm: Alarm) {
+        match alarm {
+            Alarm::Alarm0 => self.mode2().mask0.write(|w| w.sel().off()),
+            #[cfg(feature = "min-samd51g")]
+            Alarm::Alarm1 => self.mode2().mask1.write(|w| w.sel().off()),
+        }
+        self.sync();
+    }
+
+    /// Enables the interrupt of an alarm.
+    #[inline]
+    pub fn enable_alarm_interrupt(&mut self, alarm: Alarm) {
+        self.mode2().intenset.write(|w| match alarm {
+            Alarm::Alarm0 => w.alarm0().set_bit(),
+            #[cfg(feature = "min-samd51g")]
+            Alarm::Alarm1 => w.alarm1().set_bit(),
+        });
+    }
+
+    /// Disables the interrupt of an alarm.
+    #[inline]
+    pub fn disable_alarm_interrupt(&mut self, alarm: Alarm) {
+        self.mode2().intenclr.write(|w| match alarm {
+            Alarm::Alarm0 => w.alarm0().set_bit(),
+            #[cfg(feature = "min-samd51g")]
+            Alarm::Alarm1 => w.alarm1().set_bit(),
+        });
+    }
+
+    /// Returns `true` if the alarm triggered since its flag was last cleared.
+    #[inline]
+    pub fn is_alarm_triggered(&self, alarm: Alarm) -> bool {
+        let flags = self.mode2().intflag.read();
+        match alarm {
+            Alarm::Alarm0 => flags.alarm0().bit_is_set(),
+            #[cfg(feature = "min-samd51g")]
+            Alarm::Alarm1 => flags.alarm1().bit_is_set(),
+        }
+    }
+
+    /// Clears the `ALARMn` flag of an alarm, acknowledging its interrupt.
+    #[inline]
+    pub fn clear_alarm(&mut self, alarm: Alarm) {
+        self.mode2().intflag.write(|w| match alarm {
+            Alarm::Alarm0 => w.alarm0().set_bit(),
+            #[cfg(feature = "min-samd51g")]
+            Alarm::Alarm1 => w.alarm1().set_bit(),
+        });
+    }
+}
+
 // --- Timer / Counter Functionality
 
 impl Periodic for Rtc<Count32Mode> {}
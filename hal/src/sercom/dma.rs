@@ -378,3 +378,136 @@ where
             .begin(C::Sercom::DMA_RX_TRIGGER, trigger_action)
     }
 }
+
+//=============================================================================
+// Async DMA transfers
+//=============================================================================
+
+/// Trigger action of the SERCOM DMA transfers
+#[cfg(all(feature = "async", feature = "min-samd51g"))]
+pub(super) const TRIGGER_ACTION: TriggerAction = TriggerAction::BURST;
+#[cfg(all(feature = "async", any(feature = "samd11", feature = "samd21")))]
+pub(super) const TRIGGER_ACTION: TriggerAction = TriggerAction::BEAT;
+
+/// Source or destination of an async DMA transfer, borrowed for the duration
+/// of the transfer
+#[cfg(feature = "async")]
+pub(super) struct DmaSlice<T> {
+    ptr: *mut T,
+    len: usize,
+}
+
+#[cfg(feature = "async")]
+impl<T: Beat> DmaSlice<T> {
+    /// Source of the words of `slice`
+    #[inline]
+    pub(super) fn from_slice(slice: &[T]) -> Self {
+        Self {
+            // The DMAC only reads from a source
+            ptr: slice.as_ptr() as *mut T,
+            len: slice.len(),
+        }
+    }
+
+    /// Destination of the words of `slice`
+    #[inline]
+    pub(super) fn from_mut(slice: &mut [T]) -> Self {
+        Self {
+            ptr: slice.as_mut_ptr(),
+            len: slice.len(),
+        }
+    }
+
+    /// Non-incrementing source or destination, e.g. the `DATA` register of
+    /// a SERCOM
+    #[inline]
+    pub(super) fn single(ptr: *mut T) -> Self {
+        Self { ptr, len: 1 }
+    }
+
+    #[inline]
+    pub(super) fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Split off the next `max` words at most. A non-incrementing slice is
+    /// returned as is.
+    #[inline]
+    pub(super) fn take(&mut self, max: usize) -> Self {
+        if self.len <= 1 {
+            return Self::single(self.ptr);
+        }
+        let len = self.len.min(max);
+        let chunk = Self { ptr: self.ptr, len };
+        // SAFETY: `len` is at most the length of the slice
+        self.ptr = unsafe { self.ptr.add(len) };
+        self.len -= len;
+        chunk
+    }
+}
+
+#[cfg(feature = "async")]
+unsafe impl<T: Beat> Buffer for DmaSlice<T> {
+    type Beat = T;
+
+    #[inline]
+    fn dma_ptr(&mut self) -> *mut Self::Beat {
+        if self.incrementing() {
+            // SAFETY: one past the end of the slice
+            unsafe { self.ptr.add(self.len) }
+        } else {
+            self.ptr
+        }
+    }
+
+    #[inline]
+    fn incrementing(&self) -> bool {
+        self.len > 1
+    }
+
+    #[inline]
+    fn buffer_len(&self) -> usize {
+        self.len
+    }
+}
+
+/// Maximum number of beats of a DMA transfer
+#[cfg(feature = "async")]
+pub(super) const MAX_BEATS: usize = u16::MAX as usize;
+
+/// Wait for both `a` and `b` to complete, e.g. the RX and TX transfers of a
+/// SPI, which must run at the same time. `a` is polled first.
+#[cfg(feature = "async")]
+pub(super) async fn join<A, B>(a: A, b: B) -> (A::Output, B::Output)
+where
+    A: core::future::Future,
+    B: core::future::Future,
+{
+    use core::task::Poll;
+
+    let mut a = core::pin::pin!(a);
+    let mut b = core::pin::pin!(b);
+    let mut output_a = None;
+    let mut output_b = None;
+    core::future::poll_fn(|cx| {
+        if output_a.is_none() {
+            if let Poll::Ready(output) = a.as_mut().poll(cx) {
+                output_a = Some(output);
+            }
+        }
+        if output_b.is_none() {
+            if let Poll::Ready(output) = b.as_mut().poll(cx) {
+                output_b = Some(output);
+            }
+        }
+        match (output_a.take(), output_b.take()) {
+            (Some(a), Some(b)) => Poll::Ready((a, b)),
+            (a, b) => {
+                output_a = a;
+                output_b = b;
+                Poll::Pending
+            }
+        }
+    })
+    .await
+}
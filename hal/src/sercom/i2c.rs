@@ -268,6 +268,10 @@ pub use pads::*;
 mod reg;
 use reg::Registers;
 
+use crate::clock::{ClockId, Reclock};
+use crate::sercom::Sercom;
+use crate::time::Hertz;
+
 mod flags;
 pub use flags::*;
 
@@ -276,6 +280,11 @@ pub use config::*;
 
 mod impl_ehal;
 
+#[cfg(feature = "async")]
+mod async_api;
+#[cfg(feature = "async")]
+pub use async_api::*;
+
 /// Word size for an I2C message
 pub type Word = u8;
 
@@ -416,6 +425,20 @@ impl<C: AnyConfig> I2c<C> {
     }
 }
 
+impl<C: AnyConfig> Reclock for I2c<C> {
+    #[inline]
+    fn clock_id(&self) -> ClockId {
+        <C::Sercom as Sercom>::CORE_CLOCK
+    }
+
+    /// Recompute the `BAUD` register for the new GCLK frequency. The SERCOM
+    /// is temporarily disabled, see [`reconfigure`](I2c::reconfigure).
+    #[inline]
+    fn reclock(&mut self, freq: Hertz) {
+        self.reconfigure(|c| c.reclock(freq));
+    }
+}
+
 impl<P: PadSet> AsRef<Config<P>> for I2c<Config<P>> {
     #[inline]
     fn as_ref(&self) -> &Config<P> {
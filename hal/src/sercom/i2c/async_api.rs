@@ -0,0 +1,169 @@
+//! Async I2C driver
+//!
+//! [`I2cAsync`] waits for the I2C interrupt flags asynchronously, instead of
+//! spinning on them. The transfers are always interrupt driven: the DMA length
+//! mode of the SERCOM ends every transfer with a STOP condition, which can't
+//! express the repeated starts of an [`Operation`] list.
+
+use core::future::poll_fn;
+use core::task::Poll;
+
+use super::reg::Registers;
+use super::{AnyConfig, BusState, Error, Flags, I2c};
+use crate::ehal_1::i2c::{ErrorType, Operation};
+use crate::sercom::register_waker;
+
+/// Async I2C driver, see [`I2c::into_async`]
+pub struct I2cAsync<C: AnyConfig> {
+    i2c: I2c<C>,
+}
+
+impl<C: AnyConfig> I2c<C> {
+    /// Turn the `I2c` into an async driver
+    ///
+    /// The interrupt handler(s) of the SERCOM must call
+    /// [`sercom::on_interrupt`](crate::sercom::on_interrupt), and be unmasked
+    /// in the NVIC.
+    #[inline]
+    pub fn into_async(self) -> I2cAsync<C> {
+        I2cAsync { i2c: self }
+    }
+}
+
+impl<C: AnyConfig> I2cAsync<C> {
+    /// Return the blocking driver
+    #[inline]
+    pub fn free(mut self) -> I2c<C> {
+        self.i2c.disable_interrupts(Flags::all());
+        self.i2c
+    }
+
+    #[inline]
+    fn registers(&mut self) -> &mut Registers<C::Sercom> {
+        &mut self.i2c.config.as_mut().registers
+    }
+
+    /// Wait until one of the `flags` is set, and return the flags set
+    async fn wait_flags(&mut self, flags: Flags) -> Flags {
+        poll_fn(|cx| {
+            register_waker::<C::Sercom>(cx.waker());
+            let set = self.i2c.read_flags() & flags;
+            if set.is_empty() {
+                self.i2c.enable_interrupts(flags);
+                Poll::Pending
+            } else {
+                Poll::Ready(set)
+            }
+        })
+        .await
+    }
+
+    async fn start_write(&mut self, address: u8) -> Result<(), Error> {
+        self.registers().start_write(address)?;
+        self.wait_flags(Flags::MB | Flags::ERROR).await;
+        self.i2c.read_status().check_bus_error()
+    }
+
+    async fn start_read(&mut self, address: u8) -> Result<(), Error> {
+        self.registers().start_read(address)?;
+        let flags = self.wait_flags(Flags::MB | Flags::SB | Flags::ERROR).await;
+        // If arbitration was lost, it will be signalled via the mb bit
+        if flags.contains(Flags::MB) {
+            return Err(Error::ArbitrationLost);
+        }
+        self.i2c.read_status().check_bus_error()
+    }
+
+    async fn send_bytes(&mut self, bytes: &[u8]) -> Result<(), Error> {
+        for byte in bytes {
+            self.registers().write_byte(*byte);
+            self.wait_flags(Flags::MB | Flags::ERROR).await;
+            self.i2c.read_status().check_bus_error()?;
+        }
+        Ok(())
+    }
+
+    /// Receive into `buffer`. The first byte is already being received after
+    /// a (repeated) start, otherwise the last byte received is acknowledged
+    /// to receive another one.
+    async fn fill_buffer(&mut self, buffer: &mut [u8], after_start: bool) -> Result<(), Error> {
+        for (i, dest) in buffer.iter_mut().enumerate() {
+            if i > 0 || !after_start {
+                self.registers().cmd_read();
+            }
+            let flags = self.wait_flags(Flags::SB | Flags::ERROR).await;
+            if flags.contains(Flags::ERROR) {
+                self.i2c.read_status().check_bus_error()?;
+            }
+            *dest = self.registers().read_byte();
+        }
+        self.registers().nack_next();
+        Ok(())
+    }
+
+    async fn transaction_inner(
+        &mut self,
+        address: u8,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Error> {
+        // Kind of the previous operation, `Some(true)` for a read
+        let mut reading = None;
+        for operation in operations {
+            match operation {
+                Operation::Write(bytes) => {
+                    if reading != Some(false) {
+                        self.start_write(address).await?;
+                    }
+                    reading = Some(false);
+                    self.send_bytes(bytes).await?;
+                }
+                Operation::Read([]) => (),
+                Operation::Read(buffer) => {
+                    let after_start = reading != Some(true);
+                    if after_start {
+                        self.start_read(address).await?;
+                    }
+                    reading = Some(true);
+                    self.fill_buffer(buffer, after_start).await?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<C: AnyConfig> ErrorType for I2cAsync<C> {
+    type Error = Error;
+}
+
+impl<C: AnyConfig> embedded_hal_async::i2c::I2c for I2cAsync<C> {
+    /// Execute `operations` in a single transaction
+    ///
+    /// Adjacent operations of the same kind are merged, a repeated start is
+    /// sent when switching between writes and reads. Empty reads are skipped.
+    /// A stop is sent at the end of the transaction, after an error, or when
+    /// the returned future is dropped before completion.
+    async fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Error> {
+        let i2c = StopOnDrop(self);
+        i2c.0.transaction_inner(address, operations).await
+    }
+}
+
+/// Release the bus of an async transaction, when its future completes or is
+/// dropped
+struct StopOnDrop<'a, C: AnyConfig>(&'a mut I2cAsync<C>);
+
+impl<C: AnyConfig> Drop for StopOnDrop<'_, C> {
+    fn drop(&mut self) {
+        self.0.i2c.disable_interrupts(Flags::all());
+        if self.0.i2c.read_status().busstate() == BusState::Owner {
+            // Don't acknowledge a byte being received
+            self.0.registers().nack_next();
+            self.0.registers().cmd_stop();
+        }
+    }
+}
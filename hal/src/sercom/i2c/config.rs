@@ -2,6 +2,7 @@
 
 use super::{I2c, InactiveTimeout, PadSet, Registers};
 use crate::{
+    clock::{ClockId, Reclock},
     pac::sercom0::i2cm::ctrla::MODE_A,
     sercom::*,
     time::Hertz,
@@ -33,6 +34,7 @@ where
     pub(super) registers: Registers<P::Sercom>,
     pads: P,
     freq: Hertz,
+    baud: Option<Hertz>,
 }
 
 impl<P: PadSet> Config<P> {
@@ -46,6 +48,7 @@ impl<P: PadSet> Config<P> {
             registers,
             pads,
             freq: freq.into(),
+            baud: None,
         }
     }
 
@@ -146,6 +149,8 @@ impl<P: PadSet> Config<P> {
     /// the maximum supported baud rate.
     #[inline]
     pub fn set_baud(&mut self, baud: impl Into<Hertz>) {
+        let baud = baud.into();
+        self.baud = Some(baud);
         self.registers.set_baud(self.freq, baud);
     }
 
@@ -234,6 +239,23 @@ impl<P: PadSet> Config<P> {
     }
 }
 
+impl<P: PadSet> Reclock for Config<P> {
+    #[inline]
+    fn clock_id(&self) -> ClockId {
+        <P::Sercom as Sercom>::CORE_CLOCK
+    }
+
+    /// Store the new GCLK frequency, and recompute the `BAUD` register from
+    /// the last baud rate set
+    #[inline]
+    fn reclock(&mut self, freq: Hertz) {
+        self.freq = freq;
+        if let Some(baud) = self.baud {
+            self.registers.set_baud(freq, baud);
+        }
+    }
+}
+
 //=============================================================================
 // AnyConfig
 //=============================================================================
@@ -1,4 +1,7 @@
 //! `embedded-hal` trait implementations for [`I2c`]s
+//!
+//! With the `ehal-1` feature, [`I2c`] also implements the embedded HAL 1.0
+//! [`I2c`](crate::ehal_1::i2c::I2c) trait.
 
 use super::{config::AnyConfig, flags::Error, I2c};
 use embedded_hal::blocking::i2c::{Read, Write, WriteRead};
@@ -32,3 +35,67 @@ impl<C: AnyConfig> WriteRead for I2c<C> {
         Ok(())
     }
 }
+
+#[cfg(feature = "ehal-1")]
+mod ehal_1 {
+    use super::super::{config::AnyConfig, flags::Error, I2c};
+    use crate::ehal_1::i2c::{self, ErrorKind, ErrorType, NoAcknowledgeSource, Operation};
+
+    impl i2c::Error for Error {
+        #[inline]
+        fn kind(&self) -> ErrorKind {
+            match self {
+                Error::BusError => ErrorKind::Bus,
+                Error::ArbitrationLost => ErrorKind::ArbitrationLoss,
+                Error::Nack => ErrorKind::NoAcknowledge(NoAcknowledgeSource::Unknown),
+                Error::LengthError | Error::Timeout => ErrorKind::Other,
+            }
+        }
+    }
+
+    impl<C: AnyConfig> ErrorType for I2c<C> {
+        type Error = Error;
+    }
+
+    impl<C: AnyConfig> i2c::I2c for I2c<C> {
+        /// Execute `operations` in a single transaction
+        ///
+        /// Adjacent operations of the same kind are merged, a repeated start
+        /// is sent when switching between writes and reads. Empty reads are
+        /// skipped.
+        fn transaction(
+            &mut self,
+            address: u8,
+            operations: &mut [Operation<'_>],
+        ) -> Result<(), Self::Error> {
+            let registers = &mut self.config.as_mut().registers;
+            // Kind of the previous operation, `Some(true)` for a read
+            let mut reading = None;
+            for operation in operations {
+                match operation {
+                    Operation::Write(bytes) => {
+                        if reading != Some(false) {
+                            registers.start_write_blocking(address)?;
+                        }
+                        registers.send_bytes(bytes)?;
+                        reading = Some(false);
+                    }
+                    Operation::Read([]) => (),
+                    Operation::Read(buffer) => {
+                        if reading == Some(true) {
+                            registers.continue_fill_buffer(buffer);
+                        } else {
+                            registers.start_read_blocking(address)?;
+                            registers.fill_buffer(buffer)?;
+                        }
+                        reading = Some(true);
+                    }
+                }
+            }
+            if reading.is_some() {
+                registers.cmd_stop();
+            }
+            Ok(())
+        }
+    }
+}
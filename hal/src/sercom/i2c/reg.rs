@@ -209,9 +209,10 @@ impl<S: Sercom> Registers<S> {
         }
     }
 
-    /// Start a blocking write transaction
+    /// Start a write transaction, without waiting for the address to be
+    /// transmitted
     #[inline]
-    pub(super) fn start_write_blocking(&mut self, addr: u8) -> Result<(), Error> {
+    pub(super) fn start_write(&mut self, addr: u8) -> Result<(), Error> {
         if self.get_smart_mode() {
             self.disable();
             self.set_smart_mode(false);
@@ -227,15 +228,23 @@ impl<S: Sercom> Registers<S> {
                 .addr
                 .write(|w| w.addr().bits(encode_write_address(addr)));
         }
+        Ok(())
+    }
+
+    /// Start a blocking write transaction
+    #[inline]
+    pub(super) fn start_write_blocking(&mut self, addr: u8) -> Result<(), Error> {
+        self.start_write(addr)?;
 
         // wait for transmission to complete
         while !self.i2c_master().intflag.read().mb().bit_is_set() {}
         self.read_status().check_bus_error()
     }
 
-    /// Start a blocking read transaction
+    /// Start a read transaction, without waiting for the address to be
+    /// transmitted
     #[inline]
-    pub(super) fn start_read_blocking(&mut self, addr: u8) -> Result<(), Error> {
+    pub(super) fn start_read(&mut self, addr: u8) -> Result<(), Error> {
         if self.get_smart_mode() {
             self.disable();
             self.set_smart_mode(false);
@@ -255,6 +264,13 @@ impl<S: Sercom> Registers<S> {
                 .addr
                 .write(|w| w.addr().bits(encode_read_address(addr)));
         }
+        Ok(())
+    }
+
+    /// Start a blocking read transaction
+    #[inline]
+    pub(super) fn start_read_blocking(&mut self, addr: u8) -> Result<(), Error> {
+        self.start_read(addr)?;
 
         // wait for transmission to complete
         loop {
@@ -347,12 +363,32 @@ impl<S: Sercom> Registers<S> {
         self.sync_sysop();
     }
 
+    /// Write a byte to the `DATA` register, without waiting for its
+    /// transmission
+    #[inline]
+    pub(super) fn write_byte(&mut self, byte: u8) {
+        unsafe {
+            self.i2c_master().data.write(|w| w.bits(byte));
+        }
+    }
+
+    /// Read the byte received from the `DATA` register
+    #[inline]
+    pub(super) fn read_byte(&mut self) -> u8 {
+        self.i2c_master().data.read().bits()
+    }
+
+    /// Arrange to send a NACK on the next command, to stop the slave from
+    /// transmitting more data
+    #[inline]
+    pub(super) fn nack_next(&mut self) {
+        self.i2c_master().ctrlb.modify(|_, w| w.ackact().set_bit());
+    }
+
     #[inline]
     pub(super) fn send_bytes(&mut self, bytes: &[u8]) -> Result<(), Error> {
         for b in bytes {
-            unsafe {
-                self.i2c_master().data.write(|w| w.bits(*b));
-            }
+            self.write_byte(*b);
 
             loop {
                 let intflag = self.i2c_master().intflag.read();
@@ -368,7 +404,7 @@ impl<S: Sercom> Registers<S> {
     #[inline]
     pub(super) fn read_one(&mut self) -> u8 {
         while !self.i2c_master().intflag.read().sb().bit_is_set() {}
-        self.i2c_master().data.read().bits()
+        self.read_byte()
     }
 
     #[inline]
@@ -388,13 +424,25 @@ impl<S: Sercom> Registers<S> {
             }
         }
 
-        // arrange to send nack on next command to
-        // stop slave from transmitting more data
-        self.i2c_master().ctrlb.modify(|_, w| w.ackact().set_bit());
+        self.nack_next();
 
         Ok(())
     }
 
+    /// Receive more bytes in the read transaction started by
+    /// [`fill_buffer`](Self::fill_buffer)
+    #[cfg(feature = "ehal-1")]
+    #[inline]
+    pub(super) fn continue_fill_buffer(&mut self, buffer: &mut [u8]) {
+        for dest in buffer.iter_mut() {
+            // Ack the last byte so that we can receive another one
+            self.cmd_read();
+            *dest = self.read_one();
+        }
+
+        self.nack_next();
+    }
+
     #[inline]
     pub(super) fn do_write(&mut self, addr: u8, bytes: &[u8]) -> Result<(), Error> {
         self.start_write_blocking(addr)?;
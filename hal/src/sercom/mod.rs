@@ -30,6 +30,42 @@ implements [`IsI2cPad`](pad::IsI2cPad).
 [`IsI2cPad`]: pad::IsI2cPad
 "
 )]
+#![cfg_attr(
+    feature = "async",
+    doc = "
+# Async drivers
+
+With the `async` feature, [`I2c`](i2c::I2c), [`Spi`](spi::Spi) and
+[`Uart`](uart::Uart) can be turned into the async drivers
+[`I2cAsync`](i2c::I2cAsync), [`SpiAsync`](spi::SpiAsync) and
+[`UartAsync`](uart::UartAsync) with `into_async`. They implement the
+`embedded-hal-async` and `embedded-io-async` traits, and wait for the SERCOM
+interrupt flags asynchronously. The SERCOM interrupt handler(s) must call
+[`on_interrupt`], and be unmasked in the NVIC. On SAMx5x chips, each SERCOM
+has four interrupt lines (`SERCOM0_0` to `SERCOM0_3`), which all need to call
+[`on_interrupt`].
+
+```ignore
+use atsamd_hal::pac::interrupt;
+use atsamd_hal::sercom::{self, i2c, Sercom0};
+
+#[interrupt]
+fn SERCOM0() {
+    sercom::on_interrupt::<Sercom0>();
+}
+
+async fn read_temperature<C: i2c::AnyConfig>(i2c: &mut i2c::I2cAsync<C>) -> [u8; 2] {
+    let mut buffer = [0; 2];
+    i2c.write_read(0x48, &[0x00], &mut buffer).await.unwrap();
+    buffer
+}
+```
+
+With the `dma` feature, the SPI and UART drivers can also transfer with DMA
+channels, attached with `SpiAsync::with_dma_channels`,
+`UartAsync::with_rx_dma_channel` and `UartAsync::with_tx_dma_channel`.
+"
+)]
 
 use core::ops::Deref;
 
@@ -54,6 +90,7 @@ use pac::{SERCOM6, SERCOM7};
 #[cfg(feature = "dma")]
 use crate::dmac::TriggerSource;
 
+use crate::clock::ClockId;
 use crate::typelevel::Sealed;
 
 pub mod pad;
@@ -75,12 +112,18 @@ pub mod dma;
 pub trait Sercom: Sealed + Deref<Target = sercom0::RegisterBlock> {
     /// SERCOM number
     const NUM: usize;
+    /// Core peripheral channel
+    const CORE_CLOCK: ClockId;
     /// RX Trigger source for DMA transactions
     #[cfg(feature = "dma")]
     const DMA_RX_TRIGGER: TriggerSource;
     /// TX trigger source for DMA transactions
     #[cfg(feature = "dma")]
     const DMA_TX_TRIGGER: TriggerSource;
+    /// Pointer to the register block, used by [`on_interrupt`]
+    #[cfg(feature = "async")]
+    #[doc(hidden)]
+    const PTR: *const sercom0::RegisterBlock;
     /// Enable the corresponding APB clock
     fn enable_apb_clock(&mut self, ctrl: &APB_CLK_CTRL);
 }
@@ -94,10 +137,13 @@ macro_rules! sercom {
                 impl Sealed for Sercom~N {}
                 impl Sercom for Sercom~N {
                     const NUM: usize = N;
+                    const CORE_CLOCK: ClockId = ClockId::[<SERCOM~N _CORE>];
                     #[cfg(feature = "dma")]
                     const DMA_RX_TRIGGER: TriggerSource = TriggerSource::[<SERCOM~N _RX>];
                     #[cfg(feature = "dma")]
                     const DMA_TX_TRIGGER: TriggerSource = TriggerSource::[<SERCOM~N _TX>];
+                    #[cfg(feature = "async")]
+                    const PTR: *const sercom0::RegisterBlock = SERCOM~N::PTR as *const _;
                     #[inline]
                     fn enable_apb_clock(&mut self, ctrl: &APB_CLK_CTRL) {
                         ctrl.$apbmask.modify(|_, w| w.[<sercom~N _>]().set_bit());
@@ -123,3 +169,57 @@ sercom!(apbbmask: (2, 3));
 sercom!(apbdmask: (4, 5));
 #[cfg(feature = "min-samd51n")]
 sercom!(apbdmask: (6, 7));
+
+//==============================================================================
+//  Async
+//==============================================================================
+
+/// Number of SERCOM instances
+#[cfg(all(feature = "async", feature = "samd11"))]
+const NUM_SERCOM: usize = 2;
+#[cfg(all(feature = "async", feature = "samd21"))]
+const NUM_SERCOM: usize = 6;
+#[cfg(all(feature = "async", feature = "min-samd51g"))]
+const NUM_SERCOM: usize = 8;
+
+#[cfg(feature = "async")]
+#[allow(clippy::declare_interior_mutable_const)]
+const NEW_WAKER: crate::waker::WakerCell = crate::waker::WakerCell::new();
+#[cfg(feature = "async")]
+static SERCOM_WAKERS: [crate::waker::WakerCell; NUM_SERCOM] = [NEW_WAKER; NUM_SERCOM];
+/// Wakers of the UART transmitters, which may wait in another task than the
+/// receivers once split
+#[cfg(feature = "async")]
+static SERCOM_TX_WAKERS: [crate::waker::WakerCell; NUM_SERCOM] = [NEW_WAKER; NUM_SERCOM];
+
+/// Wake the tasks waiting on the SERCOM `S`
+///
+/// This must be called from the interrupt handler(s) of the SERCOM used by
+/// an async driver, e.g. [`i2c::I2cAsync`]. The interrupts pending are
+/// disabled; their flags are checked and cleared by the woken task.
+#[cfg(feature = "async")]
+pub fn on_interrupt<S: Sercom>() {
+    // SAFETY: INTFLAG, INTENSET and INTENCLR are at the same place in every
+    // mode, and writing INTENCLR only disables interrupts
+    let sercom = unsafe { &*S::PTR }.i2cm();
+    let pending = sercom.intflag.read().bits() & sercom.intenset.read().bits();
+    if pending != 0 {
+        sercom.intenclr.write(|w| unsafe { w.bits(pending) });
+        SERCOM_WAKERS[S::NUM].wake();
+        SERCOM_TX_WAKERS[S::NUM].wake();
+    }
+}
+
+/// Register the waker of the task waiting on the SERCOM `S`
+#[cfg(feature = "async")]
+#[inline]
+pub(crate) fn register_waker<S: Sercom>(waker: &core::task::Waker) {
+    SERCOM_WAKERS[S::NUM].register(waker);
+}
+
+/// Register the waker of the task transmitting on the UART of the SERCOM `S`
+#[cfg(feature = "async")]
+#[inline]
+pub(crate) fn register_tx_waker<S: Sercom>(waker: &core::task::Waker) {
+    SERCOM_TX_WAKERS[S::NUM].register(waker);
+}
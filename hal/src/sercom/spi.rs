@@ -319,6 +319,7 @@ use bitflags::bitflags;
 use embedded_hal::spi;
 pub use embedded_hal::spi::{Phase, Polarity, MODE_0, MODE_1, MODE_2, MODE_3};
 
+use crate::clock::{ClockId, Reclock};
 use crate::sercom::*;
 use crate::time::Hertz;
 use crate::typelevel::{Is, NoneT, Sealed};
@@ -371,6 +372,16 @@ pub mod impl_ehal;
 #[path = "spi/impl_ehal_thumbv7em.rs"]
 pub mod impl_ehal;
 
+#[cfg(feature = "ehal-1")]
+#[path = "spi/impl_ehal_1.rs"]
+mod impl_ehal_1;
+
+#[cfg(feature = "async")]
+#[path = "spi/async_api.rs"]
+mod async_api;
+#[cfg(feature = "async")]
+pub use async_api::*;
+
 //=============================================================================
 // BitOrder
 //=============================================================================
@@ -445,6 +456,9 @@ impl TryFrom<Status> for () {
 pub enum Error {
     Overflow,
     LengthError,
+    /// Error of a DMA transfer of [`SpiAsync`]
+    #[cfg(all(feature = "async", feature = "dma"))]
+    Dma(crate::dmac::Error),
 }
 
 //=============================================================================
@@ -644,6 +658,7 @@ where
     mode: PhantomData<M>,
     size: PhantomData<Z>,
     freq: Hertz,
+    baud: Option<Hertz>,
 }
 
 impl<P: ValidPads> Config<P> {
@@ -664,6 +679,7 @@ impl<P: ValidPads> Config<P> {
             mode: PhantomData,
             size: PhantomData,
             freq: freq.into(),
+            baud: None,
         }
     }
 
@@ -717,6 +733,7 @@ where
             mode: PhantomData,
             size: PhantomData,
             freq: self.freq,
+            baud: self.baud,
         }
     }
 
@@ -882,6 +899,8 @@ where
     /// 512. Values outside this range will saturate at the extremes.
     #[inline]
     pub fn set_baud(&mut self, baud: impl Into<Hertz>) {
+        let baud = baud.into();
+        self.baud = Some(baud);
         self.regs.set_baud(self.freq, baud);
     }
 
@@ -998,6 +1017,28 @@ where
     }
 }
 
+impl<P, M, Z> Reclock for Config<P, M, Z>
+where
+    P: ValidPads,
+    M: OpMode,
+    Z: Size,
+{
+    #[inline]
+    fn clock_id(&self) -> ClockId {
+        <P::Sercom as Sercom>::CORE_CLOCK
+    }
+
+    /// Store the new GCLK frequency, and recompute the `BAUD` register from
+    /// the last baud rate set
+    #[inline]
+    fn reclock(&mut self, freq: Hertz) {
+        self.freq = freq;
+        if let Some(baud) = self.baud {
+            self.regs.set_baud(freq, baud);
+        }
+    }
+}
+
 //=============================================================================
 // AnyConfig
 //=============================================================================
@@ -1145,6 +1186,24 @@ where
     }
 }
 
+impl<C, A> Reclock for Spi<C, A>
+where
+    C: ValidConfig,
+    A: Capability,
+{
+    #[inline]
+    fn clock_id(&self) -> ClockId {
+        <C::Sercom as Sercom>::CORE_CLOCK
+    }
+
+    /// Recompute the `BAUD` register for the new GCLK frequency. The SERCOM
+    /// is temporarily disabled, see [`reconfigure`](Spi::reconfigure).
+    #[inline]
+    fn reclock(&mut self, freq: Hertz) {
+        self.reconfigure(|c| c.reclock(freq));
+    }
+}
+
 impl<C, A> Spi<C, A>
 where
     C: ValidConfig,
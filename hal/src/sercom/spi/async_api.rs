@@ -0,0 +1,313 @@
+//! Async SPI driver
+//!
+//! [`SpiAsync`] implements [`SpiBus`] from `embedded-hal-async` for [`Duplex`]
+//! [`Spi`] structs. Without DMA, the words are transferred one by one, waiting
+//! for the `DRE` and `RXC` flags asynchronously. With the `dma` feature, DMA
+//! channels can be attached with [`SpiAsync::with_dma_channels`] to transfer
+//! `u8` words.
+
+use core::future::poll_fn;
+use core::task::Poll;
+
+use embedded_hal::spi::FullDuplex;
+use embedded_hal_async::spi::SpiBus;
+use nb::block;
+
+use crate::ehal_1::spi::ErrorType;
+use crate::sercom::register_waker;
+use crate::typelevel::NoneT;
+
+use super::*;
+
+#[cfg(feature = "dma")]
+use crate::dmac::{AnyChannel, Ready};
+#[cfg(feature = "dma")]
+use crate::sercom::dma::{join, DmaSlice, MAX_BEATS, TRIGGER_ACTION};
+
+/// Async SPI driver, see [`Spi::into_async`]
+///
+/// `R` and `T` are the RX and TX DMA channels, if any.
+pub struct SpiAsync<C, A, R = NoneT, T = NoneT>
+where
+    C: ValidConfig,
+    A: Capability,
+{
+    spi: Spi<C, A>,
+    _rx_channel: R,
+    _tx_channel: T,
+}
+
+impl<C, A> Spi<C, A>
+where
+    C: ValidConfig,
+    A: Capability,
+{
+    /// Turn the `Spi` into an async driver
+    ///
+    /// The interrupt handler(s) of the SERCOM must call
+    /// [`sercom::on_interrupt`](crate::sercom::on_interrupt), and be unmasked
+    /// in the NVIC.
+    #[inline]
+    pub fn into_async(self) -> SpiAsync<C, A> {
+        SpiAsync {
+            spi: self,
+            _rx_channel: NoneT,
+            _tx_channel: NoneT,
+        }
+    }
+}
+
+impl<C, A> SpiAsync<C, A>
+where
+    C: ValidConfig,
+    A: Capability,
+{
+    /// Attach DMA channels, used for the transfers of `u8` words
+    ///
+    /// The `DMAC` interrupt handler(s) must call
+    /// [`dmac::on_interrupt`](crate::dmac::on_interrupt).
+    #[cfg(feature = "dma")]
+    #[inline]
+    pub fn with_dma_channels<R, T>(self, rx_channel: R, tx_channel: T) -> SpiAsync<C, A, R, T>
+    where
+        R: AnyChannel<Status = Ready>,
+        T: AnyChannel<Status = Ready>,
+    {
+        SpiAsync {
+            spi: self.spi,
+            _rx_channel: rx_channel,
+            _tx_channel: tx_channel,
+        }
+    }
+
+    /// Return the blocking driver
+    #[inline]
+    pub fn free(mut self) -> Spi<C, A> {
+        self.spi.disable_interrupts(Flags::all());
+        self.spi
+    }
+}
+
+#[cfg(feature = "dma")]
+impl<C, A, R, T> SpiAsync<C, A, R, T>
+where
+    C: ValidConfig,
+    A: Capability,
+    R: AnyChannel<Status = Ready>,
+    T: AnyChannel<Status = Ready>,
+{
+    /// Return the blocking driver and the DMA channels
+    #[inline]
+    pub fn free(mut self) -> (Spi<C, A>, R, T) {
+        self.spi.disable_interrupts(Flags::all());
+        (self.spi, self._rx_channel, self._tx_channel)
+    }
+}
+
+impl<C, A, R, T> SpiAsync<C, A, R, T>
+where
+    C: ValidConfig,
+    A: Capability,
+{
+    /// Wait until one of the `flags` is set, and return the flags set
+    async fn wait_flags(&mut self, flags: Flags) -> Flags {
+        poll_fn(|cx| {
+            register_waker::<C::Sercom>(cx.waker());
+            let set = self.spi.read_flags() & flags;
+            if set.is_empty() {
+                self.spi.enable_interrupts(flags);
+                Poll::Pending
+            } else {
+                self.spi.disable_interrupts(flags);
+                Poll::Ready(set)
+            }
+        })
+        .await
+    }
+}
+
+impl<C, A, R, T> ErrorType for SpiAsync<C, A, R, T>
+where
+    C: ValidConfig,
+    A: Capability,
+{
+    type Error = Error;
+}
+
+//=============================================================================
+// Interrupt driven transfers
+//=============================================================================
+
+impl<C> SpiAsync<C, Duplex>
+where
+    C: ValidConfig,
+    C::Word: Copy,
+    Spi<C, Duplex>: FullDuplex<C::Word, Error = Error>,
+{
+    /// Send `word` and return the word received
+    async fn transfer_word(&mut self, word: C::Word) -> Result<C::Word, Error> {
+        self.wait_flags(Flags::DRE | Flags::ERROR).await;
+        block!(self.spi.send(word))?;
+        self.wait_flags(Flags::RXC | Flags::ERROR).await;
+        block!(FullDuplex::read(&mut self.spi))
+    }
+}
+
+impl<C> SpiBus<C::Word> for SpiAsync<C, Duplex>
+where
+    C: ValidConfig,
+    C::Word: Copy + Default + 'static,
+    Spi<C, Duplex>: FullDuplex<C::Word, Error = Error>,
+{
+    async fn read(&mut self, words: &mut [C::Word]) -> Result<(), Error> {
+        for word in words.iter_mut() {
+            *word = self.transfer_word(C::Word::default()).await?;
+        }
+        Ok(())
+    }
+
+    async fn write(&mut self, words: &[C::Word]) -> Result<(), Error> {
+        for word in words {
+            self.transfer_word(*word).await?;
+        }
+        Ok(())
+    }
+
+    /// Transfer `write` while receiving into `read`
+    ///
+    /// If `write` is shorter than `read`, the default word (`0`) is sent for
+    /// the remaining words. If `read` is shorter, the remaining words received
+    /// are discarded.
+    async fn transfer(&mut self, read: &mut [C::Word], write: &[C::Word]) -> Result<(), Error> {
+        for i in 0..read.len().max(write.len()) {
+            let word = self
+                .transfer_word(write.get(i).copied().unwrap_or_default())
+                .await?;
+            if let Some(dest) = read.get_mut(i) {
+                *dest = word;
+            }
+        }
+        Ok(())
+    }
+
+    async fn transfer_in_place(&mut self, words: &mut [C::Word]) -> Result<(), Error> {
+        for word in words.iter_mut() {
+            *word = self.transfer_word(*word).await?;
+        }
+        Ok(())
+    }
+
+    /// Every word is received before the next one is sent, so there is never a
+    /// transfer in progress
+    async fn flush(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+//=============================================================================
+// DMA transfers
+//=============================================================================
+
+#[cfg(feature = "dma")]
+impl<C, R, T> SpiAsync<C, Duplex, R, T>
+where
+    C: ValidConfig<Word = u8>,
+    C::Size: Size<Word = u8>,
+    R: AnyChannel<Status = Ready>,
+    T: AnyChannel<Status = Ready>,
+{
+    /// Receive into `read` while sending `write`, in transfers of at most
+    /// [`MAX_BEATS`] words. One of them may be a single non-incrementing word.
+    async fn transfer_dma(
+        &mut self,
+        mut read: DmaSlice<u8>,
+        mut write: DmaSlice<u8>,
+    ) -> Result<(), Error> {
+        let mut remaining = read.len().max(write.len());
+        while remaining > 0 {
+            let len = remaining.min(MAX_BEATS);
+            let data = self.spi.data_ptr();
+            // SAFETY: the buffers are borrowed until the transfers complete,
+            // and the transfers are stopped if the futures are dropped
+            let (rx, tx) = unsafe {
+                join(
+                    self._rx_channel.as_mut().transfer_future(
+                        DmaSlice::single(data),
+                        read.take(len),
+                        C::Sercom::DMA_RX_TRIGGER,
+                        TRIGGER_ACTION,
+                    ),
+                    self._tx_channel.as_mut().transfer_future(
+                        write.take(len),
+                        DmaSlice::single(data),
+                        C::Sercom::DMA_TX_TRIGGER,
+                        TRIGGER_ACTION,
+                    ),
+                )
+                .await
+            };
+            rx.and(tx).map_err(Error::Dma)?;
+            remaining -= len;
+        }
+        self.spi.read_flags_errors()?;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "dma")]
+impl<C, R, T> SpiBus<u8> for SpiAsync<C, Duplex, R, T>
+where
+    C: ValidConfig<Word = u8>,
+    C::Size: Size<Word = u8>,
+    R: AnyChannel<Status = Ready>,
+    T: AnyChannel<Status = Ready>,
+{
+    async fn read(&mut self, words: &mut [u8]) -> Result<(), Error> {
+        if words.is_empty() {
+            return Ok(());
+        }
+        let mut zero = 0;
+        self.transfer_dma(DmaSlice::from_mut(words), DmaSlice::single(&mut zero))
+            .await
+    }
+
+    async fn write(&mut self, words: &[u8]) -> Result<(), Error> {
+        if words.is_empty() {
+            return Ok(());
+        }
+        let mut sink = 0;
+        self.transfer_dma(DmaSlice::single(&mut sink), DmaSlice::from_slice(words))
+            .await
+    }
+
+    /// Transfer `write` while receiving into `read`
+    ///
+    /// If `write` is shorter than `read`, `0` is sent for the remaining words.
+    /// If `read` is shorter, the remaining words received are discarded.
+    async fn transfer(&mut self, read: &mut [u8], write: &[u8]) -> Result<(), Error> {
+        let len = read.len().min(write.len());
+        let (read, read_rest) = read.split_at_mut(len);
+        let (write, write_rest) = write.split_at(len);
+        self.transfer_dma(DmaSlice::from_mut(read), DmaSlice::from_slice(write))
+            .await?;
+        if !read_rest.is_empty() {
+            self.read(read_rest).await?;
+        }
+        if !write_rest.is_empty() {
+            self.write(write_rest).await?;
+        }
+        Ok(())
+    }
+
+    async fn transfer_in_place(&mut self, words: &mut [u8]) -> Result<(), Error> {
+        // Each word is sent before the word received is written at its place
+        let write = DmaSlice::from_slice(words);
+        self.transfer_dma(DmaSlice::from_mut(words), write).await
+    }
+
+    /// The transfers complete when the last word is received, so there is
+    /// never a transfer in progress
+    async fn flush(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+}
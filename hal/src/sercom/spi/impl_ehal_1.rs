@@ -0,0 +1,91 @@
+//! Implement [`embedded_hal_1`](crate::ehal_1) traits for [`Spi`] structs
+//!
+//! [`SpiBus`] is implemented for [`Duplex`] [`Spi`] structs that implement
+//! `spi::FullDuplex` from embedded HAL 0.2, i.e. for every transaction length
+//! that can be read and written in a single access to the `DATA` register. The
+//! transfers are completed word by word.
+
+use embedded_hal::spi::FullDuplex;
+use nb::block;
+
+use crate::ehal_1::spi::{self, ErrorKind, ErrorType, SpiBus};
+
+use super::*;
+
+impl spi::Error for Error {
+    #[inline]
+    fn kind(&self) -> ErrorKind {
+        match self {
+            Error::Overflow => ErrorKind::Overrun,
+            Error::LengthError => ErrorKind::Other,
+            #[cfg(all(feature = "async", feature = "dma"))]
+            Error::Dma(_) => ErrorKind::Other,
+        }
+    }
+}
+
+impl<C, A> ErrorType for Spi<C, A>
+where
+    C: ValidConfig,
+    A: Capability,
+{
+    type Error = Error;
+}
+
+impl<C> SpiBus<C::Word> for Spi<C, Duplex>
+where
+    C: ValidConfig,
+    C::Word: Copy + Default,
+    Self: FullDuplex<C::Word, Error = Error>,
+{
+    #[inline]
+    fn read(&mut self, words: &mut [C::Word]) -> Result<(), Error> {
+        for word in words.iter_mut() {
+            block!(self.send(C::Word::default()))?;
+            *word = block!(FullDuplex::read(self))?;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn write(&mut self, words: &[C::Word]) -> Result<(), Error> {
+        for word in words {
+            block!(self.send(*word))?;
+            block!(FullDuplex::read(self))?;
+        }
+        Ok(())
+    }
+
+    /// Transfer `write` while receiving into `read`
+    ///
+    /// If `write` is shorter than `read`, the default word (`0`) is sent for
+    /// the remaining words. If `read` is shorter, the remaining words received
+    /// are discarded.
+    #[inline]
+    fn transfer(&mut self, read: &mut [C::Word], write: &[C::Word]) -> Result<(), Error> {
+        for i in 0..read.len().max(write.len()) {
+            block!(self.send(write.get(i).copied().unwrap_or_default()))?;
+            let word = block!(FullDuplex::read(self))?;
+            if let Some(dest) = read.get_mut(i) {
+                *dest = word;
+            }
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn transfer_in_place(&mut self, words: &mut [C::Word]) -> Result<(), Error> {
+        for word in words.iter_mut() {
+            block!(self.send(*word))?;
+            *word = block!(FullDuplex::read(self))?;
+        }
+        Ok(())
+    }
+
+    /// Every word is received before the next one is sent, so there is never a
+    /// transfer in progress
+    #[inline]
+    fn flush(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+}
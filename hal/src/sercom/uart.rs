@@ -411,7 +411,17 @@ pub use config::*;
 
 pub mod impl_ehal;
 
-use crate::{sercom::*, typelevel::Sealed};
+#[cfg(feature = "async")]
+mod async_api;
+#[cfg(feature = "async")]
+pub use async_api::*;
+
+use crate::{
+    clock::{ClockId, Reclock},
+    sercom::*,
+    time::Hertz,
+    typelevel::Sealed,
+};
 use core::{convert::TryInto, marker::PhantomData};
 use num_traits::AsPrimitive;
 
@@ -606,6 +616,8 @@ where
 {
     config: C,
     capability: PhantomData<D>,
+    /// Whether words were written since the last flush
+    tx_pending: bool,
 }
 
 impl<C, D> Uart<C, D>
@@ -743,6 +755,24 @@ where
     }
 }
 
+impl<C, D> Reclock for Uart<C, D>
+where
+    C: ValidConfig,
+    D: Capability,
+{
+    #[inline]
+    fn clock_id(&self) -> ClockId {
+        <C::Sercom as Sercom>::CORE_CLOCK
+    }
+
+    /// Recompute the `BAUD` register for the new GCLK frequency. The SERCOM
+    /// is temporarily disabled, see [`reconfigure`](Uart::reconfigure).
+    #[inline]
+    fn reclock(&mut self, freq: Hertz) {
+        self._reconfigure(|c| c.reclock(freq));
+    }
+}
+
 impl<C, D> Uart<C, D>
 where
     C: ValidConfig,
@@ -824,10 +854,12 @@ where
             Uart {
                 config: self.config,
                 capability: PhantomData,
+                tx_pending: false,
             },
             Uart {
                 config,
                 capability: PhantomData,
+                tx_pending: self.tx_pending,
             },
         )
     }
@@ -860,10 +892,11 @@ where
 
     /// Join [`RxDuplex`] and [`TxDuplex`] halves back into a full `Uart<C,
     /// Duplex>`
-    pub fn join(rx: Uart<C, RxDuplex>, _tx: Uart<C, TxDuplex>) -> Self {
+    pub fn join(rx: Uart<C, RxDuplex>, tx: Uart<C, TxDuplex>) -> Self {
         Self {
             config: rx.config,
             capability: PhantomData,
+            tx_pending: tx.tx_pending,
         }
     }
 }
@@ -966,4 +999,12 @@ where
     pub unsafe fn write_data(&mut self, data: DataReg) {
         self.config.as_mut().registers.write_data(data);
     }
+
+    /// Clear the `TXC` flag before writing, so that a flush waits for the
+    /// words written next
+    #[inline]
+    pub(super) fn start_transmit(&mut self) {
+        self.clear_flags(Flags::TXC);
+        self.tx_pending = true;
+    }
 }
@@ -0,0 +1,300 @@
+//! Async UART driver
+//!
+//! [`UartAsync`] implements [`Read`] and [`Write`] from `embedded-io-async`
+//! for UARTs with `u8` words. Without DMA, the bytes are transferred one by
+//! one, waiting for the `RXC` and `DRE` flags asynchronously. With the `dma`
+//! feature, DMA channels can be attached with
+//! [`UartAsync::with_rx_dma_channel`] and [`UartAsync::with_tx_dma_channel`].
+//!
+//! The halves of a split [`Duplex`](super::Duplex) [`Uart`] can be used in
+//! different tasks.
+
+use core::future::poll_fn;
+use core::task::{Poll, Waker};
+
+use embedded_io_async::{ErrorType, Read, Write};
+
+use super::{Capability, Error, Flags, Receive, Status, Transmit, Uart, ValidConfig};
+use crate::sercom::{register_tx_waker, register_waker};
+use crate::typelevel::NoneT;
+
+#[cfg(feature = "dma")]
+use super::Sercom;
+#[cfg(feature = "dma")]
+use crate::dmac::{AnyChannel, Ready};
+#[cfg(feature = "dma")]
+use crate::sercom::dma::{DmaSlice, MAX_BEATS, TRIGGER_ACTION};
+
+/// Async UART driver, see [`Uart::into_async`]
+///
+/// `R` and `T` are the RX and TX DMA channels, if any.
+pub struct UartAsync<C, D, R = NoneT, T = NoneT>
+where
+    C: ValidConfig,
+    D: Capability,
+{
+    uart: Uart<C, D>,
+    _rx_channel: R,
+    _tx_channel: T,
+}
+
+impl<C, D> Uart<C, D>
+where
+    C: ValidConfig,
+    D: Capability,
+{
+    /// Turn the `Uart` into an async driver
+    ///
+    /// The interrupt handler(s) of the SERCOM must call
+    /// [`sercom::on_interrupt`](crate::sercom::on_interrupt), and be unmasked
+    /// in the NVIC.
+    #[inline]
+    pub fn into_async(self) -> UartAsync<C, D> {
+        UartAsync {
+            uart: self,
+            _rx_channel: NoneT,
+            _tx_channel: NoneT,
+        }
+    }
+}
+
+impl<C, D, T> UartAsync<C, D, NoneT, T>
+where
+    C: ValidConfig,
+    D: Receive,
+{
+    /// Attach a DMA channel, used to receive
+    ///
+    /// A read then fills the whole buffer. The `DMAC` interrupt handler(s)
+    /// must call [`dmac::on_interrupt`](crate::dmac::on_interrupt).
+    #[cfg(feature = "dma")]
+    #[inline]
+    pub fn with_rx_dma_channel<R>(self, rx_channel: R) -> UartAsync<C, D, R, T>
+    where
+        R: AnyChannel<Status = Ready>,
+    {
+        UartAsync {
+            uart: self.uart,
+            _rx_channel: rx_channel,
+            _tx_channel: self._tx_channel,
+        }
+    }
+}
+
+impl<C, D, R> UartAsync<C, D, R, NoneT>
+where
+    C: ValidConfig,
+    D: Transmit,
+{
+    /// Attach a DMA channel, used to transmit
+    ///
+    /// A write then sends the whole buffer. The `DMAC` interrupt handler(s)
+    /// must call [`dmac::on_interrupt`](crate::dmac::on_interrupt).
+    #[cfg(feature = "dma")]
+    #[inline]
+    pub fn with_tx_dma_channel<T>(self, tx_channel: T) -> UartAsync<C, D, R, T>
+    where
+        T: AnyChannel<Status = Ready>,
+    {
+        UartAsync {
+            uart: self.uart,
+            _rx_channel: self._rx_channel,
+            _tx_channel: tx_channel,
+        }
+    }
+}
+
+impl<C, D, R, T> UartAsync<C, D, R, T>
+where
+    C: ValidConfig,
+    D: Capability,
+{
+    /// Return the blocking driver and the DMA channels, if any
+    #[inline]
+    pub fn free(mut self) -> (Uart<C, D>, R, T) {
+        self.uart.disable_interrupts(Flags::all());
+        (self.uart, self._rx_channel, self._tx_channel)
+    }
+
+    /// Wait until one of the `flags` is set, and return the flags set
+    async fn wait_flags(&mut self, flags: Flags, register: fn(&Waker)) -> Flags {
+        poll_fn(|cx| {
+            register(cx.waker());
+            let set = self.uart.read_flags() & flags;
+            if set.is_empty() {
+                self.uart.enable_interrupts(flags);
+                Poll::Pending
+            } else {
+                self.uart.disable_interrupts(flags);
+                Poll::Ready(set)
+            }
+        })
+        .await
+    }
+
+    /// Wait until the bytes written since the last flush are transmitted
+    async fn wait_transmitted(&mut self) {
+        if self.uart.tx_pending {
+            self.wait_flags(Flags::TXC, register_tx_waker::<C::Sercom>)
+                .await;
+            self.uart.tx_pending = false;
+        }
+    }
+}
+
+impl<C, D, R, T> UartAsync<C, D, R, T>
+where
+    C: ValidConfig,
+    D: Receive,
+{
+    /// Clear the error flag and status of `error`, so that the next read
+    /// can succeed
+    #[inline]
+    fn clear_error(&mut self, error: Error) {
+        self.uart.clear_flags(Flags::ERROR);
+        self.uart.clear_status(Status::from(error));
+    }
+}
+
+impl<C, D, R, T> ErrorType for UartAsync<C, D, R, T>
+where
+    C: ValidConfig,
+    D: Capability,
+{
+    type Error = Error;
+}
+
+//=============================================================================
+// Interrupt driven transfers
+//=============================================================================
+
+impl<C, D, T> Read for UartAsync<C, D, NoneT, T>
+where
+    C: ValidConfig<Word = u8>,
+    D: Receive,
+{
+    /// Wait for at least one byte, then read the bytes already received
+    ///
+    /// An error is only returned if no byte was read, otherwise it is
+    /// returned by the next call. The error is cleared when returned.
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        self.wait_flags(Flags::RXC | Flags::ERROR, register_waker::<C::Sercom>)
+            .await;
+        embedded_io::Read::read(&mut self.uart, buf).map_err(|error| {
+            self.clear_error(error);
+            error
+        })
+    }
+}
+
+impl<C, D, R> Write for UartAsync<C, D, R, NoneT>
+where
+    C: ValidConfig<Word = u8>,
+    D: Transmit,
+{
+    /// Wait until the first byte can be written, then write the bytes that
+    /// fit in the transmit buffer
+    async fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        self.wait_flags(Flags::DRE, register_tx_waker::<C::Sercom>)
+            .await;
+        embedded_io::Write::write(&mut self.uart, buf)
+    }
+
+    /// Wait until all the bytes written have been transmitted
+    async fn flush(&mut self) -> Result<(), Error> {
+        self.wait_transmitted().await;
+        Ok(())
+    }
+}
+
+//=============================================================================
+// DMA transfers
+//=============================================================================
+
+#[cfg(feature = "dma")]
+impl<C, D, R, T> Read for UartAsync<C, D, R, T>
+where
+    C: ValidConfig<Word = u8>,
+    D: Receive,
+    R: AnyChannel<Status = Ready>,
+{
+    /// Fill `buf` with the bytes received
+    ///
+    /// The error is cleared when returned.
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+        let mut dest = DmaSlice::from_mut(buf);
+        let mut remaining = dest.len();
+        while remaining > 0 {
+            let len = remaining.min(MAX_BEATS);
+            let data = self.uart.data_ptr();
+            // SAFETY: the buffer is borrowed until the transfer completes, and
+            // the transfer is stopped if the future is dropped
+            unsafe {
+                self._rx_channel
+                    .as_mut()
+                    .transfer_future(
+                        DmaSlice::single(data),
+                        dest.take(len),
+                        C::Sercom::DMA_RX_TRIGGER,
+                        TRIGGER_ACTION,
+                    )
+                    .await
+                    .map_err(Error::Dma)?;
+            }
+            remaining -= len;
+        }
+        let status: Result<(), Error> = self.uart.read_status().try_into();
+        if let Err(error) = status {
+            self.clear_error(error);
+            return Err(error);
+        }
+        Ok(buf.len())
+    }
+}
+
+#[cfg(feature = "dma")]
+impl<C, D, R, T> Write for UartAsync<C, D, R, T>
+where
+    C: ValidConfig<Word = u8>,
+    D: Transmit,
+    T: AnyChannel<Status = Ready>,
+{
+    /// Send the whole `buf`
+    async fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+        let mut source = DmaSlice::from_slice(buf);
+        let mut remaining = source.len();
+        while remaining > 0 {
+            let len = remaining.min(MAX_BEATS);
+            let data = self.uart.data_ptr();
+            self.uart.start_transmit();
+            // SAFETY: the buffer is borrowed until the transfer completes, and
+            // the transfer is stopped if the future is dropped
+            unsafe {
+                self._tx_channel
+                    .as_mut()
+                    .transfer_future(
+                        source.take(len),
+                        DmaSlice::single(data),
+                        C::Sercom::DMA_TX_TRIGGER,
+                        TRIGGER_ACTION,
+                    )
+                    .await
+                    .map_err(Error::Dma)?;
+            }
+            remaining -= len;
+        }
+        Ok(buf.len())
+    }
+
+    /// Wait until all the bytes written have been transmitted
+    async fn flush(&mut self) -> Result<(), Error> {
+        self.wait_transmitted().await;
+        Ok(())
+    }
+}
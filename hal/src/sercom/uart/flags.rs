@@ -88,6 +88,9 @@ pub enum Error {
     InconsistentSyncField,
     /// Detected a collision
     CollisionDetected,
+    /// Error of a DMA transfer of [`UartAsync`](super::UartAsync)
+    #[cfg(all(feature = "async", feature = "dma"))]
+    Dma(crate::dmac::Error),
 }
 
 impl TryFrom<Status> for () {
@@ -122,6 +125,8 @@ impl From<Error> for Status {
             Overflow => Status::BUFOVF,
             InconsistentSyncField => Status::ISF,
             CollisionDetected => Status::COLL,
+            #[cfg(all(feature = "async", feature = "dma"))]
+            Dma(_) => Status::empty(),
         }
     }
 }
@@ -1,4 +1,7 @@
 //! `embedded-hal` trait implementations for [`Uart`]s
+//!
+//! With the `ehal-1` feature, [`Uart`]s with 8-bit words also implement the
+//! [`embedded_io`](crate::embedded_io) `Read` and `Write` traits.
 
 use super::{DataReg, Error, Flags, Receive, Transmit, Uart, ValidConfig};
 use embedded_hal::{
@@ -39,6 +42,7 @@ where
     #[inline]
     fn write(&mut self, word: C::Word) -> nb::Result<(), Self::Error> {
         if self.read_flags().contains(Flags::DRE) {
+            self.start_transmit();
             unsafe { self.write_data(word.as_()) };
             Ok(())
         } else {
@@ -46,11 +50,14 @@ where
         }
     }
 
-    /// Wait for a `TXC` flag
+    /// Wait for a `TXC` flag, if words were written since the last flush
     #[inline]
     fn flush(&mut self) -> nb::Result<(), Self::Error> {
-        if self.read_flags().contains(Flags::TXC) {
+        if !self.tx_pending {
+            Ok(())
+        } else if self.read_flags().contains(Flags::TXC) {
             self.clear_flags(Flags::TXC);
+            self.tx_pending = false;
             Ok(())
         } else {
             Err(WouldBlock)
@@ -65,3 +72,110 @@ where
     Uart<C, D>: Write<C::Word>,
 {
 }
+
+#[cfg(feature = "ehal-1")]
+mod embedded_io {
+    use super::super::{Capability, Error, Flags, Receive, Transmit, Uart, ValidConfig};
+    use crate::embedded_io::{ErrorKind, ErrorType, Read, ReadReady, Write, WriteReady};
+
+    impl crate::embedded_io::Error for Error {
+        #[inline]
+        fn kind(&self) -> ErrorKind {
+            match self {
+                Error::ParityError | Error::FrameError | Error::InconsistentSyncField => {
+                    ErrorKind::InvalidData
+                }
+                Error::Overflow | Error::CollisionDetected => ErrorKind::Other,
+                #[cfg(all(feature = "async", feature = "dma"))]
+                Error::Dma(_) => ErrorKind::Other,
+            }
+        }
+    }
+
+    impl<C, D> ErrorType for Uart<C, D>
+    where
+        C: ValidConfig,
+        D: Capability,
+    {
+        type Error = Error;
+    }
+
+    impl<C, D> Read for Uart<C, D>
+    where
+        C: ValidConfig<Word = u8>,
+        D: Receive,
+    {
+        /// Wait for at least one byte, then read the bytes already received
+        ///
+        /// An error is only returned if no byte was read, otherwise it is
+        /// returned by the next call.
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+            let mut count = 0;
+            while count < buf.len() {
+                match self.read_flags_errors() {
+                    Ok(flags) if flags.contains(Flags::RXC) => {
+                        buf[count] = unsafe { self.read_data() as u8 };
+                        count += 1;
+                    }
+                    Ok(_) if count == 0 => (),
+                    Err(error) if count == 0 => return Err(error),
+                    _ => break,
+                }
+            }
+            Ok(count)
+        }
+    }
+
+    impl<C, D> ReadReady for Uart<C, D>
+    where
+        C: ValidConfig<Word = u8>,
+        D: Receive,
+    {
+        #[inline]
+        fn read_ready(&mut self) -> Result<bool, Error> {
+            Ok(self.read_flags_errors()?.contains(Flags::RXC))
+        }
+    }
+
+    impl<C, D> Write for Uart<C, D>
+    where
+        C: ValidConfig<Word = u8>,
+        D: Transmit,
+    {
+        /// Wait until the first byte can be written, then write the bytes that
+        /// fit in the transmit buffer
+        fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+            let mut count = 0;
+            while count < buf.len() {
+                if self.read_flags().contains(Flags::DRE) {
+                    self.start_transmit();
+                    unsafe { self.write_data(buf[count].into()) };
+                    count += 1;
+                } else if count > 0 {
+                    break;
+                }
+            }
+            Ok(count)
+        }
+
+        /// Wait until all the bytes written have been transmitted
+        fn flush(&mut self) -> Result<(), Error> {
+            if self.tx_pending {
+                while !self.read_flags().contains(Flags::TXC) {}
+                self.tx_pending = false;
+            }
+            Ok(())
+        }
+    }
+
+    impl<C, D> WriteReady for Uart<C, D>
+    where
+        C: ValidConfig<Word = u8>,
+        D: Transmit,
+    {
+        #[inline]
+        fn write_ready(&mut self) -> Result<bool, Error> {
+            Ok(self.read_flags().contains(Flags::DRE))
+        }
+    }
+}
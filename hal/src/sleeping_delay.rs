@@ -1,9 +1,13 @@
 //! Delays with WFI sleep while we wait using a timer
+//!
+//! `WFI` enters the sleep mode currently selected. On SAMx5x, choose it
+//! beforehand with `pm::Pm::set_sleep_mode`. On SAMD11/SAMD21, it is selected
+//! by `PM.SLEEP` and the `SLEEPDEEP` bit, see the `pm` module.
 use core::sync::atomic;
 use cortex_m::asm;
 
 use crate::ehal::blocking::delay::{DelayMs, DelayUs};
-use crate::time::U32Ext;
+use crate::time::{Microseconds, U32Ext};
 use crate::timer_traits::InterruptDrivenTimer;
 
 const NUM_US_IN_S: u32 = 1_000_000;
@@ -33,6 +37,11 @@ where
     pub fn free(self) -> TIM {
         self.timer
     }
+
+    /// Delay for `duration`, e.g. a `fugit` duration
+    pub fn delay(&mut self, duration: impl Into<Microseconds>) {
+        self.delay_us(duration.into().0);
+    }
 }
 
 impl<TIM, TYPE> DelayUs<TYPE> for SleepingDelay<TIM>
@@ -0,0 +1,1173 @@
+//! # Timer/counter (TC)
+//!
+//! [`Counter`] exposes the TC peripherals as plain counters, in the three
+//! counter modes of the hardware:
+//!
+//! * [`Count8`]: 8-bit counter, with a period register (`PER`) setting its top
+//!   value.
+//! * [`Count16`]: 16-bit counter.
+//! * [`Count32`]: 32-bit counter, made of two paired instances. The
+//!   lower-numbered instance is the master and holds the whole configuration,
+//!   the other one is the slave and is taken by the driver so that it cannot
+//!   be used on its own. The available pairs are:
+//!   * SAMD11: TC1 + TC2
+//!   * SAMD21: TC4 + TC5, TC6 + TC7
+//!   * SAMx5x: TC0 + TC1, TC2 + TC3, TC4 + TC5, TC6 + TC7
+//!
+//! The counter counts up or down, either continuously or once
+//! ([`Config::one_shot`]), and gives access to its two compare/capture
+//! channels. In [`Count16`] and [`Count32`] modes, setting a top value with
+//! [`Counter::set_top`] uses the channel 0 as period, which leaves only the
+//! channel 1 for compare or capture.
+//!
+//! For a simple periodic timeout, see [`TimerCounter`](crate::timer) instead.
+//! With the `rtic` feature, a counter extended to 64 bits can be used as an
+//! RTIC `Monotonic` timer.
+//!
+//! ```no_run
+//! # use atsamd_hal::clock::GenericClockController;
+//! # use atsamd_hal::pac::Peripherals;
+//! # use atsamd_hal::tc::*;
+//! let mut peripherals = Peripherals::take().unwrap();
+//! let mut clocks = GenericClockController::with_external_32kosc(
+//!     peripherals.GCLK,
+//!     &mut peripherals.MCLK,
+//!     &mut peripherals.OSC32KCTRL,
+//!     &mut peripherals.OSCCTRL,
+//!     &mut peripherals.NVMCTRL,
+//! );
+//! let gclk0 = clocks.gclk0();
+//! let clock = clocks.tc2_tc3(&gclk0).unwrap();
+//! // Free running 32-bit counter at 120MHz / 8 = 15MHz
+//! let mut counter = Counter::count32(
+//!     peripherals.TC2,
+//!     peripherals.TC3,
+//!     &clock,
+//!     &mut peripherals.MCLK,
+//!     Config::new().prescaler(Prescaler::DIV8),
+//! );
+//! counter.enable();
+//! let start = counter.count();
+//! ```
+
+use core::marker::PhantomData;
+
+use bitflags::bitflags;
+
+use crate::capture;
+use crate::clock::{self, ClockId, PeripheralClock, Reclock};
+#[cfg(all(feature = "unproven", feature = "dma"))]
+use crate::dmac::TriggerSource;
+use crate::tcc::prescaler_divider;
+use crate::time::Hertz;
+use crate::typelevel::Sealed;
+
+#[cfg(feature = "min-samd51g")]
+use crate::pac::{tc0 as tc, MCLK as Apb};
+#[cfg(feature = "samd11")]
+use crate::pac::{tc1 as tc, PM as Apb};
+#[cfg(feature = "samd21")]
+use crate::pac::{tc3 as tc, PM as Apb};
+
+#[cfg(any(feature = "samd11", feature = "samd21"))]
+use tc::count16::ctrla::WAVEGEN_A as Wavegen;
+#[cfg(feature = "min-samd51g")]
+use tc::count16::wave::WAVEGEN_A as Wavegen;
+
+use tc::count16::ctrla::MODE_A;
+use tc::RegisterBlock;
+
+pub use tc::count16::ctrla::PRESCALER_A as Prescaler;
+pub use tc::count16::evctrl::EVACT_A as EventAction;
+
+/// Address of the `COUNT` register, for read requests
+#[cfg(any(feature = "samd11", feature = "samd21"))]
+const COUNT_ADDR: u8 = 0x10;
+
+bitflags! {
+    /// Interrupt bit flags of a TC
+    ///
+    /// The binary format of the underlying bits exactly matches the `INTFLAG`
+    /// bits.
+    pub struct Flags: u8 {
+        /// Overflow or underflow
+        const OVF = 0x01;
+        /// A capture was lost, since the channel held a value not read yet
+        const ERR = 0x02;
+        /// Match or capture on channel 0
+        const MC0 = 0x10;
+        /// Match or capture on channel 1
+        const MC1 = 0x20;
+    }
+}
+
+//==============================================================================
+// Instances
+//==============================================================================
+
+/// A TC instance
+pub trait Instance: Sealed {
+    /// Peripheral clock token of the instance
+    type Clock: PeripheralClock;
+
+    #[doc(hidden)]
+    fn register_block(&self) -> &RegisterBlock;
+
+    #[doc(hidden)]
+    fn clock_freq(clock: &Self::Clock) -> Hertz;
+
+    #[doc(hidden)]
+    fn enable_apb(apb: &mut Apb, enable: bool);
+
+    /// EVSYS user of the event input
+    #[doc(hidden)]
+    const EVSYS_USER: u8;
+
+    /// Position of the instance on its peripheral channel, see
+    /// [`Reclock::channel_users`]
+    #[doc(hidden)]
+    const CHANNEL_USERS: u8;
+
+    /// DMA triggers of the compare/capture channels
+    #[cfg(all(feature = "unproven", feature = "dma"))]
+    #[doc(hidden)]
+    const DMA_TRIGGERS: [TriggerSource; 2];
+}
+
+/// A TC instance that can be paired with the next one to count on 32 bits
+pub trait Master: Instance {
+    /// The instance used as slave
+    type Slave: Instance;
+}
+
+/// The TC instances used by a [`Counter`]: a single instance, or a
+/// `(master, slave)` pair in [`Count32`] mode
+pub trait Resource: Sealed {
+    /// Peripheral clock token of the instances
+    type Clock: PeripheralClock;
+
+    #[doc(hidden)]
+    fn register_block(&self) -> &RegisterBlock;
+
+    #[doc(hidden)]
+    fn clock_freq(clock: &Self::Clock) -> Hertz;
+
+    #[doc(hidden)]
+    fn enable_apb(apb: &mut Apb, enable: bool);
+
+    #[doc(hidden)]
+    const EVSYS_USER: u8;
+
+    #[doc(hidden)]
+    const CHANNEL_USERS: u8;
+
+    #[cfg(all(feature = "unproven", feature = "dma"))]
+    #[doc(hidden)]
+    const DMA_TRIGGERS: [TriggerSource; 2];
+}
+
+impl<I: Instance> Resource for I {
+    type Clock = I::Clock;
+
+    const EVSYS_USER: u8 = I::EVSYS_USER;
+
+    const CHANNEL_USERS: u8 = I::CHANNEL_USERS;
+
+    #[cfg(all(feature = "unproven", feature = "dma"))]
+    const DMA_TRIGGERS: [TriggerSource; 2] = I::DMA_TRIGGERS;
+
+    #[inline]
+    fn register_block(&self) -> &RegisterBlock {
+        Instance::register_block(self)
+    }
+
+    #[inline]
+    fn clock_freq(clock: &Self::Clock) -> Hertz {
+        <I as Instance>::clock_freq(clock)
+    }
+
+    #[inline]
+    fn enable_apb(apb: &mut Apb, enable: bool) {
+        <I as Instance>::enable_apb(apb, enable)
+    }
+}
+
+impl<M: Master> Sealed for (M, M::Slave) {}
+
+impl<M: Master> Resource for (M, M::Slave) {
+    type Clock = M::Clock;
+
+    // The events and DMA requests come from the master
+    const EVSYS_USER: u8 = M::EVSYS_USER;
+
+    // Both instances are on the same channel
+    const CHANNEL_USERS: u8 = M::CHANNEL_USERS | <M::Slave as Instance>::CHANNEL_USERS;
+
+    #[cfg(all(feature = "unproven", feature = "dma"))]
+    const DMA_TRIGGERS: [TriggerSource; 2] = M::DMA_TRIGGERS;
+
+    #[inline]
+    fn register_block(&self) -> &RegisterBlock {
+        Instance::register_block(&self.0)
+    }
+
+    #[inline]
+    fn clock_freq(clock: &Self::Clock) -> Hertz {
+        <M as Instance>::clock_freq(clock)
+    }
+
+    #[inline]
+    fn enable_apb(apb: &mut Apb, enable: bool) {
+        <M as Instance>::enable_apb(apb, enable);
+        <M::Slave as Instance>::enable_apb(apb, enable);
+    }
+}
+
+macro_rules! tc_instance {
+    ($($TC:ident: ($Clock:ident, $users:expr, $apbmask:ident, $apb:ident, $user:expr, [$mc0:ident, $mc1:ident]),)+) => {
+        $(
+            impl Sealed for crate::pac::$TC {}
+
+            impl Instance for crate::pac::$TC {
+                type Clock = clock::$Clock;
+
+                const EVSYS_USER: u8 = $user;
+
+                const CHANNEL_USERS: u8 = $users;
+
+                #[cfg(all(feature = "unproven", feature = "dma"))]
+                const DMA_TRIGGERS: [TriggerSource; 2] =
+                    [TriggerSource::$mc0, TriggerSource::$mc1];
+
+                #[inline]
+                fn register_block(&self) -> &RegisterBlock {
+                    self
+                }
+
+                #[inline]
+                fn clock_freq(clock: &Self::Clock) -> Hertz {
+                    clock.freq()
+                }
+
+                #[inline]
+                fn enable_apb(apb: &mut Apb, enable: bool) {
+                    apb.$apbmask.modify(|_, w| w.$apb().bit(enable));
+                }
+            }
+        )+
+    };
+}
+
+macro_rules! tc_pair {
+    ($($Master:ident + $Slave:ident,)+) => {
+        $(
+            impl Master for crate::pac::$Master {
+                type Slave = crate::pac::$Slave;
+            }
+        )+
+    };
+}
+
+#[cfg(feature = "samd11")]
+tc_instance! {
+    TC1: (Tc1Tc2Clock, 0b01, apbcmask, tc1_, 0x0C, [TC1_MC0, TC1_MC1]),
+    TC2: (Tc1Tc2Clock, 0b10, apbcmask, tc2_, 0x0D, [TC2_MC0, TC2_MC1]),
+}
+
+#[cfg(feature = "samd11")]
+tc_pair! {
+    TC1 + TC2,
+}
+
+#[cfg(feature = "samd21")]
+tc_instance! {
+    TC3: (Tcc2Tc3Clock, 0b10, apbcmask, tc3_, 0x12, [TC3_MC0, TC3_MC1]),
+    TC4: (Tc4Tc5Clock, 0b01, apbcmask, tc4_, 0x13, [TC4_MC0, TC4_MC1]),
+    TC5: (Tc4Tc5Clock, 0b10, apbcmask, tc5_, 0x14, [TC5_MC0, TC5_MC1]),
+}
+
+#[cfg(feature = "samd21")]
+tc_pair! {
+    TC4 + TC5,
+}
+
+#[cfg(feature = "min-samd21j")]
+tc_instance! {
+    TC6: (Tc6Tc7Clock, 0b01, apbcmask, tc6_, 0x15, [TC6_MC0, TC6_MC1]),
+    TC7: (Tc6Tc7Clock, 0b10, apbcmask, tc7_, 0x16, [TC7_MC0, TC7_MC1]),
+}
+
+#[cfg(feature = "min-samd21j")]
+tc_pair! {
+    TC6 + TC7,
+}
+
+#[cfg(feature = "min-samd51g")]
+tc_instance! {
+    TC0: (Tc0Tc1Clock, 0b01, apbamask, tc0_, 0x2B, [TC0_MC_0, TC0_MC_1]),
+    TC1: (Tc0Tc1Clock, 0b10, apbamask, tc1_, 0x2C, [TC1_MC_0, TC1_MC_1]),
+    TC2: (Tc2Tc3Clock, 0b01, apbbmask, tc2_, 0x2D, [TC2_MC_0, TC2_MC_1]),
+    TC3: (Tc2Tc3Clock, 0b10, apbbmask, tc3_, 0x2E, [TC3_MC_0, TC3_MC_1]),
+}
+
+#[cfg(feature = "min-samd51g")]
+tc_pair! {
+    TC0 + TC1,
+    TC2 + TC3,
+}
+
+#[cfg(feature = "min-samd51j")]
+tc_instance! {
+    TC4: (Tc4Tc5Clock, 0b01, apbcmask, tc4_, 0x2F, [TC4_MC_0, TC4_MC_1]),
+    TC5: (Tc4Tc5Clock, 0b10, apbcmask, tc5_, 0x30, [TC5_MC_0, TC5_MC_1]),
+}
+
+#[cfg(feature = "min-samd51j")]
+tc_pair! {
+    TC4 + TC5,
+}
+
+#[cfg(feature = "min-samd51n")]
+tc_instance! {
+    TC6: (Tc6Tc7Clock, 0b01, apbdmask, tc6_, 0x31, [TC6_MC_0, TC6_MC_1]),
+    TC7: (Tc6Tc7Clock, 0b10, apbdmask, tc7_, 0x32, [TC7_MC_0, TC7_MC_1]),
+}
+
+#[cfg(feature = "min-samd51n")]
+tc_pair! {
+    TC6 + TC7,
+}
+
+//==============================================================================
+// Counter sizes
+//==============================================================================
+
+/// Size of the counter, see the [module documentation](self)
+pub trait Size: Sealed {
+    /// Type of the counter, period and compare/capture values
+    type Word: Copy;
+
+    #[doc(hidden)]
+    const MODE: MODE_A;
+
+    /// Number of bits of the counter
+    #[doc(hidden)]
+    const BITS: u32;
+
+    /// Keep the low bits of `value` that fit in the counter
+    #[doc(hidden)]
+    fn truncate(value: u32) -> Self::Word;
+
+    #[doc(hidden)]
+    fn count(regs: &RegisterBlock) -> Self::Word;
+
+    #[doc(hidden)]
+    fn set_count(regs: &RegisterBlock, count: Self::Word);
+
+    #[doc(hidden)]
+    fn cc(regs: &RegisterBlock, channel: usize) -> Self::Word;
+
+    #[doc(hidden)]
+    fn set_cc(regs: &RegisterBlock, channel: usize, value: Self::Word);
+
+    #[doc(hidden)]
+    fn cc_ptr(regs: &RegisterBlock, channel: usize) -> *mut Self::Word;
+
+    /// Write the top value, and return the waveform generation it needs
+    #[doc(hidden)]
+    fn set_top(regs: &RegisterBlock, top: Option<Self::Word>) -> Wavegen;
+}
+
+/// 8-bit counter mode
+pub enum Count8 {}
+
+/// 16-bit counter mode
+pub enum Count16 {}
+
+/// 32-bit counter mode, using a pair of instances
+pub enum Count32 {}
+
+macro_rules! size {
+    ($Size:ident, $Word:ty, $count:ident, $MODE:ident) => {
+        impl Sealed for $Size {}
+
+        impl Size for $Size {
+            type Word = $Word;
+
+            const MODE: MODE_A = MODE_A::$MODE;
+
+            const BITS: u32 = <$Word>::BITS;
+
+            #[inline]
+            fn truncate(value: u32) -> $Word {
+                value as $Word
+            }
+
+            #[inline]
+            fn count(regs: &RegisterBlock) -> $Word {
+                regs.$count().count.read().count().bits()
+            }
+
+            #[inline]
+            fn set_count(regs: &RegisterBlock, count: $Word) {
+                regs.$count()
+                    .count
+                    .write(|w| unsafe { w.count().bits(count) });
+            }
+
+            #[inline]
+            fn cc(regs: &RegisterBlock, channel: usize) -> $Word {
+                regs.$count().cc[channel].read().cc().bits()
+            }
+
+            #[inline]
+            fn set_cc(regs: &RegisterBlock, channel: usize, value: $Word) {
+                regs.$count().cc[channel].write(|w| unsafe { w.cc().bits(value) });
+            }
+
+            #[inline]
+            fn cc_ptr(regs: &RegisterBlock, channel: usize) -> *mut $Word {
+                regs.$count().cc[channel].as_ptr()
+            }
+
+            size!(@top $Size, $Word, $count);
+        }
+    };
+    (@top Count8, $Word:ty, $count:ident) => {
+        #[inline]
+        fn set_top(regs: &RegisterBlock, top: Option<$Word>) -> Wavegen {
+            let top = top.unwrap_or(<$Word>::MAX);
+            regs.$count().per.write(|w| unsafe { w.per().bits(top) });
+            Wavegen::NFRQ
+        }
+    };
+    (@top $Size:ident, $Word:ty, $count:ident) => {
+        #[inline]
+        fn set_top(regs: &RegisterBlock, top: Option<$Word>) -> Wavegen {
+            match top {
+                Some(top) => {
+                    Self::set_cc(regs, 0, top);
+                    Wavegen::MFRQ
+                }
+                None => Wavegen::NFRQ,
+            }
+        }
+    };
+}
+
+size!(Count8, u8, count8, COUNT8);
+size!(Count16, u16, count16, COUNT16);
+size!(Count32, u32, count32, COUNT32);
+
+//==============================================================================
+// Config
+//==============================================================================
+
+/// Counting direction
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    /// Count up, from zero to the top value
+    Up,
+    /// Count down, from the top value to zero
+    Down,
+}
+
+/// Configuration of a [`Counter`]
+#[derive(Clone, Copy, Debug)]
+pub struct Config {
+    prescaler: Prescaler,
+    direction: Direction,
+    one_shot: bool,
+    run_in_standby: bool,
+    capture: [bool; 2],
+}
+
+impl Config {
+    /// Default configuration: no prescaler, counting up continuously, stopped
+    /// in standby, both channels in compare mode
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            prescaler: Prescaler::DIV1,
+            direction: Direction::Up,
+            one_shot: false,
+            run_in_standby: false,
+            capture: [false; 2],
+        }
+    }
+
+    /// Set the prescaler dividing the peripheral clock
+    #[inline]
+    pub fn prescaler(mut self, prescaler: Prescaler) -> Self {
+        self.prescaler = prescaler;
+        self
+    }
+
+    /// Set the counting direction
+    #[inline]
+    pub fn direction(mut self, direction: Direction) -> Self {
+        self.direction = direction;
+        self
+    }
+
+    /// Stop the counter on the next overflow or underflow, until it is
+    /// retriggered
+    #[inline]
+    pub fn one_shot(mut self, one_shot: bool) -> Self {
+        self.one_shot = one_shot;
+        self
+    }
+
+    /// Keep counting in STANDBY
+    #[inline]
+    pub fn run_in_standby(mut self, enable: bool) -> Self {
+        self.run_in_standby = enable;
+        self
+    }
+
+    /// Use the compare/capture `channel` to capture the counter on an event,
+    /// instead of comparing it
+    ///
+    /// # Panics
+    ///
+    /// Panics if `channel` is not 0 or 1.
+    #[inline]
+    pub fn capture(mut self, channel: usize, enable: bool) -> Self {
+        self.capture[channel] = enable;
+        self
+    }
+}
+
+impl Default for Config {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+//==============================================================================
+// Counter
+//==============================================================================
+
+/// A TC used as a counter, see the [module documentation](self)
+pub struct Counter<R: Resource, S: Size> {
+    tc: R,
+    freq: Hertz,
+    prescaler: Prescaler,
+    size: PhantomData<S>,
+}
+
+/// An 8-bit [`Counter`]
+pub type Counter8<I> = Counter<I, Count8>;
+
+/// A 16-bit [`Counter`]
+pub type Counter16<I> = Counter<I, Count16>;
+
+/// A 32-bit [`Counter`], using the `M` instance and its slave
+pub type Counter32<M> = Counter<(M, <M as Master>::Slave), Count32>;
+
+impl<I: Instance> Counter<I, Count8> {
+    /// Configure `tc` as an 8-bit counter
+    ///
+    /// The counter is left disabled, see [`enable`](Self::enable).
+    pub fn count8(tc: I, clock: &I::Clock, apb: &mut Apb, config: Config) -> Self {
+        Self::new(tc, clock, apb, config)
+    }
+}
+
+impl<I: Instance> Counter<I, Count16> {
+    /// Configure `tc` as a 16-bit counter
+    ///
+    /// The counter is left disabled, see [`enable`](Self::enable).
+    pub fn count16(tc: I, clock: &I::Clock, apb: &mut Apb, config: Config) -> Self {
+        Self::new(tc, clock, apb, config)
+    }
+}
+
+impl<M: Master> Counter<(M, M::Slave), Count32> {
+    /// Configure the `master` and `slave` pair as a 32-bit counter
+    ///
+    /// The counter is left disabled, see [`enable`](Self::enable).
+    pub fn count32(
+        master: M,
+        slave: M::Slave,
+        clock: &M::Clock,
+        apb: &mut Apb,
+        config: Config,
+    ) -> Self {
+        Self::new((master, slave), clock, apb, config)
+    }
+}
+
+impl<R: Resource, S: Size> Counter<R, S> {
+    fn new(tc: R, clock: &R::Clock, apb: &mut Apb, config: Config) -> Self {
+        R::enable_apb(apb, true);
+        let counter = Self {
+            tc,
+            freq: R::clock_freq(clock),
+            prescaler: config.prescaler,
+            size: PhantomData,
+        };
+        counter.reset();
+        counter.configure(config);
+        counter
+    }
+
+    #[inline]
+    fn regs(&self) -> &RegisterBlock {
+        self.tc.register_block()
+    }
+
+    /// Wait for the synchronization of the registers
+    #[inline]
+    fn sync(&self) {
+        #[cfg(feature = "min-samd51g")]
+        while self.regs().count16().syncbusy.read().bits() != 0 {}
+        #[cfg(any(feature = "samd11", feature = "samd21"))]
+        while self.regs().count16().status.read().syncbusy().bit_is_set() {}
+    }
+
+    fn reset(&self) {
+        let count = self.regs().count16();
+        count.ctrla.modify(|_, w| w.enable().clear_bit());
+        self.sync();
+        count.ctrla.write(|w| w.swrst().set_bit());
+        // the SVD erroneously marks swrst as write-only, so we need to
+        // manually read the bit here
+        while count.ctrla.read().bits() & 1 != 0 {}
+        self.sync();
+    }
+
+    /// Write the enable-protected configuration. The TC must be disabled.
+    fn configure(&self, config: Config) {
+        let count = self.regs().count16();
+        #[cfg(feature = "min-samd51g")]
+        count.ctrla.write(|w| {
+            w.mode().variant(S::MODE);
+            w.prescaler().variant(config.prescaler);
+            w.runstdby().bit(config.run_in_standby);
+            w.capten0().bit(config.capture[0]);
+            w.capten1().bit(config.capture[1])
+        });
+        #[cfg(any(feature = "samd11", feature = "samd21"))]
+        {
+            count.ctrla.write(|w| {
+                w.mode().variant(S::MODE);
+                w.prescaler().variant(config.prescaler);
+                w.runstdby().bit(config.run_in_standby)
+            });
+            self.sync();
+            count.ctrlc.write(|w| {
+                w.cpten0().bit(config.capture[0]);
+                w.cpten1().bit(config.capture[1])
+            });
+        }
+        self.sync();
+        // No top value, for the whole range of the counter
+        self.set_wavegen(S::set_top(self.regs(), None));
+        count.ctrlbclr.write(|w| {
+            w.dir().set_bit();
+            w.oneshot().set_bit()
+        });
+        self.sync();
+        count.ctrlbset.write(|w| {
+            w.dir().bit(config.direction == Direction::Down);
+            w.oneshot().bit(config.one_shot)
+        });
+        self.sync();
+    }
+
+    /// Write the waveform generation mode. The TC must be disabled.
+    fn set_wavegen(&self, wavegen: Wavegen) {
+        #[cfg(feature = "min-samd51g")]
+        self.regs()
+            .count16()
+            .wave
+            .write(|w| w.wavegen().variant(wavegen));
+        #[cfg(any(feature = "samd11", feature = "samd21"))]
+        self.regs()
+            .count16()
+            .ctrla
+            .modify(|_, w| w.wavegen().variant(wavegen));
+        self.sync();
+    }
+
+    /// Returns the frequency of the counter, i.e. the peripheral clock
+    /// divided by the prescaler
+    pub fn freq(&self) -> Hertz {
+        Hertz(self.freq.0 / prescaler_divider(self.prescaler.into()))
+    }
+
+    /// Enable the counter, which starts counting
+    pub fn enable(&mut self) {
+        self.regs()
+            .count16()
+            .ctrla
+            .modify(|_, w| w.enable().set_bit());
+        self.sync();
+    }
+
+    /// Disable the counter
+    pub fn disable(&mut self) {
+        self.regs()
+            .count16()
+            .ctrla
+            .modify(|_, w| w.enable().clear_bit());
+        self.sync();
+    }
+
+    /// Returns true if the counter is enabled
+    #[inline]
+    pub fn is_enabled(&self) -> bool {
+        self.regs().count16().ctrla.read().enable().bit_is_set()
+    }
+
+    /// Returns true if the counter is stopped, by [`stop`](Self::stop) or at
+    /// the end of a one-shot count
+    #[inline]
+    pub fn is_stopped(&self) -> bool {
+        self.regs().count16().status.read().stop().bit_is_set()
+    }
+
+    fn command(&mut self, command: tc::count16::ctrlbset::CMD_A) {
+        let count = self.regs().count16();
+        count.ctrlbset.write(|w| w.cmd().variant(command));
+        self.sync();
+        // The command reads back as NONE once executed
+        while count.ctrlbset.read().cmd().bits() != 0 {}
+    }
+
+    /// Restart the counter from zero, or from the top value when counting
+    /// down
+    ///
+    /// This also restarts a one-shot counter.
+    #[inline]
+    pub fn retrigger(&mut self) {
+        self.command(tc::count16::ctrlbset::CMD_A::RETRIGGER);
+    }
+
+    /// Stop the counter, keeping its value
+    #[inline]
+    pub fn stop(&mut self) {
+        self.command(tc::count16::ctrlbset::CMD_A::STOP);
+    }
+
+    /// Returns the current value of the counter
+    ///
+    /// The value is synchronized from the counter clock domain before being
+    /// read, so this waits a few cycles of the peripheral clock.
+    pub fn count(&mut self) -> S::Word {
+        #[cfg(feature = "min-samd51g")]
+        self.command(tc::count16::ctrlbset::CMD_A::READSYNC);
+        #[cfg(any(feature = "samd11", feature = "samd21"))]
+        {
+            self.regs().count16().readreq.write(|w| unsafe {
+                w.rreq().set_bit();
+                w.addr().bits(COUNT_ADDR)
+            });
+            self.sync();
+        }
+        S::count(self.regs())
+    }
+
+    /// Set the current value of the counter
+    pub fn set_count(&mut self, count: S::Word) {
+        S::set_count(self.regs(), count);
+        self.sync();
+    }
+
+    /// Set the counting direction
+    pub fn set_direction(&mut self, direction: Direction) {
+        let count = self.regs().count16();
+        match direction {
+            Direction::Up => count.ctrlbclr.write(|w| w.dir().set_bit()),
+            Direction::Down => count.ctrlbset.write(|w| w.dir().set_bit()),
+        }
+        self.sync();
+    }
+
+    /// Returns the counting direction
+    #[inline]
+    pub fn direction(&self) -> Direction {
+        if self.regs().count16().ctrlbset.read().dir().bit_is_set() {
+            Direction::Down
+        } else {
+            Direction::Up
+        }
+    }
+
+    /// Enable or disable the one-shot mode
+    pub fn set_one_shot(&mut self, one_shot: bool) {
+        let count = self.regs().count16();
+        if one_shot {
+            count.ctrlbset.write(|w| w.oneshot().set_bit());
+        } else {
+            count.ctrlbclr.write(|w| w.oneshot().set_bit());
+        }
+        self.sync();
+    }
+
+    /// Set the action on the event input, or disable the event input if
+    /// `None`
+    ///
+    /// The counter is briefly disabled, since the event configuration is
+    /// enable-protected. See the [`capture`](crate::capture) module to
+    /// capture the period and pulse width of a signal with the event input.
+    pub fn set_event_action(&mut self, action: Option<EventAction>) {
+        self.set_event_input(action, false);
+    }
+
+    /// Set the action on the event input, acting on the inverted event if
+    /// `invert` is set
+    pub(crate) fn set_event_input(&mut self, action: Option<EventAction>, invert: bool) {
+        let enabled = self.is_enabled();
+        self.disable();
+        self.regs().count16().evctrl.write(|w| {
+            if let Some(action) = action {
+                w.evact().variant(action);
+                w.tcei().set_bit();
+                w.tcinv().bit(invert);
+            }
+            w
+        });
+        if enabled {
+            self.enable();
+        }
+    }
+
+    /// Set the top value of the counter, or use the whole range of the
+    /// counter if `None`
+    ///
+    /// In [`Count16`] and [`Count32`] modes, the top value is held by the
+    /// compare/capture channel 0. The counter is briefly disabled if the
+    /// waveform generation mode changes.
+    pub fn set_top(&mut self, top: Option<S::Word>) {
+        let wavegen = S::set_top(self.regs(), top);
+        self.sync();
+        #[cfg(feature = "min-samd51g")]
+        let current = self.regs().count16().wave.read().wavegen().variant();
+        #[cfg(any(feature = "samd11", feature = "samd21"))]
+        let current = self.regs().count16().ctrla.read().wavegen().variant();
+        if current != wavegen {
+            let enabled = self.is_enabled();
+            self.disable();
+            self.set_wavegen(wavegen);
+            if enabled {
+                self.enable();
+            }
+        }
+    }
+
+    /// Returns the value of the compare/capture `channel`
+    ///
+    /// In capture mode, this also clears the corresponding `MCx` flag.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `channel` is not 0 or 1.
+    #[inline]
+    pub fn cc(&self, channel: usize) -> S::Word {
+        S::cc(self.regs(), channel)
+    }
+
+    /// Set the value of the compare/capture `channel`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `channel` is not 0 or 1.
+    pub fn set_cc(&mut self, channel: usize, value: S::Word) {
+        S::set_cc(self.regs(), channel, value);
+        self.sync();
+    }
+
+    /// Read the interrupt flags
+    #[inline]
+    pub fn read_flags(&self) -> Flags {
+        Flags::from_bits_truncate(self.regs().count16().intflag.read().bits())
+    }
+
+    /// Clear the interrupt flags
+    #[inline]
+    pub fn clear_flags(&mut self, flags: Flags) {
+        self.regs()
+            .count16()
+            .intflag
+            .write(|w| unsafe { w.bits(flags.bits()) });
+    }
+
+    /// Enable the interrupts
+    #[inline]
+    pub fn enable_interrupts(&mut self, flags: Flags) {
+        self.regs()
+            .count16()
+            .intenset
+            .write(|w| unsafe { w.bits(flags.bits()) });
+    }
+
+    /// Disable the interrupts
+    #[inline]
+    pub fn disable_interrupts(&mut self, flags: Flags) {
+        self.regs()
+            .count16()
+            .intenclr
+            .write(|w| unsafe { w.bits(flags.bits()) });
+    }
+
+    /// Reset and disable the counter, and return the underlying instance(s)
+    pub fn free(self, apb: &mut Apb) -> R {
+        self.reset();
+        R::enable_apb(apb, false);
+        self.tc
+    }
+}
+
+impl<R: Resource, S: Size> Sealed for Counter<R, S> {}
+
+impl<R: Resource, S: Size> capture::Timer for Counter<R, S>
+where
+    S::Word: Into<u32>,
+{
+    type Word = S::Word;
+
+    const EVSYS_USER: u8 = R::EVSYS_USER;
+
+    #[cfg(all(feature = "unproven", feature = "dma"))]
+    const DMA_TRIGGERS: [TriggerSource; 2] = R::DMA_TRIGGERS;
+
+    #[inline]
+    fn capture_freq(&self) -> Hertz {
+        self.freq()
+    }
+
+    fn start_capture(&mut self, order: capture::Order) {
+        self.disable();
+        let count = self.regs().count16();
+        #[cfg(feature = "min-samd51g")]
+        count.ctrla.modify(|_, w| {
+            w.capten0().set_bit();
+            w.capten1().set_bit()
+        });
+        #[cfg(any(feature = "samd11", feature = "samd21"))]
+        count.ctrlc.write(|w| {
+            w.cpten0().set_bit();
+            w.cpten1().set_bit()
+        });
+        self.sync();
+        // The captures need the whole range of the counter
+        self.set_wavegen(S::set_top(self.regs(), None));
+        // PWP only swaps the channels; the falling edge starting a period
+        // comes from the inverted event
+        match order {
+            capture::Order::PeriodPulseWidth => self.set_event_input(Some(EventAction::PPW), false),
+            capture::Order::PulseWidthPeriod => self.set_event_input(Some(EventAction::PWP), true),
+        }
+        self.enable();
+    }
+
+    fn stop_capture(&mut self) {
+        self.disable();
+        self.set_event_action(None);
+    }
+
+    #[inline]
+    fn captured(&self, channel: usize) -> S::Word {
+        self.cc(channel)
+    }
+
+    #[inline]
+    fn captured_ptr(&self, channel: usize) -> *mut S::Word {
+        S::cc_ptr(self.regs(), channel)
+    }
+
+    #[inline]
+    fn capture_flags(&self) -> capture::Flags {
+        capture::Flags::from_bits_truncate(self.read_flags().bits())
+    }
+
+    #[inline]
+    fn clear_capture_flags(&mut self, flags: capture::Flags) {
+        self.clear_flags(Flags::from_bits_truncate(flags.bits()));
+    }
+
+    #[inline]
+    fn enable_capture_interrupts(&mut self, flags: capture::Flags) {
+        self.enable_interrupts(Flags::from_bits_truncate(flags.bits()));
+    }
+
+    #[inline]
+    fn disable_capture_interrupts(&mut self, flags: capture::Flags) {
+        self.disable_interrupts(Flags::from_bits_truncate(flags.bits()));
+    }
+}
+
+impl<R: Resource, S: Size> Reclock for Counter<R, S> {
+    #[inline]
+    fn clock_id(&self) -> ClockId {
+        <R::Clock as PeripheralClock>::ID
+    }
+
+    /// Store the new clock frequency. The counter keeps counting at the new
+    /// rate, see [`freq`](Counter::freq).
+    #[inline]
+    fn reclock(&mut self, freq: Hertz) {
+        self.freq = freq;
+    }
+
+    #[inline]
+    fn channel_users(&self) -> u8 {
+        R::CHANNEL_USERS
+    }
+}
+
+//==============================================================================
+// Monotonic
+//==============================================================================
+
+/// Instant of a [`Monotonic`] counting at `FREQ` Hz
+#[cfg(feature = "rtic")]
+pub type Instant<const FREQ: u32> = fugit::TimerInstantU64<FREQ>;
+
+/// Duration of a [`Monotonic`] counting at `FREQ` Hz
+#[cfg(feature = "rtic")]
+pub type Duration<const FREQ: u32> = fugit::TimerDurationU64<FREQ>;
+
+/// An RTIC monotonic timer counting at `FREQ` Hz, made of a [`Counter`]
+/// extended to 64 bits in software
+///
+/// The counter counts up over its whole range. The channel 1 marks the
+/// middle of the range, so that the overflow and half period interrupts
+/// count the half periods elapsed; the channel 0 is the compare of the timer
+/// queue. The RTIC monotonic must be bound to the interrupt of the TC (of the
+/// master in [`Count32`] mode), which must be served within half a period of
+/// the counter, e.g. 32 ms for a [`Count16`] counter at 1 MHz.
+///
+/// ```no_run
+/// # use atsamd_hal::clock::GenericClockController;
+/// # use atsamd_hal::pac::Peripherals;
+/// # use atsamd_hal::tc::*;
+/// # let mut peripherals = Peripherals::take().unwrap();
+/// # let mut clocks = GenericClockController::with_external_32kosc(
+/// #     peripherals.GCLK,
+/// #     &mut peripherals.MCLK,
+/// #     &mut peripherals.OSC32KCTRL,
+/// #     &mut peripherals.OSCCTRL,
+/// #     &mut peripherals.NVMCTRL,
+/// # );
+/// let gclk0 = clocks.gclk0();
+/// let clock = clocks.tc2_tc3(&gclk0).unwrap();
+/// // 120 MHz / 8 = 15 MHz
+/// let counter = Counter::count32(
+///     peripherals.TC2,
+///     peripherals.TC3,
+///     &clock,
+///     &mut peripherals.MCLK,
+///     Config::new().prescaler(Prescaler::DIV8),
+/// );
+/// let mono: Monotonic<_, _, 15_000_000> = Monotonic::new(counter);
+/// ```
+#[cfg(feature = "rtic")]
+pub struct Monotonic<R: Resource, S: Size, const FREQ: u32> {
+    counter: Counter<R, S>,
+    /// Number of half periods of the counter elapsed
+    half_periods: u64,
+}
+
+#[cfg(feature = "rtic")]
+impl<R: Resource, S: Size, const FREQ: u32> Monotonic<R, S, FREQ>
+where
+    S::Word: Into<u32>,
+{
+    /// Use `counter` as a monotonic timer
+    ///
+    /// The counter must not be configured for capture. It is reconfigured to
+    /// count up continuously over its whole range, and started by RTIC.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the counter does not count at `FREQ` Hz, i.e. if its clock
+    /// divided by its prescaler is not `FREQ`.
+    pub fn new(mut counter: Counter<R, S>) -> Self {
+        assert_eq!(counter.freq().0, FREQ);
+        counter.disable();
+        counter.disable_interrupts(Flags::all());
+        counter.set_event_action(None);
+        counter.set_top(None);
+        counter.set_direction(Direction::Up);
+        counter.set_one_shot(false);
+        counter.set_cc(1, S::truncate(1 << (S::BITS - 1)));
+        Self {
+            counter,
+            half_periods: 0,
+        }
+    }
+
+    /// Disable the counter and its interrupts, and return it
+    pub fn free(mut self) -> Counter<R, S> {
+        self.counter.disable();
+        self.counter.disable_interrupts(Flags::all());
+        self.counter
+    }
+}
+
+#[cfg(feature = "rtic")]
+impl<R: Resource, S: Size, const FREQ: u32> rtic_monotonic::Monotonic for Monotonic<R, S, FREQ>
+where
+    S::Word: Into<u32>,
+{
+    // The overflow and half period interrupts are needed even when no task is
+    // scheduled
+    const DISABLE_INTERRUPT_ON_EMPTY_QUEUE: bool = false;
+
+    type Instant = Instant<FREQ>;
+    type Duration = Duration<FREQ>;
+
+    fn now(&mut self) -> Self::Instant {
+        let half = 1 << (S::BITS - 1);
+        let count: u32 = self.counter.count().into();
+        // The half period count lags by one if its interrupt is pending. The
+        // top bit of the counter tells in which half the counter actually is:
+        // it differs from the parity of the count only in that case, and the
+        // XOR then adds the missing half period.
+        let parity = (self.half_periods & 1) as u32 * half;
+        Instant::from_ticks((self.half_periods << (S::BITS - 1)) + (count ^ parity) as u64)
+    }
+
+    fn zero() -> Self::Instant {
+        Instant::from_ticks(0)
+    }
+
+    unsafe fn reset(&mut self) {
+        self.counter.disable();
+        self.counter.set_count(S::truncate(0));
+        self.half_periods = 0;
+        self.counter.clear_flags(Flags::all());
+        self.counter.enable_interrupts(Flags::OVF | Flags::MC1);
+        self.counter.enable();
+    }
+
+    fn set_compare(&mut self, instant: Self::Instant) {
+        let now = self.now().ticks();
+        let target = instant.ticks();
+        // The counter matches the low bits of `target` exactly at `target` if
+        // it is less than a period ahead. Further instants are armed by a
+        // later call, since the queue is checked again at each half period.
+        // Instants already passed are dequeued by RTIC, which checks `now`
+        // again after this call.
+        if target > now && target - now < 1 << S::BITS {
+            self.counter.set_cc(0, S::truncate(target as u32));
+            self.counter.clear_flags(Flags::MC0);
+            self.counter.enable_interrupts(Flags::MC0);
+        }
+    }
+
+    fn clear_compare_flag(&mut self) {
+        self.counter.disable_interrupts(Flags::MC0);
+        self.counter.clear_flags(Flags::MC0);
+    }
+
+    fn on_interrupt(&mut self) {
+        let flags = self.counter.read_flags() & (Flags::OVF | Flags::MC1);
+        self.counter.clear_flags(flags);
+        self.half_periods += flags.bits().count_ones() as u64;
+    }
+}
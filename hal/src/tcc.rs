@@ -0,0 +1,1959 @@
+//! # Timer/counter for control applications (TCC)
+//!
+//! [`Tcc`] drives several waveform outputs of a TCC at once, with the
+//! waveform generation and motor control features of the peripheral:
+//!
+//! * Waveform generation: normal and match frequency ([`Waveform::NFRQ`],
+//!   [`Waveform::MFRQ`]), single-slope PWM ([`Waveform::NPWM`], with the
+//!   [`Ramp`] operations) and dual-slope PWM, with the overflow at the top,
+//!   at zero or both ([`Waveform::DSTOP`], [`Waveform::DSBOTTOM`],
+//!   [`Waveform::DSBOTH`], [`Waveform::DSCRITICAL`]).
+//! * Dithering ([`Resolution`]), extending the period and duty cycles by a
+//!   fraction of a cycle on average, on the instances implementing
+//!   [`Dithering`].
+//! * Circular buffers ([`Config::circular_top`], [`Config::circular_cc`]),
+//!   alternating between two top or compare/capture values, and locked
+//!   updates ([`Tcc::lock_update`]).
+//! * Complementary outputs: a [`ComplementaryPair`] drives `WO[x]` and
+//!   `WO[x + 4]` from the dead-time insertion unit `x`, which generates the
+//!   low side and high side signals from the compare/capture channel of
+//!   `WO[x]`, with the dead times of [`Config::dead_time`]. Only the
+//!   instances implementing [`DeadTimeInsertion`] have these units.
+//! * Output matrix: [`OutputMatrix`] selects the compare/capture channel
+//!   driving each waveform output, on the instances implementing
+//!   [`OutputMatrixSelection`].
+//! * Recoverable faults A and B ([`RecoverableFault`]), on the event inputs
+//!   `MCE0` and `MCE1`. The outputs are forced to their inactive level while
+//!   the fault is active, and the counter can be halted or restarted.
+//! * Non-recoverable faults ([`NonRecoverableFault`]), on the event inputs
+//!   `EV0` and `EV1`. Each output is driven to its safe state
+//!   ([`Config::safe_state`]) until the fault is cleared by software.
+//! * Pattern generation ([`Pattern`]), overriding the outputs with fixed
+//!   levels, e.g. for the commutation of a brushless DC motor, on the
+//!   instances implementing [`PatternGeneration`].
+//!
+//! The outputs are [`Output`] pins, [`ComplementaryPair`]s, or tuples of
+//! them. The pins are checked at compile time to be waveform outputs of the
+//! TCC. The fault inputs are connected to EIC pins with
+//! [`Tcc::connect_fault`].
+//!
+//! ```no_run
+//! # use atsamd_hal::clock::GenericClockController;
+//! # use atsamd_hal::gpio::Pins;
+//! # use atsamd_hal::pac::{Peripherals, TCC0};
+//! # use atsamd_hal::tcc::*;
+//! let mut peripherals = Peripherals::take().unwrap();
+//! let mut clocks = GenericClockController::with_external_32kosc(
+//!     peripherals.GCLK,
+//!     &mut peripherals.MCLK,
+//!     &mut peripherals.OSC32KCTRL,
+//!     &mut peripherals.OSCCTRL,
+//!     &mut peripherals.NVMCTRL,
+//! );
+//! let gclk0 = clocks.gclk0();
+//! let pins = Pins::new(peripherals.PORT);
+//!
+//! // Half bridge on WO[0] (low side) and WO[4] (high side), with 0.5 µs of
+//! // dead time at 120 MHz, both sides off on a non-recoverable fault
+//! let bridge = ComplementaryPair::<TCC0, _, _>::new(pins.pa08, pins.pb10);
+//! let clock = clocks.tcc0_tcc1(&gclk0).unwrap();
+//! let config = Config::new()
+//!     .dead_time(60, 60)
+//!     .non_recoverable_fault(0, Some(NonRecoverableFault::new()))
+//!     .safe_state(0, Some(false))
+//!     .safe_state(4, Some(false));
+//! let mut tcc = Tcc::new(peripherals.TCC0, &clock, &mut peripherals.MCLK, bridge, config);
+//! // 20 kHz, 25% duty cycle
+//! tcc.set_top(5999);
+//! tcc.set_cc(0, 1500);
+//! tcc.enable();
+//! ```
+
+use bitflags::bitflags;
+
+use crate::clock::{self, PeripheralClock};
+use crate::eic::pin::ExternalInterrupt;
+use crate::evsys;
+use crate::gpio::{Alternate, AlternateConfig, AnyPin, Pin, PinId};
+use crate::pac::EVSYS;
+use crate::pwm::Channel;
+use crate::time::Hertz;
+use crate::typelevel::Sealed;
+
+#[cfg(all(feature = "unproven", feature = "dma"))]
+use crate::dmac::TriggerSource;
+
+#[cfg(feature = "min-samd51g")]
+use crate::pac::{tcc0 as tcc, MCLK as Apb};
+#[cfg(any(feature = "samd11", feature = "samd21"))]
+use crate::pac::{tcc0 as tcc, PM as Apb};
+
+pub use tcc::RegisterBlock;
+
+pub use tcc::ctrla::PRESCALER_A as Prescaler;
+pub use tcc::ctrla::RESOLUTION_A as Resolution;
+pub use tcc::fctrla::HALT_A as FaultHalt;
+pub use tcc::fctrla::SRC_A as FaultSource;
+pub use tcc::wave::RAMP_A as Ramp;
+pub use tcc::wave::WAVEGEN_A as Waveform;
+
+bitflags! {
+    /// Interrupt bit flags of a TCC
+    ///
+    /// The binary format of the underlying bits exactly matches the `INTFLAG`
+    /// bits.
+    pub struct Flags: u32 {
+        /// Overflow or underflow
+        const OVF = 1 << 0;
+        /// Retrigger
+        const TRG = 1 << 1;
+        /// Counter event
+        const CNT = 1 << 2;
+        /// A capture was lost, since the channel held a value not read yet
+        const ERR = 1 << 3;
+        /// Non-recoverable update fault
+        #[cfg(feature = "min-samd51g")]
+        const UFS = 1 << 10;
+        /// Non-recoverable debug fault
+        const DFS = 1 << 11;
+        /// Recoverable fault A
+        const FAULTA = 1 << 12;
+        /// Recoverable fault B
+        const FAULTB = 1 << 13;
+        /// Non-recoverable fault 0
+        const FAULT0 = 1 << 14;
+        /// Non-recoverable fault 1
+        const FAULT1 = 1 << 15;
+        /// Match or capture on channel 0
+        const MC0 = 1 << 16;
+        /// Match or capture on channel 1
+        const MC1 = 1 << 17;
+        /// Match or capture on channel 2
+        const MC2 = 1 << 18;
+        /// Match or capture on channel 3
+        const MC3 = 1 << 19;
+        /// Match or capture on channel 4
+        const MC4 = 1 << 20;
+        /// Match or capture on channel 5
+        const MC5 = 1 << 21;
+    }
+}
+
+bitflags! {
+    /// Fault states and inputs of a TCC
+    ///
+    /// The bits are shifted from the `STATUS` bits: the fault inputs are at
+    /// bit 8, the fault states at bit 12.
+    pub struct Faults: u8 {
+        /// Recoverable fault A
+        const A = 0x01;
+        /// Recoverable fault B
+        const B = 0x02;
+        /// Non-recoverable fault 0
+        const NON_RECOVERABLE0 = 0x04;
+        /// Non-recoverable fault 1
+        const NON_RECOVERABLE1 = 0x08;
+    }
+}
+
+/// `STATUS` bit of the first fault input
+const STATUS_FAULT_INPUTS: u32 = 8;
+/// `STATUS` bit of the first fault state
+const STATUS_FAULTS: u32 = 12;
+
+/// Fails to evaluate if `condition` is false, which turns a check of
+/// associated constants into a compile-time error
+const fn check(condition: bool) {
+    [()][!condition as usize]
+}
+
+//==============================================================================
+// Instances
+//==============================================================================
+
+/// A TCC instance
+pub trait Instance: Sealed {
+    /// Peripheral clock token of the instance
+    type Clock: PeripheralClock;
+
+    /// Number of compare/capture channels
+    const CC_NUM: usize;
+
+    /// Number of waveform outputs
+    const WO_NUM: usize;
+
+    /// Largest value of the counter, which has 24 or 16 bits
+    const MAX: u32;
+
+    /// EVSYS user of the event input 0. The event input 1 and the channel
+    /// event inputs 0 and 1 are the following users.
+    #[doc(hidden)]
+    const EVSYS_USER: u8;
+
+    /// Position of the instance on its peripheral channel, see
+    /// [`Reclock::channel_users`](crate::clock::Reclock::channel_users)
+    #[doc(hidden)]
+    const CHANNEL_USERS: u8;
+
+    /// Whether the instance has a pattern generator, i.e. implements
+    /// [`PatternGeneration`]
+    #[doc(hidden)]
+    const PATTERN_GENERATION: bool;
+
+    /// DMA triggers of the compare/capture channels 0 and 1
+    #[cfg(all(feature = "unproven", feature = "dma"))]
+    #[doc(hidden)]
+    const DMA_TRIGGERS: [TriggerSource; 2];
+
+    #[doc(hidden)]
+    fn register_block(&self) -> &RegisterBlock;
+
+    #[doc(hidden)]
+    fn clock_freq(clock: &Self::Clock) -> Hertz;
+
+    #[doc(hidden)]
+    fn enable_apb(apb: &mut Apb, enable: bool);
+}
+
+/// A TCC instance with dead-time insertion units, driving the complementary
+/// outputs `WO[x]` and `WO[x + 4]`
+pub trait DeadTimeInsertion: Instance {}
+
+/// A TCC instance with dithering, see [`Resolution`]
+pub trait Dithering: Instance {}
+
+/// A TCC instance whose output matrix can be changed, see [`OutputMatrix`]
+pub trait OutputMatrixSelection: Instance {}
+
+/// A TCC instance with a pattern generator, see [`Pattern`]
+pub trait PatternGeneration: Instance {}
+
+macro_rules! tcc_instance {
+    ($($TCC:ident: ($Clock:ident, $users:expr, $apbmask:ident, $apb:ident, $cc:expr, $wo:expr, $bits:expr, $pg:expr, $user:expr, [$mc0:ident, $mc1:ident]),)+) => {
+        $(
+            impl Sealed for crate::pac::$TCC {}
+
+            impl Instance for crate::pac::$TCC {
+                type Clock = clock::$Clock;
+
+                const CC_NUM: usize = $cc;
+                const WO_NUM: usize = $wo;
+                const MAX: u32 = (1 << $bits) - 1;
+                const EVSYS_USER: u8 = $user;
+                const CHANNEL_USERS: u8 = $users;
+                const PATTERN_GENERATION: bool = $pg;
+
+                #[cfg(all(feature = "unproven", feature = "dma"))]
+                const DMA_TRIGGERS: [TriggerSource; 2] =
+                    [TriggerSource::$mc0, TriggerSource::$mc1];
+
+                #[inline]
+                fn register_block(&self) -> &RegisterBlock {
+                    self
+                }
+
+                #[inline]
+                fn clock_freq(clock: &Self::Clock) -> Hertz {
+                    clock.freq()
+                }
+
+                #[inline]
+                fn enable_apb(apb: &mut Apb, enable: bool) {
+                    apb.$apbmask.modify(|_, w| w.$apb().bit(enable));
+                }
+            }
+        )+
+    };
+}
+
+#[cfg(feature = "samd11")]
+tcc_instance! {
+    TCC0: (Tcc0Clock, 0b01, apbcmask, tcc0_, 4, 8, 24, true, 0x06, [TCC0_MC0, TCC0_MC1]),
+}
+
+#[cfg(feature = "samd11")]
+impl DeadTimeInsertion for crate::pac::TCC0 {}
+#[cfg(feature = "samd11")]
+impl Dithering for crate::pac::TCC0 {}
+#[cfg(feature = "samd11")]
+impl OutputMatrixSelection for crate::pac::TCC0 {}
+#[cfg(feature = "samd11")]
+impl PatternGeneration for crate::pac::TCC0 {}
+
+#[cfg(feature = "samd21")]
+tcc_instance! {
+    TCC0: (Tcc0Tcc1Clock, 0b01, apbcmask, tcc0_, 4, 8, 24, true, 0x04, [TCC0_MC0, TCC0_MC1]),
+    TCC1: (Tcc0Tcc1Clock, 0b10, apbcmask, tcc1_, 2, 4, 24, true, 0x0A, [TCC1_MC0, TCC1_MC1]),
+    TCC2: (Tcc2Tc3Clock, 0b01, apbcmask, tcc2_, 2, 2, 16, false, 0x0E, [TCC2_MC0, TCC2_MC1]),
+}
+
+#[cfg(feature = "samd21")]
+impl DeadTimeInsertion for crate::pac::TCC0 {}
+#[cfg(feature = "samd21")]
+impl Dithering for crate::pac::TCC0 {}
+#[cfg(feature = "samd21")]
+impl Dithering for crate::pac::TCC1 {}
+#[cfg(feature = "samd21")]
+impl OutputMatrixSelection for crate::pac::TCC0 {}
+#[cfg(feature = "samd21")]
+impl PatternGeneration for crate::pac::TCC0 {}
+#[cfg(feature = "samd21")]
+impl PatternGeneration for crate::pac::TCC1 {}
+
+#[cfg(feature = "min-samd51g")]
+tcc_instance! {
+    TCC0: (Tcc0Tcc1Clock, 0b01, apbbmask, tcc0_, 6, 8, 24, true, 0x10, [TCC0_MC_0, TCC0_MC_1]),
+    TCC1: (Tcc0Tcc1Clock, 0b10, apbbmask, tcc1_, 4, 8, 24, true, 0x18, [TCC1_MC_0, TCC1_MC_1]),
+    TCC2: (Tcc2Tcc3Clock, 0b01, apbcmask, tcc2_, 3, 3, 16, false, 0x1E, [TCC2_MC_0, TCC2_MC_1]),
+}
+
+#[cfg(feature = "min-samd51g")]
+impl DeadTimeInsertion for crate::pac::TCC0 {}
+#[cfg(feature = "min-samd51g")]
+impl DeadTimeInsertion for crate::pac::TCC1 {}
+#[cfg(feature = "min-samd51g")]
+impl Dithering for crate::pac::TCC0 {}
+#[cfg(feature = "min-samd51g")]
+impl Dithering for crate::pac::TCC1 {}
+#[cfg(feature = "min-samd51g")]
+impl OutputMatrixSelection for crate::pac::TCC0 {}
+#[cfg(feature = "min-samd51g")]
+impl OutputMatrixSelection for crate::pac::TCC1 {}
+#[cfg(feature = "min-samd51g")]
+impl PatternGeneration for crate::pac::TCC0 {}
+#[cfg(feature = "min-samd51g")]
+impl PatternGeneration for crate::pac::TCC1 {}
+
+#[cfg(feature = "min-samd51j")]
+tcc_instance! {
+    TCC3: (Tcc2Tcc3Clock, 0b10, apbcmask, tcc3_, 2, 2, 16, false, 0x23, [TCC3_MC_0, TCC3_MC_1]),
+    TCC4: (Tcc4Clock, 0b01, apbdmask, tcc4_, 2, 2, 16, false, 0x27, [TCC4_MC_0, TCC4_MC_1]),
+}
+
+//==============================================================================
+// Waveform outputs
+//==============================================================================
+
+/// Type-level enum of the waveform outputs `WO[x]`
+pub trait WoId: Sealed {
+    /// Index of the waveform output
+    const INDEX: usize;
+}
+
+macro_rules! wo {
+    ($($Wo:ident: $index:expr,)+) => {
+        $(
+            #[doc = concat!("Waveform output `WO[", $index, "]`")]
+            pub enum $Wo {}
+
+            impl Sealed for $Wo {}
+
+            impl WoId for $Wo {
+                const INDEX: usize = $index;
+            }
+        )+
+    };
+}
+
+wo! {
+    WO0: 0,
+    WO1: 1,
+    WO2: 2,
+    WO3: 3,
+    WO4: 4,
+    WO5: 5,
+    WO6: 6,
+    WO7: 7,
+}
+
+/// A waveform output driven by the dead-time insertion unit of `L`, with the
+/// complement of `L`
+pub trait Complement<L: WoId>: WoId {}
+
+impl Complement<WO0> for WO4 {}
+impl Complement<WO1> for WO5 {}
+impl Complement<WO2> for WO6 {}
+impl Complement<WO3> for WO7 {}
+
+/// A pin that can be driven by a waveform output of the TCC `T`
+pub trait OutputId<T: Instance>: PinId {
+    /// Alternate function of the pin for `T`
+    type Config: AlternateConfig;
+    /// Waveform output driving the pin
+    type Wo: WoId;
+}
+
+macro_rules! outputs {
+    ($TCC:ident: [$($(#[$attr:meta])* ($Id:ident, $Config:ident, $Wo:ident),)+]) => {
+        $(
+            $(#[$attr])*
+            impl OutputId<crate::pac::$TCC> for crate::gpio::$Id {
+                type Config = crate::gpio::$Config;
+                type Wo = $Wo;
+            }
+        )+
+    };
+}
+
+#[cfg(feature = "samd11")]
+outputs!(TCC0: [
+    (PA04, F, WO0),
+    (PA05, F, WO1),
+]);
+
+#[cfg(feature = "samd21")]
+outputs!(TCC0: [
+    (PA04, E, WO0),
+    (PA05, E, WO1),
+    (PA08, E, WO0),
+    (PA09, E, WO1),
+    (PA10, F, WO2),
+    (PA11, F, WO3),
+    #[cfg(feature = "min-samd21g")]
+    (PA12, F, WO6),
+    #[cfg(feature = "min-samd21g")]
+    (PA13, F, WO7),
+    (PA14, F, WO4),
+    (PA15, F, WO5),
+    (PA16, F, WO6),
+    (PA17, F, WO7),
+    (PA18, F, WO2),
+    (PA19, F, WO3),
+    #[cfg(feature = "min-samd21g")]
+    (PA20, F, WO6),
+    #[cfg(feature = "min-samd21g")]
+    (PA21, F, WO7),
+    (PA22, F, WO4),
+    (PA23, F, WO5),
+    #[cfg(feature = "min-samd21g")]
+    (PB10, F, WO4),
+    #[cfg(feature = "min-samd21g")]
+    (PB11, F, WO5),
+    #[cfg(feature = "min-samd21j")]
+    (PB12, F, WO6),
+    #[cfg(feature = "min-samd21j")]
+    (PB13, F, WO7),
+    #[cfg(feature = "min-samd21j")]
+    (PB16, F, WO4),
+    #[cfg(feature = "min-samd21j")]
+    (PB17, F, WO5),
+    #[cfg(feature = "min-samd21j")]
+    (PB30, E, WO0),
+    #[cfg(feature = "min-samd21j")]
+    (PB31, E, WO1),
+]);
+
+#[cfg(feature = "samd21")]
+outputs!(TCC1: [
+    (PA06, E, WO0),
+    (PA07, E, WO1),
+    (PA08, F, WO2),
+    (PA09, F, WO3),
+    (PA10, E, WO0),
+    (PA11, E, WO1),
+    (PA24, F, WO2),
+    (PA25, F, WO3),
+    (PA30, E, WO0),
+    (PA31, E, WO1),
+    #[cfg(feature = "min-samd21j")]
+    (PB30, F, WO2),
+    #[cfg(feature = "min-samd21j")]
+    (PB31, F, WO3),
+]);
+
+#[cfg(feature = "samd21")]
+outputs!(TCC2: [
+    #[cfg(not(any(feature = "samd21el", feature = "samd21gl")))]
+    (PA00, E, WO0),
+    #[cfg(not(any(feature = "samd21el", feature = "samd21gl")))]
+    (PA01, E, WO1),
+    #[cfg(feature = "min-samd21g")]
+    (PA12, E, WO0),
+    #[cfg(feature = "min-samd21g")]
+    (PA13, E, WO1),
+    (PA16, E, WO0),
+    (PA17, E, WO1),
+]);
+
+#[cfg(feature = "min-samd51g")]
+outputs!(TCC0: [
+    (PA08, F, WO0),
+    (PA09, F, WO1),
+    (PA10, F, WO2),
+    (PA11, F, WO3),
+    (PA12, F, WO6),
+    (PA13, F, WO7),
+    (PA16, G, WO4),
+    (PA17, G, WO5),
+    (PA18, G, WO6),
+    (PA19, G, WO7),
+    (PA20, G, WO0),
+    (PA21, G, WO1),
+    (PA22, G, WO2),
+    (PA23, G, WO3),
+    (PB10, F, WO4),
+    (PB11, F, WO5),
+    #[cfg(feature = "min-samd51j")]
+    (PB12, G, WO0),
+    #[cfg(feature = "min-samd51j")]
+    (PB13, G, WO1),
+    #[cfg(feature = "min-samd51j")]
+    (PB14, G, WO2),
+    #[cfg(feature = "min-samd51j")]
+    (PB15, G, WO3),
+    #[cfg(feature = "min-samd51j")]
+    (PB16, G, WO4),
+    #[cfg(feature = "min-samd51j")]
+    (PB17, G, WO5),
+    #[cfg(feature = "min-samd51j")]
+    (PB30, G, WO6),
+    #[cfg(feature = "min-samd51j")]
+    (PB31, G, WO7),
+    #[cfg(feature = "min-samd51n")]
+    (PC10, F, WO0),
+    #[cfg(feature = "min-samd51n")]
+    (PC11, F, WO1),
+    #[cfg(feature = "min-samd51n")]
+    (PC12, F, WO2),
+    #[cfg(feature = "min-samd51n")]
+    (PC13, F, WO3),
+    #[cfg(feature = "min-samd51n")]
+    (PC14, F, WO4),
+    #[cfg(feature = "min-samd51n")]
+    (PC15, F, WO5),
+    #[cfg(feature = "min-samd51n")]
+    (PC16, F, WO0),
+    #[cfg(feature = "min-samd51n")]
+    (PC17, F, WO1),
+    #[cfg(feature = "min-samd51n")]
+    (PC18, F, WO2),
+    #[cfg(feature = "min-samd51n")]
+    (PC19, F, WO3),
+    #[cfg(feature = "min-samd51n")]
+    (PC20, F, WO4),
+    #[cfg(feature = "min-samd51n")]
+    (PC21, F, WO5),
+    #[cfg(feature = "min-samd51p")]
+    (PC04, F, WO0),
+    #[cfg(feature = "min-samd51p")]
+    (PC22, F, WO6),
+    #[cfg(feature = "min-samd51p")]
+    (PC23, F, WO7),
+    #[cfg(feature = "min-samd51p")]
+    (PD08, F, WO1),
+    #[cfg(feature = "min-samd51p")]
+    (PD09, F, WO2),
+    #[cfg(feature = "min-samd51p")]
+    (PD10, F, WO3),
+    #[cfg(feature = "min-samd51p")]
+    (PD11, F, WO4),
+    #[cfg(feature = "min-samd51p")]
+    (PD12, F, WO5),
+]);
+
+#[cfg(feature = "min-samd51g")]
+outputs!(TCC1: [
+    (PA08, G, WO4),
+    (PA09, G, WO5),
+    (PA10, G, WO6),
+    (PA11, G, WO7),
+    (PA12, G, WO2),
+    (PA13, G, WO3),
+    (PA14, G, WO2),
+    (PA15, G, WO3),
+    (PA16, F, WO0),
+    (PA17, F, WO1),
+    (PA18, F, WO2),
+    (PA19, F, WO3),
+    (PA20, F, WO4),
+    (PA21, F, WO5),
+    (PA22, F, WO6),
+    (PA23, F, WO7),
+    (PB10, G, WO0),
+    (PB11, G, WO1),
+    #[cfg(feature = "min-samd51n")]
+    (PB18, F, WO0),
+    #[cfg(feature = "min-samd51n")]
+    (PB19, F, WO1),
+    #[cfg(feature = "min-samd51n")]
+    (PB20, F, WO2),
+    #[cfg(feature = "min-samd51n")]
+    (PB21, F, WO3),
+    #[cfg(feature = "min-samd51n")]
+    (PC10, G, WO4),
+    #[cfg(feature = "min-samd51n")]
+    (PC11, G, WO5),
+    #[cfg(feature = "min-samd51n")]
+    (PC12, G, WO6),
+    #[cfg(feature = "min-samd51n")]
+    (PC13, G, WO7),
+    #[cfg(feature = "min-samd51n")]
+    (PC14, G, WO0),
+    #[cfg(feature = "min-samd51n")]
+    (PC15, G, WO1),
+    #[cfg(feature = "min-samd51p")]
+    (PB26, F, WO2),
+    #[cfg(feature = "min-samd51p")]
+    (PB27, F, WO3),
+    #[cfg(feature = "min-samd51p")]
+    (PB28, F, WO4),
+    #[cfg(feature = "min-samd51p")]
+    (PB29, F, WO5),
+    #[cfg(feature = "min-samd51p")]
+    (PD20, F, WO0),
+    #[cfg(feature = "min-samd51p")]
+    (PD21, F, WO1),
+]);
+
+#[cfg(feature = "min-samd51g")]
+outputs!(TCC2: [
+    (PA14, F, WO0),
+    (PA15, F, WO1),
+    (PA24, F, WO2),
+    (PA30, F, WO0),
+    (PA31, F, WO1),
+    (PB02, F, WO2),
+]);
+
+#[cfg(feature = "min-samd51j")]
+outputs!(TCC3: [
+    (PB12, F, WO0),
+    (PB13, F, WO1),
+    (PB16, F, WO0),
+    (PB17, F, WO1),
+]);
+
+#[cfg(feature = "min-samd51j")]
+outputs!(TCC4: [
+    (PB14, F, WO0),
+    (PB15, F, WO1),
+    (PB30, F, WO0),
+    (PB31, F, WO1),
+]);
+
+/// The pins driven by a [`Tcc`]: an [`Output`], a [`ComplementaryPair`], or
+/// a tuple of them
+pub trait Outputs<T: Instance> {
+    /// Bit mask of the waveform outputs
+    #[doc(hidden)]
+    const MASK: u8;
+
+    /// Bit mask of the dead-time insertion units
+    #[doc(hidden)]
+    const DTI: u8;
+
+    /// Number of waveform outputs, counting an output used twice twice
+    #[doc(hidden)]
+    const COUNT: u32;
+
+    /// Bit mask of the compare/capture channels driving the outputs, with the
+    /// default [`OutputMatrix`]
+    #[doc(hidden)]
+    const CHANNELS: u8;
+
+    /// Number of compare/capture channels, counting a channel used twice
+    /// twice
+    #[doc(hidden)]
+    const CHANNEL_COUNT: u32;
+}
+
+/// A pin driven by the waveform output `I::Wo` of the TCC `T`
+pub struct Output<T: Instance, I: OutputId<T>> {
+    pin: Pin<I, Alternate<I::Config>>,
+    tcc: core::marker::PhantomData<T>,
+}
+
+impl<T: Instance, I: OutputId<T>> Output<T, I> {
+    /// Configure `pin` in the alternate function of the TCC `T`
+    #[inline]
+    pub fn new(pin: impl AnyPin<Id = I>) -> Self {
+        Self {
+            pin: pin.into().into_alternate(),
+            tcc: core::marker::PhantomData,
+        }
+    }
+
+    /// Return the pin, still in the alternate function of the TCC
+    #[inline]
+    pub fn free(self) -> Pin<I, Alternate<I::Config>> {
+        self.pin
+    }
+}
+
+impl<T: Instance, I: OutputId<T>> Outputs<T> for Output<T, I> {
+    const MASK: u8 = 1 << I::Wo::INDEX;
+    const DTI: u8 = 0;
+    const COUNT: u32 = 1;
+    const CHANNELS: u8 = 1 << (I::Wo::INDEX % T::CC_NUM);
+    const CHANNEL_COUNT: u32 = 1;
+}
+
+/// The low side pin `L` and high side pin `H` of a complementary pair,
+/// driven by a dead-time insertion unit of the TCC `T`
+pub struct ComplementaryPair<T, L, H>
+where
+    T: DeadTimeInsertion,
+    L: OutputId<T>,
+    H: OutputId<T>,
+    H::Wo: Complement<L::Wo>,
+{
+    low: Output<T, L>,
+    high: Output<T, H>,
+}
+
+impl<T, L, H> ComplementaryPair<T, L, H>
+where
+    T: DeadTimeInsertion,
+    L: OutputId<T>,
+    H: OutputId<T>,
+    H::Wo: Complement<L::Wo>,
+{
+    /// Configure the `low` and `high` side pins in the alternate function of
+    /// the TCC `T`
+    #[inline]
+    pub fn new(low: impl AnyPin<Id = L>, high: impl AnyPin<Id = H>) -> Self {
+        Self {
+            low: Output::new(low),
+            high: Output::new(high),
+        }
+    }
+
+    /// Return the low side and high side pins, still in the alternate
+    /// function of the TCC
+    #[inline]
+    #[allow(clippy::type_complexity)]
+    pub fn free(self) -> (Pin<L, Alternate<L::Config>>, Pin<H, Alternate<H::Config>>) {
+        (self.low.free(), self.high.free())
+    }
+}
+
+impl<T, L, H> Outputs<T> for ComplementaryPair<T, L, H>
+where
+    T: DeadTimeInsertion,
+    L: OutputId<T>,
+    H: OutputId<T>,
+    H::Wo: Complement<L::Wo>,
+{
+    const MASK: u8 = (1 << L::Wo::INDEX) | (1 << H::Wo::INDEX);
+    const DTI: u8 = 1 << L::Wo::INDEX;
+    const COUNT: u32 = 2;
+    // Both sides are driven by the channel of the low side
+    const CHANNELS: u8 = 1 << (L::Wo::INDEX % T::CC_NUM);
+    const CHANNEL_COUNT: u32 = 1;
+}
+
+macro_rules! outputs_tuple {
+    ($($O:ident),+) => {
+        impl<T: Instance, $($O: Outputs<T>),+> Outputs<T> for ($($O,)+) {
+            const MASK: u8 = $($O::MASK)|+;
+            const DTI: u8 = $($O::DTI)|+;
+            const COUNT: u32 = 0 $(+ $O::COUNT)+;
+            const CHANNELS: u8 = $($O::CHANNELS)|+;
+            const CHANNEL_COUNT: u32 = 0 $(+ $O::CHANNEL_COUNT)+;
+        }
+    };
+}
+
+outputs_tuple!(A, B);
+outputs_tuple!(A, B, C);
+outputs_tuple!(A, B, C, D);
+outputs_tuple!(A, B, C, D, E);
+outputs_tuple!(A, B, C, D, E, F);
+outputs_tuple!(A, B, C, D, E, F, G);
+outputs_tuple!(A, B, C, D, E, F, G, H);
+
+//==============================================================================
+// Configuration
+//==============================================================================
+
+/// Selection of the compare/capture channel driving each waveform output
+/// (`WEXCTRL.OTMX`)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputMatrix {
+    /// `WO[x]` is driven by the channel `x % CC_NUM`
+    Modulo = 0,
+    /// `WO[x]` is driven by the channel `x % (CC_NUM / 2)`
+    HalfModulo = 1,
+    /// All the outputs are driven by the channel 0
+    Channel0 = 2,
+    /// `WO[0]` is driven by the channel 0, the other outputs by the channel 1
+    Channel0Then1 = 3,
+}
+
+/// Edges of the waveform output starting the blanking of a
+/// [`RecoverableFault`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Blanking {
+    /// Rising edges
+    Rise = 1,
+    /// Falling edges
+    Fall = 2,
+    /// Both edges
+    Both = 3,
+}
+
+/// Configuration of the recoverable fault A or B (`FCTRLA`/`FCTRLB`)
+///
+/// The fault A is triggered by the event input `MCE0`, the fault B by the
+/// event input `MCE1`. While the fault is active, the outputs are forced to
+/// their inactive level.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RecoverableFault {
+    source: FaultSource,
+    keep: bool,
+    qualify: bool,
+    restart: bool,
+    halt: FaultHalt,
+    blanking: Option<Blanking>,
+    blanking_ticks: u8,
+    filter: u8,
+}
+
+impl RecoverableFault {
+    /// A fault on the event input, inverted or not by `source`, which forces
+    /// the outputs inactive while the event is active, without halting the
+    /// counter
+    #[inline]
+    pub fn new(source: FaultSource) -> Self {
+        Self {
+            source,
+            keep: false,
+            qualify: false,
+            restart: false,
+            halt: FaultHalt::DISABLE,
+            blanking: None,
+            blanking_ticks: 0,
+            filter: 0,
+        }
+    }
+
+    /// Set the halt action on a fault
+    ///
+    /// * `HW`: the counter is halted while the fault is active
+    /// * `SW`: the counter is halted until the fault is cleared with
+    ///   [`Tcc::clear_faults`]
+    /// * `NR`: the fault is handled as a non-recoverable fault, the outputs
+    ///   are driven to their safe state
+    #[inline]
+    pub fn halt(mut self, halt: FaultHalt) -> Self {
+        self.halt = halt;
+        self
+    }
+
+    /// Keep the outputs inactive until the end of the period once the fault is
+    /// gone
+    #[inline]
+    pub fn keep(mut self, keep: bool) -> Self {
+        self.keep = keep;
+        self
+    }
+
+    /// Ignore the fault input while the output is inactive
+    #[inline]
+    pub fn qualify(mut self, qualify: bool) -> Self {
+        self.qualify = qualify;
+        self
+    }
+
+    /// Restart the counter on a fault
+    #[inline]
+    pub fn restart(mut self, restart: bool) -> Self {
+        self.restart = restart;
+        self
+    }
+
+    /// Ignore the fault input for `ticks` cycles of the counter after the
+    /// `edges` of the output, e.g. to hide the switching noise
+    #[inline]
+    pub fn blanking(mut self, edges: Option<Blanking>, ticks: u8) -> Self {
+        self.blanking = edges;
+        self.blanking_ticks = ticks;
+        self
+    }
+
+    /// Filter the fault input, which must stay active for `samples`
+    /// consecutive samples. `samples` is clamped to 15.
+    #[inline]
+    pub fn filter(mut self, samples: u8) -> Self {
+        self.filter = samples.min(15);
+        self
+    }
+
+    /// Value of the `FCTRLA`/`FCTRLB` register
+    fn bits(&self) -> u32 {
+        // SRC[1:0], KEEP[3], QUAL[4], BLANK[6:5], RESTART[7], HALT[9:8],
+        // BLANKVAL[23:16], FILTERVAL[27:24]
+        let blanking = self.blanking.map_or(0, |b| b as u32);
+        u8::from(self.source) as u32
+            | (self.keep as u32) << 3
+            | (self.qualify as u32) << 4
+            | blanking << 5
+            | (self.restart as u32) << 7
+            | (u8::from(self.halt) as u32) << 8
+            | (self.blanking_ticks as u32) << 16
+            | (self.filter as u32) << 24
+    }
+}
+
+/// Configuration of the non-recoverable fault 0 or 1
+///
+/// The fault `n` is triggered by the event input `EVn`. The outputs are
+/// driven to their safe state, see [`Config::safe_state`], until the fault
+/// is cleared with [`Tcc::clear_faults`] once the input is inactive.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct NonRecoverableFault {
+    invert: bool,
+    filter: u8,
+}
+
+impl NonRecoverableFault {
+    /// A fault on the active event input, without filter
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Trigger the fault on the inactive event input
+    #[inline]
+    pub fn invert(mut self, invert: bool) -> Self {
+        self.invert = invert;
+        self
+    }
+
+    /// Filter the fault input, which must stay active for `samples`
+    /// consecutive samples. `samples` is clamped to 15.
+    #[inline]
+    pub fn filter(mut self, samples: u8) -> Self {
+        self.filter = samples.min(15);
+        self
+    }
+}
+
+/// Configuration of a [`Tcc`]
+#[derive(Clone, Copy, Debug)]
+pub struct Config {
+    prescaler: Prescaler,
+    run_in_standby: bool,
+    waveform: Waveform,
+    ramp: Ramp,
+    polarity: u8,
+    circular_top: bool,
+    circular_cc: u8,
+    dead_time: (u8, u8),
+    faults: [Option<RecoverableFault>; 2],
+    non_recoverable_faults: [Option<NonRecoverableFault>; 2],
+    safe_state_enable: u8,
+    safe_state_value: u8,
+    invert: u8,
+}
+
+impl Config {
+    /// Default configuration: no prescaler, stopped in standby, normal
+    /// single-slope PWM without dithering or circular buffers, default output
+    /// matrix, no dead time and no fault
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            prescaler: Prescaler::DIV1,
+            run_in_standby: false,
+            waveform: Waveform::NPWM,
+            ramp: Ramp::RAMP1,
+            polarity: 0,
+            circular_top: false,
+            circular_cc: 0,
+            dead_time: (0, 0),
+            faults: [None; 2],
+            non_recoverable_faults: [None; 2],
+            safe_state_enable: 0,
+            safe_state_value: 0,
+            invert: 0,
+        }
+    }
+
+    /// Set the prescaler dividing the peripheral clock
+    #[inline]
+    pub fn prescaler(mut self, prescaler: Prescaler) -> Self {
+        self.prescaler = prescaler;
+        self
+    }
+
+    /// Keep counting in STANDBY
+    #[inline]
+    pub fn run_in_standby(mut self, enable: bool) -> Self {
+        self.run_in_standby = enable;
+        self
+    }
+
+    /// Set the waveform generation mode
+    ///
+    /// In the dual-slope modes, the counter counts up to the top value and
+    /// back down to zero, so the period is `2 * top` cycles of the counter.
+    /// The dual-slope modes differ by the point where the overflow interrupt
+    /// and event occur: at the top, at zero, or both. In
+    /// [`Waveform::DSCRITICAL`], the channel `x + CC_NUM / 2` replaces the
+    /// channel `x` while counting down, for non-centered pulses. In
+    /// [`Waveform::MFRQ`], the top value is the compare/capture channel 0.
+    #[inline]
+    pub fn waveform(mut self, waveform: Waveform) -> Self {
+        self.waveform = waveform;
+        self
+    }
+
+    /// Set the ramp operation of [`Waveform::NPWM`]
+    ///
+    /// The ramps other than [`Ramp::RAMP1`] alternate between two cycles A
+    /// and B, e.g. to drive two outputs from a single channel.
+    /// `Ramp::RAMP2C`, the critical single-slope operation, is not available
+    /// on SAMD21.
+    #[inline]
+    pub fn ramp(mut self, ramp: Ramp) -> Self {
+        self.ramp = ramp;
+        self
+    }
+
+    /// Set the polarity of the compare/capture `channel`
+    ///
+    /// With the default `false`, the outputs of the channel are high from the
+    /// start of the period until the compare match in single-slope PWM, and
+    /// while the counter is below the compare value in dual-slope PWM. With
+    /// `true`, they are low instead.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `channel` is not lower than 6. The channels above
+    /// [`Instance::CC_NUM`] are ignored.
+    #[inline]
+    pub fn polarity(mut self, channel: usize, inverted: bool) -> Self {
+        assert!(channel < 6);
+        if inverted {
+            self.polarity |= 1 << channel;
+        } else {
+            self.polarity &= !(1 << channel);
+        }
+        self
+    }
+
+    /// Swap the top value and its buffer at each update, i.e. alternate
+    /// between two periods
+    #[inline]
+    pub fn circular_top(mut self, enable: bool) -> Self {
+        self.circular_top = enable;
+        self
+    }
+
+    /// Swap the value of the compare/capture `channel` and its buffer at each
+    /// update, i.e. alternate between two duty cycles
+    ///
+    /// # Panics
+    ///
+    /// Panics if `channel` is not lower than 4. The channels above
+    /// [`Instance::CC_NUM`] are ignored.
+    #[inline]
+    pub fn circular_cc(mut self, channel: usize, enable: bool) -> Self {
+        assert!(channel < 4);
+        if enable {
+            self.circular_cc |= 1 << channel;
+        } else {
+            self.circular_cc &= !(1 << channel);
+        }
+        self
+    }
+
+    /// Set the dead times inserted before the rising edges of the low side
+    /// and high side outputs of the [`ComplementaryPair`]s, in cycles of the
+    /// peripheral clock
+    #[inline]
+    pub fn dead_time(mut self, low: u8, high: u8) -> Self {
+        self.dead_time = (low, high);
+        self
+    }
+
+    /// Configure the recoverable fault A, or disable it if `None`
+    #[inline]
+    pub fn fault_a(mut self, fault: Option<RecoverableFault>) -> Self {
+        self.faults[0] = fault;
+        self
+    }
+
+    /// Configure the recoverable fault B, or disable it if `None`
+    #[inline]
+    pub fn fault_b(mut self, fault: Option<RecoverableFault>) -> Self {
+        self.faults[1] = fault;
+        self
+    }
+
+    /// Configure the non-recoverable fault `n`, or disable it if `None`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is not 0 or 1.
+    #[inline]
+    pub fn non_recoverable_fault(mut self, n: usize, fault: Option<NonRecoverableFault>) -> Self {
+        self.non_recoverable_faults[n] = fault;
+        self
+    }
+
+    /// Set the level of `WO[output]` on a non-recoverable fault, or leave it
+    /// tri-stated if `None`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `output` is not lower than 8.
+    #[inline]
+    pub fn safe_state(mut self, output: usize, level: Option<bool>) -> Self {
+        assert!(output < 8);
+        let mask = 1 << output;
+        self.safe_state_enable &= !mask;
+        self.safe_state_value &= !mask;
+        if let Some(level) = level {
+            self.safe_state_enable |= mask;
+            if level {
+                self.safe_state_value |= mask;
+            }
+        }
+        self
+    }
+
+    /// Invert `WO[output]`, after the dead-time insertion and the pattern
+    /// generation
+    ///
+    /// # Panics
+    ///
+    /// Panics if `output` is not lower than 8.
+    #[inline]
+    pub fn invert(mut self, output: usize, invert: bool) -> Self {
+        assert!(output < 8);
+        if invert {
+            self.invert |= 1 << output;
+        } else {
+            self.invert &= !(1 << output);
+        }
+        self
+    }
+}
+
+impl Default for Config {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Output levels forced by the pattern generator
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Pattern {
+    enable: u8,
+    value: u8,
+}
+
+impl Pattern {
+    /// A pattern leaving all the outputs to the waveform generation
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Force `WO[output]` to `level`, or leave it to the waveform generation
+    /// if `None`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `output` is not lower than 8.
+    #[inline]
+    pub fn output(mut self, output: usize, level: Option<bool>) -> Self {
+        assert!(output < 8);
+        let mask = 1 << output;
+        self.enable &= !mask;
+        self.value &= !mask;
+        if let Some(level) = level {
+            self.enable |= mask;
+            if level {
+                self.value |= mask;
+            }
+        }
+        self
+    }
+
+    /// Returns the level forced on `WO[output]`, if any
+    #[inline]
+    pub fn level(&self, output: usize) -> Option<bool> {
+        let mask = 1 << output;
+        (self.enable & mask != 0).then(|| self.value & mask != 0)
+    }
+
+    /// Value of the `PATT` register
+    #[inline]
+    fn bits(&self) -> u16 {
+        self.enable as u16 | (self.value as u16) << 8
+    }
+
+    #[inline]
+    fn from_bits(bits: u16) -> Self {
+        Self {
+            enable: bits as u8,
+            value: (bits >> 8) as u8,
+        }
+    }
+}
+
+/// Fault inputs of a TCC, see [`Tcc::connect_fault`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FaultInput {
+    /// Recoverable fault A, on the event input `MCE0`
+    A,
+    /// Recoverable fault B, on the event input `MCE1`
+    B,
+    /// Non-recoverable fault 0, on the event input `EV0`
+    NonRecoverable0,
+    /// Non-recoverable fault 1, on the event input `EV1`
+    NonRecoverable1,
+}
+
+impl FaultInput {
+    /// EVSYS user of the input, relative to the event input 0
+    #[inline]
+    fn user_offset(self) -> u8 {
+        match self {
+            FaultInput::NonRecoverable0 => 0,
+            FaultInput::NonRecoverable1 => 1,
+            FaultInput::A => 2,
+            FaultInput::B => 3,
+        }
+    }
+}
+
+//==============================================================================
+// Tcc
+//==============================================================================
+
+/// A TCC driving several outputs, see the [module documentation](self)
+pub struct Tcc<T: Instance, O: Outputs<T>> {
+    tcc: T,
+    outputs: O,
+    freq: Hertz,
+    prescaler: Prescaler,
+}
+
+impl<T: Instance, O: Outputs<T>> Tcc<T, O> {
+    const DISTINCT_OUTPUTS: () = check(O::COUNT == O::MASK.count_ones());
+
+    /// Configure `tcc` with `config`, driving the `outputs`
+    ///
+    /// The top value is the largest value of the counter, and all the
+    /// compare/capture channels are zero. The TCC is left disabled, see
+    /// [`enable`](Self::enable).
+    ///
+    /// Fails to compile if two outputs use the same waveform output.
+    pub fn new(tcc: T, clock: &T::Clock, apb: &mut Apb, outputs: O, config: Config) -> Self {
+        let () = Self::DISTINCT_OUTPUTS;
+        T::enable_apb(apb, true);
+        let tcc = Self {
+            tcc,
+            outputs,
+            freq: T::clock_freq(clock),
+            prescaler: config.prescaler,
+        };
+        tcc.reset();
+        tcc.configure(config);
+        tcc
+    }
+
+    #[inline]
+    fn regs(&self) -> &RegisterBlock {
+        self.tcc.register_block()
+    }
+
+    /// Wait for the synchronization of the registers
+    #[inline]
+    fn sync(&self) {
+        while self.regs().syncbusy.read().bits() != 0 {}
+    }
+
+    fn reset(&self) {
+        let regs = self.regs();
+        regs.ctrla.modify(|_, w| w.enable().clear_bit());
+        self.sync();
+        regs.ctrla.write(|w| w.swrst().set_bit());
+        while regs.syncbusy.read().swrst().bit_is_set() {}
+    }
+
+    /// Write the enable-protected configuration. The TCC must be disabled.
+    fn configure(&self, config: Config) {
+        let regs = self.regs();
+        regs.ctrla.write(|w| {
+            w.prescaler().variant(config.prescaler);
+            w.runstdby().bit(config.run_in_standby)
+        });
+        regs.wexctrl.write(|w| unsafe {
+            w.dtien0().bit(O::DTI & 0x1 != 0);
+            w.dtien1().bit(O::DTI & 0x2 != 0);
+            w.dtien2().bit(O::DTI & 0x4 != 0);
+            w.dtien3().bit(O::DTI & 0x8 != 0);
+            w.dtls().bits(config.dead_time.0);
+            w.dths().bits(config.dead_time.1)
+        });
+        let [fault_a, fault_b] = config.faults;
+        regs.fctrla
+            .write(|w| unsafe { w.bits(fault_a.map_or(0, |f| f.bits())) });
+        regs.fctrlb
+            .write(|w| unsafe { w.bits(fault_b.map_or(0, |f| f.bits())) });
+        let [nr0, nr1] = config.non_recoverable_faults;
+        // NRE[7:0], NRV[15:8], INVEN[23:16], FILTERVAL0[27:24],
+        // FILTERVAL1[31:28]
+        let filter = |f: Option<NonRecoverableFault>| f.map_or(0, |f| f.filter as u32);
+        regs.drvctrl.write(|w| unsafe {
+            w.bits(
+                config.safe_state_enable as u32
+                    | (config.safe_state_value as u32) << 8
+                    | (config.invert as u32) << 16
+                    | filter(nr0) << 24
+                    | filter(nr1) << 28,
+            )
+        });
+        regs.evctrl.write(|w| {
+            if let Some(fault) = nr0 {
+                w.evact0().fault();
+                w.tcinv0().bit(fault.invert);
+                w.tcei0().set_bit();
+            }
+            if let Some(fault) = nr1 {
+                w.evact1().fault();
+                w.tcinv1().bit(fault.invert);
+                w.tcei1().set_bit();
+            }
+            w.mcei0().bit(fault_a.is_some());
+            w.mcei1().bit(fault_b.is_some())
+        });
+        // WAVEGEN[2:0], RAMP[5:4], CIPEREN[7], CICCEN[11:8], POL[21:16]
+        let channels = (1 << T::CC_NUM) - 1;
+        regs.wave.write(|w| unsafe {
+            w.bits(
+                u8::from(config.waveform) as u32
+                    | (u8::from(config.ramp) as u32) << 4
+                    | (config.circular_top as u32) << 7
+                    | (config.circular_cc as u32 & channels) << 8
+                    | (config.polarity as u32 & channels) << 16,
+            )
+        });
+        self.sync();
+        regs.per().write(|w| unsafe { w.bits(T::MAX) });
+        self.sync();
+    }
+
+    /// Returns the frequency of the counter, i.e. the peripheral clock
+    /// divided by the prescaler
+    pub fn freq(&self) -> Hertz {
+        Hertz(self.freq.0 / prescaler_divider(self.prescaler.into()))
+    }
+
+    /// Enable the TCC, which starts counting and driving the outputs
+    pub fn enable(&mut self) {
+        self.regs().ctrla.modify(|_, w| w.enable().set_bit());
+        self.sync();
+    }
+
+    /// Disable the TCC
+    pub fn disable(&mut self) {
+        self.regs().ctrla.modify(|_, w| w.enable().clear_bit());
+        self.sync();
+    }
+
+    /// Returns true if the TCC is enabled
+    #[inline]
+    pub fn is_enabled(&self) -> bool {
+        self.regs().ctrla.read().enable().bit_is_set()
+    }
+
+    /// Returns the top value of the counter
+    #[inline]
+    pub fn top(&self) -> u32 {
+        self.regs().per().read().bits()
+    }
+
+    /// Set the top value of the counter, setting the period of the outputs
+    /// to `top + 1` cycles of the counter, or `2 * top` cycles in dual-slope
+    /// PWM
+    ///
+    /// # Panics
+    ///
+    /// Panics if `top` is larger than [`Instance::MAX`].
+    pub fn set_top(&mut self, top: u32) {
+        assert!(top <= T::MAX);
+        self.regs().per().write(|w| unsafe { w.bits(top) });
+        self.sync();
+    }
+
+    /// Returns the value of the compare/capture `channel`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `channel` is not lower than [`Instance::CC_NUM`].
+    #[inline]
+    pub fn cc(&self, channel: usize) -> u32 {
+        assert!(channel < T::CC_NUM);
+        self.regs().cc()[channel].read().cc().bits()
+    }
+
+    /// Set the value of the compare/capture `channel`, i.e. the duty cycle of
+    /// the outputs it drives, in cycles of the counter
+    ///
+    /// # Panics
+    ///
+    /// Panics if `channel` is not lower than [`Instance::CC_NUM`], or if
+    /// `value` is larger than [`Instance::MAX`].
+    pub fn set_cc(&mut self, channel: usize, value: u32) {
+        assert!(channel < T::CC_NUM);
+        assert!(value <= T::MAX);
+        self.regs().cc()[channel].write(|w| unsafe { w.cc().bits(value) });
+        self.sync();
+    }
+
+    /// Set the top value of the counter at the end of the current period,
+    /// through the `PERBUF` register (`PERB` on SAMD11/SAMD21)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `top` is larger than [`Instance::MAX`].
+    pub fn set_top_buffered(&mut self, top: u32) {
+        assert!(top <= T::MAX);
+        #[cfg(feature = "min-samd51g")]
+        self.regs().perbuf().write(|w| unsafe { w.bits(top) });
+        #[cfg(any(feature = "samd11", feature = "samd21"))]
+        self.regs().perb().write(|w| unsafe { w.bits(top) });
+        self.sync();
+    }
+
+    /// Set the value of the compare/capture `channel` at the end of the
+    /// current period, through the `CCBUF` registers (`CCB` on
+    /// SAMD11/SAMD21)
+    ///
+    /// All the channels written during a period are updated at once, so that
+    /// the duty cycles never mix an old and a new value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `channel` is not lower than [`Instance::CC_NUM`], or if
+    /// `value` is larger than [`Instance::MAX`].
+    pub fn set_cc_buffered(&mut self, channel: usize, value: u32) {
+        assert!(channel < T::CC_NUM);
+        assert!(value <= T::MAX);
+        #[cfg(feature = "min-samd51g")]
+        self.regs().ccbuf()[channel].write(|w| unsafe { w.bits(value) });
+        #[cfg(any(feature = "samd11", feature = "samd21"))]
+        self.regs().ccb()[channel].write(|w| unsafe { w.bits(value) });
+        self.sync();
+    }
+
+    /// Returns the buffered value of the compare/capture `channel`, which is
+    /// the value written by [`set_cc_buffered`](Self::set_cc_buffered) until
+    /// the end of the period
+    ///
+    /// # Panics
+    ///
+    /// Panics if `channel` is not lower than [`Instance::CC_NUM`].
+    #[inline]
+    pub fn cc_buffered(&self, channel: usize) -> u32 {
+        assert!(channel < T::CC_NUM);
+        #[cfg(feature = "min-samd51g")]
+        return self.regs().ccbuf()[channel].read().bits();
+        #[cfg(any(feature = "samd11", feature = "samd21"))]
+        return self.regs().ccb()[channel].read().bits();
+    }
+
+    /// Lock the update of the top and compare/capture values from their
+    /// buffers, so that the buffers written over several periods take effect
+    /// at once after [`unlock_update`](Self::unlock_update)
+    pub fn lock_update(&mut self) {
+        self.regs().ctrlbset.write(|w| w.lupd().set_bit());
+        self.sync();
+    }
+
+    /// Unlock the update of the top and compare/capture values, which then
+    /// take their buffered values at the next update
+    pub fn unlock_update(&mut self) {
+        self.regs().ctrlbclr.write(|w| w.lupd().set_bit());
+        self.sync();
+    }
+
+    /// Set the dead times of the [`ComplementaryPair`]s, in cycles of the
+    /// peripheral clock
+    ///
+    /// The TCC is briefly disabled, since the dead times are
+    /// enable-protected.
+    pub fn set_dead_time(&mut self, low: u8, high: u8) {
+        let enabled = self.is_enabled();
+        self.disable();
+        self.regs().wexctrl.modify(|_, w| unsafe {
+            w.dtls().bits(low);
+            w.dths().bits(high)
+        });
+        if enabled {
+            self.enable();
+        }
+    }
+
+    /// Connect the EIC `source` to the fault `input` through the EVSYS
+    /// `channel`
+    ///
+    /// The EIC pin must be configured to generate events, with a sense on the
+    /// level or edges activating the fault. The fault itself is configured
+    /// by the [`Config`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `channel` is not an EVSYS channel of the device.
+    pub fn connect_fault(
+        &mut self,
+        evsys: &mut EVSYS,
+        apb: &mut Apb,
+        channel: usize,
+        source: &impl ExternalInterrupt,
+        input: FaultInput,
+    ) {
+        assert!(channel < evsys::NUM_CHANNELS);
+        evsys::enable_apb(apb);
+        evsys::connect(
+            evsys,
+            channel,
+            evsys::EXTINT_GENERATOR + source.id() as u8,
+            T::EVSYS_USER + input.user_offset(),
+        );
+    }
+
+    /// Disconnect the fault `input` and the EVSYS `channel`
+    pub fn disconnect_fault(&mut self, evsys: &mut EVSYS, channel: usize, input: FaultInput) {
+        evsys::disconnect(evsys, channel, T::EVSYS_USER + input.user_offset());
+    }
+
+    /// Returns the active faults
+    #[inline]
+    pub fn faults(&self) -> Faults {
+        Faults::from_bits_truncate((self.regs().status.read().bits() >> STATUS_FAULTS) as u8)
+    }
+
+    /// Returns the fault inputs which are currently active
+    #[inline]
+    pub fn fault_inputs(&self) -> Faults {
+        Faults::from_bits_truncate((self.regs().status.read().bits() >> STATUS_FAULT_INPUTS) as u8)
+    }
+
+    /// Clear the `faults`
+    ///
+    /// A recoverable fault with the `SW` halt action, or a non-recoverable
+    /// fault, is only cleared once its input is inactive, see
+    /// [`fault_inputs`](Self::fault_inputs).
+    #[inline]
+    pub fn clear_faults(&mut self, faults: Faults) {
+        self.regs()
+            .status
+            .write(|w| unsafe { w.bits((faults.bits() as u32) << STATUS_FAULTS) });
+    }
+
+    /// Read the interrupt flags
+    #[inline]
+    pub fn read_flags(&self) -> Flags {
+        Flags::from_bits_truncate(self.regs().intflag.read().bits())
+    }
+
+    /// Clear the interrupt flags
+    #[inline]
+    pub fn clear_flags(&mut self, flags: Flags) {
+        self.regs()
+            .intflag
+            .write(|w| unsafe { w.bits(flags.bits()) });
+    }
+
+    /// Enable the interrupts
+    #[inline]
+    pub fn enable_interrupts(&mut self, flags: Flags) {
+        self.regs()
+            .intenset
+            .write(|w| unsafe { w.bits(flags.bits()) });
+    }
+
+    /// Disable the interrupts
+    #[inline]
+    pub fn disable_interrupts(&mut self, flags: Flags) {
+        self.regs()
+            .intenclr
+            .write(|w| unsafe { w.bits(flags.bits()) });
+    }
+
+    /// Reset and disable the TCC, and return the underlying instance and the
+    /// outputs
+    pub fn free(self, apb: &mut Apb) -> (T, O) {
+        self.reset();
+        T::enable_apb(apb, false);
+        (self.tcc, self.outputs)
+    }
+
+    #[inline]
+    fn read_pattern(&self) -> Pattern {
+        Pattern::from_bits(self.regs().patt.read().bits())
+    }
+
+    fn write_pattern(&mut self, pattern: Pattern) {
+        let regs = self.regs();
+        if self.is_enabled() {
+            #[cfg(feature = "min-samd51g")]
+            regs.pattbuf.write(|w| unsafe { w.bits(pattern.bits()) });
+            #[cfg(any(feature = "samd11", feature = "samd21"))]
+            regs.pattb.write(|w| unsafe { w.bits(pattern.bits()) });
+        } else {
+            regs.patt.write(|w| unsafe { w.bits(pattern.bits()) });
+            self.sync();
+        }
+    }
+}
+
+impl<T: Dithering, O: Outputs<T>> Tcc<T, O> {
+    /// Set the dithering resolution
+    ///
+    /// With [`Resolution::DITH4`], `DITH5` and `DITH6`, the top and
+    /// compare/capture values become fixed-point numbers with 4, 5 or 6
+    /// fractional bits: a fraction of `n / 16` (resp. 32, 64) extends the
+    /// period or the pulse by one cycle in `n` periods out of 16 (resp. 32,
+    /// 64). The integer part keeps the remaining bits of the registers.
+    ///
+    /// The TCC is briefly disabled, since the resolution is enable-protected.
+    pub fn set_resolution(&mut self, resolution: Resolution) {
+        let enabled = self.is_enabled();
+        self.disable();
+        self.regs()
+            .ctrla
+            .modify(|_, w| w.resolution().variant(resolution));
+        if enabled {
+            self.enable();
+        }
+    }
+}
+
+impl<T: OutputMatrixSelection, O: Outputs<T>> Tcc<T, O> {
+    /// Set the output matrix
+    ///
+    /// The TCC is briefly disabled, since the output matrix is
+    /// enable-protected.
+    pub fn set_output_matrix(&mut self, output_matrix: OutputMatrix) {
+        let enabled = self.is_enabled();
+        self.disable();
+        self.regs()
+            .wexctrl
+            .modify(|_, w| unsafe { w.otmx().bits(output_matrix as u8) });
+        if enabled {
+            self.enable();
+        }
+    }
+}
+
+impl<T: PatternGeneration, O: Outputs<T>> Tcc<T, O> {
+    /// Returns the current pattern
+    #[inline]
+    pub fn pattern(&self) -> Pattern {
+        self.read_pattern()
+    }
+
+    /// Set the pattern forced on the outputs
+    ///
+    /// While the TCC is enabled, the pattern is buffered and applied at the
+    /// end of the current period, so that all the outputs switch at once.
+    #[inline]
+    pub fn set_pattern(&mut self, pattern: Pattern) {
+        self.write_pattern(pattern);
+    }
+}
+
+//==============================================================================
+// Pwm
+//==============================================================================
+
+/// Prescalers of the TCC, with their divider, in the order of the values of
+/// the `PRESCALER` field
+const PRESCALERS: [(Prescaler, u32); 8] = [
+    (Prescaler::DIV1, 1),
+    (Prescaler::DIV2, 2),
+    (Prescaler::DIV4, 4),
+    (Prescaler::DIV8, 8),
+    (Prescaler::DIV16, 16),
+    (Prescaler::DIV64, 64),
+    (Prescaler::DIV256, 256),
+    (Prescaler::DIV1024, 1024),
+];
+
+/// Returns the divider of a TC or TCC prescaler, from the value of its
+/// `PRESCALER` field
+#[inline]
+pub(crate) fn prescaler_divider(prescaler: u8) -> u32 {
+    PRESCALERS[prescaler as usize].1
+}
+
+/// Returns the smallest prescaler, for the best resolution, and the top value
+/// giving a period of `1 / freq` with a peripheral clock at `clock_freq`
+fn period_params(clock_freq: Hertz, freq: Hertz, max: u32) -> (Prescaler, u32) {
+    let ticks = clock_freq.0 / freq.0.max(1);
+    for &(prescaler, divider) in PRESCALERS.iter() {
+        let top = (ticks / divider).saturating_sub(1);
+        if top <= max {
+            return (prescaler, top);
+        }
+    }
+    (Prescaler::DIV1024, max)
+}
+
+/// A TCC generating PWM signals with a common period on several outputs
+///
+/// Each output is driven by the compare/capture channel `WO[x] % CC_NUM`,
+/// and both sides of a [`ComplementaryPair`] by the channel of the low side.
+/// The outputs must use distinct channels, which is checked at compile time.
+/// The period and duty cycles are double-buffered: a new value is applied at
+/// the end of the current period.
+///
+/// ```no_run
+/// # use atsamd_hal::clock::GenericClockController;
+/// # use atsamd_hal::gpio::Pins;
+/// # use atsamd_hal::pac::{Peripherals, TCC0};
+/// # use atsamd_hal::prelude::*;
+/// # use atsamd_hal::pwm::Channel;
+/// # use atsamd_hal::tcc::{Output, Pwm};
+/// let mut peripherals = Peripherals::take().unwrap();
+/// let mut clocks = GenericClockController::with_external_32kosc(
+///     peripherals.GCLK,
+///     &mut peripherals.MCLK,
+///     &mut peripherals.OSC32KCTRL,
+///     &mut peripherals.OSCCTRL,
+///     &mut peripherals.NVMCTRL,
+/// );
+/// let gclk0 = clocks.gclk0();
+/// let pins = Pins::new(peripherals.PORT);
+/// let clock = clocks.tcc0_tcc1(&gclk0).unwrap();
+/// // WO[0], WO[1] and WO[2], driven by the channels 0, 1 and 2
+/// let outputs = (
+///     Output::<TCC0, _>::new(pins.pa08),
+///     Output::<TCC0, _>::new(pins.pa09),
+///     Output::<TCC0, _>::new(pins.pa10),
+/// );
+/// let mut pwm = Pwm::new(peripherals.TCC0, &clock, &mut peripherals.MCLK, outputs, 20.khz());
+/// let max = pwm.get_max_duty();
+/// pwm.set_duty(Channel::_0, max / 4);
+/// pwm.set_duty(Channel::_1, max / 2);
+/// pwm.set_duty(Channel::_2, max * 3 / 4);
+/// ```
+pub struct Pwm<T: Instance, O: Outputs<T>> {
+    tcc: Tcc<T, O>,
+    /// Channels disabled on the instances without a pattern generator, and
+    /// their duty cycles
+    disabled: u8,
+    duties: [u32; 6],
+}
+
+impl<T: Instance, O: Outputs<T>> Pwm<T, O> {
+    const DISTINCT_CHANNELS: () = check(O::CHANNEL_COUNT == O::CHANNELS.count_ones());
+
+    /// Drive the `outputs` with a period of `1 / freq`, and a duty cycle of
+    /// zero
+    ///
+    /// The prescaler is the smallest one reaching the period, for the best
+    /// resolution. Fails to compile if two outputs use the same waveform
+    /// output or the same compare/capture channel.
+    pub fn new(
+        tcc: T,
+        clock: &T::Clock,
+        apb: &mut Apb,
+        outputs: O,
+        freq: impl Into<Hertz>,
+    ) -> Self {
+        let () = Self::DISTINCT_CHANNELS;
+        let (prescaler, top) = period_params(T::clock_freq(clock), freq.into(), T::MAX);
+        let config = Config::new().prescaler(prescaler);
+        let mut tcc = Tcc::new(tcc, clock, apb, outputs, config);
+        tcc.set_top(top);
+        tcc.enable();
+        Self {
+            tcc,
+            disabled: 0,
+            duties: [0; 6],
+        }
+    }
+
+    /// Returns the bit mask of the waveform outputs driven by `channel`
+    fn channel_outputs(channel: usize) -> u8 {
+        let mut mask = 0;
+        for wo in 0..8 {
+            if O::MASK & (1 << wo) == 0 {
+                continue;
+            }
+            // The high side of a pair follows the channel of the low side
+            let source = if wo >= 4 && O::DTI & (1 << (wo - 4)) != 0 {
+                wo - 4
+            } else {
+                wo
+            };
+            if source % T::CC_NUM == channel {
+                mask |= 1 << wo;
+            }
+        }
+        mask
+    }
+
+    /// Returns the compare/capture channel index of `channel`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `channel` does not drive any output.
+    #[inline]
+    fn index(channel: Channel) -> usize {
+        let index = channel as usize;
+        assert!(index < 8 && O::CHANNELS & (1 << index) != 0);
+        index
+    }
+
+    /// Reset and disable the TCC, and return the underlying instance and the
+    /// outputs
+    #[inline]
+    pub fn free(self, apb: &mut Apb) -> (T, O) {
+        self.tcc.free(apb)
+    }
+}
+
+impl<T: Instance, O: Outputs<T>> crate::ehal::Pwm for Pwm<T, O> {
+    type Channel = Channel;
+    type Time = Hertz;
+    type Duty = u32;
+
+    /// Drive the outputs of `channel` low, with the pattern generator
+    ///
+    /// The instances without a pattern generator keep the duty cycle of the
+    /// channel aside, and set it to zero until the channel is enabled again.
+    fn disable(&mut self, channel: Channel) {
+        let index = Self::index(channel);
+        if T::PATTERN_GENERATION {
+            let mask = Self::channel_outputs(index);
+            let mut pattern = self.tcc.read_pattern();
+            pattern.enable |= mask;
+            pattern.value &= !mask;
+            self.tcc.write_pattern(pattern);
+        } else if self.disabled & (1 << index) == 0 {
+            self.duties[index] = self.tcc.cc_buffered(index);
+            self.disabled |= 1 << index;
+            self.tcc.set_cc_buffered(index, 0);
+        }
+    }
+
+    /// Drive the outputs of `channel` with its duty cycle again
+    fn enable(&mut self, channel: Channel) {
+        let index = Self::index(channel);
+        if T::PATTERN_GENERATION {
+            let mask = Self::channel_outputs(index);
+            let mut pattern = self.tcc.read_pattern();
+            pattern.enable &= !mask;
+            self.tcc.write_pattern(pattern);
+        } else if self.disabled & (1 << index) != 0 {
+            self.disabled &= !(1 << index);
+            self.tcc.set_cc_buffered(index, self.duties[index]);
+        }
+    }
+
+    fn get_period(&self) -> Hertz {
+        Hertz(self.tcc.freq().0 / (self.tcc.top() + 1))
+    }
+
+    fn get_duty(&self, channel: Channel) -> u32 {
+        let index = Self::index(channel);
+        if self.disabled & (1 << index) != 0 {
+            self.duties[index]
+        } else {
+            self.tcc.cc_buffered(index)
+        }
+    }
+
+    fn get_max_duty(&self) -> u32 {
+        self.tcc.top()
+    }
+
+    fn set_duty(&mut self, channel: Channel, duty: u32) {
+        let index = Self::index(channel);
+        let duty = duty.min(self.get_max_duty());
+        if self.disabled & (1 << index) != 0 {
+            self.duties[index] = duty;
+        } else {
+            self.tcc.set_cc_buffered(index, duty);
+        }
+    }
+
+    /// Set the period of all the outputs
+    ///
+    /// The new period is applied at the end of the current one, unless the
+    /// prescaler changes: the TCC is then briefly disabled. The duty cycles
+    /// are not scaled.
+    fn set_period<P>(&mut self, period: P)
+    where
+        P: Into<Hertz>,
+    {
+        let (prescaler, top) = period_params(self.tcc.freq, period.into(), T::MAX);
+        if prescaler == self.tcc.prescaler {
+            self.tcc.set_top_buffered(top);
+        } else {
+            self.tcc.disable();
+            self.tcc
+                .regs()
+                .ctrla
+                .modify(|_, w| w.prescaler().variant(prescaler));
+            self.tcc.prescaler = prescaler;
+            self.tcc.set_top(top);
+            self.tcc.enable();
+        }
+    }
+}
+
+/// A channel of a TCC PWM, as an embedded HAL 1.0
+/// [`SetDutyCycle`](crate::ehal_1::pwm::SetDutyCycle)
+///
+/// This works with [`Pwm`] and the TCC types of the [`pwm`](crate::pwm)
+/// module. When their top value exceeds `u16::MAX`, the maximum duty cycle is
+/// `u16::MAX` and the duty cycles are scaled to the top value.
+#[cfg(feature = "ehal-1")]
+pub struct PwmChannel<'a, P> {
+    pwm: &'a mut P,
+    channel: Channel,
+}
+
+#[cfg(feature = "ehal-1")]
+impl<'a, P> PwmChannel<'a, P>
+where
+    P: crate::ehal::Pwm<Channel = Channel, Duty = u32>,
+{
+    /// Borrow `channel` of `pwm`
+    #[inline]
+    pub fn new(pwm: &'a mut P, channel: Channel) -> Self {
+        Self { pwm, channel }
+    }
+}
+
+#[cfg(feature = "ehal-1")]
+impl<P> crate::ehal_1::pwm::ErrorType for PwmChannel<'_, P>
+where
+    P: crate::ehal::Pwm<Channel = Channel, Duty = u32>,
+{
+    type Error = core::convert::Infallible;
+}
+
+#[cfg(feature = "ehal-1")]
+impl<P> crate::ehal_1::pwm::SetDutyCycle for PwmChannel<'_, P>
+where
+    P: crate::ehal::Pwm<Channel = Channel, Duty = u32>,
+{
+    #[inline]
+    fn max_duty_cycle(&self) -> u16 {
+        self.pwm.get_max_duty().min(u16::MAX as u32) as u16
+    }
+
+    fn set_duty_cycle(&mut self, duty: u16) -> Result<(), Self::Error> {
+        let top = self.pwm.get_max_duty() as u64;
+        let max = self.max_duty_cycle() as u64;
+        let duty = match max {
+            0 => 0,
+            _ => (duty as u64).min(max) * top / max,
+        };
+        self.pwm.set_duty(self.channel, duty as u32);
+        Ok(())
+    }
+}
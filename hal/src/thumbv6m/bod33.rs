@@ -0,0 +1,245 @@
+//! # Brown-out detector (BOD33)
+//!
+//! On SAMD11/SAMD21, the BOD33 is part of the `SYSCTRL` peripheral. It
+//! monitors `VDD` and, when it falls below the threshold, either resets the
+//! device or raises the `BOD33DET` flag of the `SYSCTRL` interrupt. The
+//! interrupt leaves some time to save data before the supply is too low, then
+//! the firmware can reset the device itself.
+//!
+//! The BOD33 is enabled at startup from the NVM user row. [`Bod33::new`]
+//! replaces that configuration. It takes the [`Bod33Token`], which the
+//! [`GenericClockController`](crate::clock::GenericClockController) hands out
+//! once, since it owns the rest of `SYSCTRL`.
+//!
+//! ```no_run
+//! # use atsamd_hal::bod33::*;
+//! # use atsamd_hal::clock::GenericClockController;
+//! # fn f(clocks: &mut GenericClockController) {
+//! let token = clocks.take_bod33_token().unwrap();
+//! let mut bod33 = Bod33::new(
+//!     token,
+//!     Bod33Config::new().level(39).action(Bod33Action::INTERRUPT),
+//! );
+//! bod33.enable_interrupts(Flags::BOD33DET);
+//! # }
+//! ```
+
+use bitflags::bitflags;
+
+use crate::pac::{sysctrl, SYSCTRL};
+
+pub use crate::pac::sysctrl::bod33::ACTION_A as Bod33Action;
+pub use crate::pac::sysctrl::bod33::PSEL_A as Bod33Prescaler;
+
+bitflags! {
+    /// BOD33 interrupt bit flags
+    ///
+    /// The binary format of the underlying bits exactly matches the BOD33
+    /// bits of the `SYSCTRL` `INTFLAG` register.
+    pub struct Flags: u32 {
+        /// BOD33 ready
+        const BOD33RDY = 1 << 9;
+        /// `VDD` crossed the BOD33 threshold
+        const BOD33DET = 1 << 10;
+        /// BOD33 synchronization ready
+        const B33SRDY = 1 << 11;
+    }
+}
+
+/// Configuration of the BOD33
+#[derive(Clone, Copy, Debug)]
+pub struct Bod33Config {
+    level: u8,
+    hysteresis: bool,
+    action: Bod33Action,
+    sampling: Option<Bod33Prescaler>,
+    run_in_standby: bool,
+}
+
+impl Bod33Config {
+    /// Default configuration: the lowest threshold, no hysteresis, reset on
+    /// detection, and a continuous monitoring in active and standby modes
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            level: 0,
+            hysteresis: false,
+            action: Bod33Action::RESET,
+            sampling: None,
+            run_in_standby: true,
+        }
+    }
+
+    /// Set the threshold on `VDD`
+    ///
+    /// Only the low six bits are used. See the BOD33 electrical
+    /// characteristics in the datasheet for the voltage of each level. The
+    /// factory setting is in the NVM user row.
+    #[inline]
+    pub fn level(mut self, level: u8) -> Self {
+        self.level = level & 0x3F;
+        self
+    }
+
+    /// Enable the hysteresis on the threshold
+    #[inline]
+    pub fn hysteresis(mut self, enable: bool) -> Self {
+        self.hysteresis = enable;
+        self
+    }
+
+    /// Set the action taken when the supply falls below the threshold
+    #[inline]
+    pub fn action(mut self, action: Bod33Action) -> Self {
+        self.action = action;
+        self
+    }
+
+    /// Sample the supply every `prescaler` cycles of the clock derived from
+    /// the OSCULP32K, instead of monitoring it continuously
+    #[inline]
+    pub fn sampling(mut self, prescaler: Bod33Prescaler) -> Self {
+        self.sampling = Some(prescaler);
+        self
+    }
+
+    /// Keep the BOD33 running in STANDBY
+    ///
+    /// It is enabled by default.
+    #[inline]
+    pub fn run_in_standby(mut self, enable: bool) -> Self {
+        self.run_in_standby = enable;
+        self
+    }
+}
+
+impl Default for Bod33Config {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Singleton token granting the right to use the BOD33
+///
+/// Take it with
+/// [`GenericClockController::take_bod33_token`](crate::clock::GenericClockController::take_bod33_token).
+pub struct Bod33Token {
+    _private: (),
+}
+
+impl Bod33Token {
+    /// Create a new token
+    ///
+    /// # Safety
+    ///
+    /// There must never be more than one instance of the token at any time.
+    #[inline]
+    pub(crate) unsafe fn new() -> Self {
+        Self { _private: () }
+    }
+}
+
+/// The BOD33 of the `SYSCTRL` peripheral
+///
+/// Since `SYSCTRL` is shared with the clock configuration, the `Bod33` owns
+/// the [`Bod33Token`] instead. It only accesses the `BOD33` register and the
+/// BOD33 bits of the interrupt registers, which nothing else in the HAL uses,
+/// so it can be moved to an interrupt handler on its own.
+pub struct Bod33 {
+    token: Bod33Token,
+}
+
+impl Bod33 {
+    /// Configure and enable the BOD33
+    pub fn new(token: Bod33Token, config: Bod33Config) -> Self {
+        let mut bod33 = Self { token };
+        bod33.reconfigure(config);
+        bod33
+    }
+
+    #[inline]
+    fn sysctrl(&self) -> &sysctrl::RegisterBlock {
+        // Safety: only the BOD33 register and the BOD33 bits of the interrupt
+        // registers are accessed, see the struct documentation
+        unsafe { &*SYSCTRL::ptr() }
+    }
+
+    fn wait_sync(&self) {
+        while self.sysctrl().pclksr.read().b33srdy().bit_is_clear() {}
+    }
+
+    /// Change the BOD33 configuration
+    ///
+    /// The BOD33 is disabled while it is reconfigured, and this waits until
+    /// it is ready again.
+    pub fn reconfigure(&mut self, config: Bod33Config) {
+        let sysctrl = self.sysctrl();
+        sysctrl.bod33.modify(|_, w| w.enable().clear_bit());
+        self.wait_sync();
+        sysctrl.bod33.write(|w| unsafe {
+            w.level().bits(config.level);
+            w.hyst().bit(config.hysteresis);
+            w.action().variant(config.action);
+            w.runstdby().bit(config.run_in_standby);
+            w.mode().bit(config.sampling.is_some());
+            if let Some(prescaler) = config.sampling {
+                w.psel().variant(prescaler);
+            }
+            w
+        });
+        self.wait_sync();
+        sysctrl.bod33.modify(|_, w| w.enable().set_bit());
+        self.wait_sync();
+        if config.sampling.is_some() {
+            // The sampling clock is only started once the BOD33 is enabled
+            sysctrl.bod33.modify(|_, w| w.cen().set_bit());
+            self.wait_sync();
+        }
+        while sysctrl.pclksr.read().bod33rdy().bit_is_clear() {}
+    }
+
+    /// Returns true if the supply is currently below the threshold
+    #[inline]
+    pub fn detected(&self) -> bool {
+        self.sysctrl().pclksr.read().bod33det().bit_is_set()
+    }
+
+    /// Read the BOD33 interrupt flags
+    #[inline]
+    pub fn read_flags(&self) -> Flags {
+        Flags::from_bits_truncate(self.sysctrl().intflag.read().bits())
+    }
+
+    /// Clear the BOD33 interrupt flags
+    #[inline]
+    pub fn clear_flags(&mut self, flags: Flags) {
+        self.sysctrl()
+            .intflag
+            .write(|w| unsafe { w.bits(flags.bits()) });
+    }
+
+    /// Enable the BOD33 interrupts
+    #[inline]
+    pub fn enable_interrupts(&mut self, flags: Flags) {
+        self.sysctrl()
+            .intenset
+            .write(|w| unsafe { w.bits(flags.bits()) });
+    }
+
+    /// Disable the BOD33 interrupts
+    #[inline]
+    pub fn disable_interrupts(&mut self, flags: Flags) {
+        self.sysctrl()
+            .intenclr
+            .write(|w| unsafe { w.bits(flags.bits()) });
+    }
+
+    /// Disable the BOD33 and its interrupts, and return the token
+    pub fn disable(mut self) -> Bod33Token {
+        self.disable_interrupts(Flags::all());
+        self.sysctrl().bod33.modify(|_, w| w.enable().clear_bit());
+        self.wait_sync();
+        self.token
+    }
+}
@@ -3,18 +3,100 @@
 //! before you can set up most of the peripherals on the atsamd21 device.
 //! The other types in this module are used to enforce at compile time
 //! that the peripherals have been correctly configured.
+//!
+//! Besides the DFLL48M and the internal oscillators, the controller can
+//! enable the XOSC ([`enable_xosc`]) and the FDPLL96M ([`enable_dpll`]), and
+//! take a generator input from a GCLK_IO pin ([`configure_gclk_in`]).
+//! Generators can then be sourced from them with
+//! [`configure_gclk_divider_and_source`], and output on their GCLK_IO pins
+//! with [`enable_gclk_out`]. The XOSC and FDPLL96M only run in standby sleep
+//! mode if their configuration asks for it.
+//!
+//! ```no_run
+//! # use atsamd_hal::clock::*;
+//! # use atsamd_hal::pac::Peripherals;
+//! # use atsamd_hal::time::U32Ext;
+//! # use atsamd_hal::pac::gclk::clkctrl::GEN_A::*;
+//! # use atsamd_hal::pac::gclk::genctrl::SRC_A::*;
+//! let mut peripherals = Peripherals::take().unwrap();
+//! let mut clocks = GenericClockController::with_internal_8mhz(
+//!     peripherals.GCLK,
+//!     &mut peripherals.PM,
+//!     &mut peripherals.SYSCTRL,
+//!     &mut peripherals.NVMCTRL,
+//! );
+//! let sysctrl = &mut peripherals.SYSCTRL;
+//! // 16MHz crystal, divided by 16 to a 1MHz DPLL reference, times 48
+//! clocks
+//!     .enable_xosc(sysctrl, XoscConfig::crystal(16.mhz()))
+//!     .unwrap();
+//! let dpll = DpllConfig::new(DpllRef::Xosc, 47, 0).xosc_divider(7);
+//! clocks.enable_dpll(sysctrl, dpll).unwrap();
+//! let gclk2 = clocks
+//!     .configure_gclk_divider_and_source(GCLK2, 1, DPLL96M, false)
+//!     .unwrap();
+//! ```
+//!
+//! # Changing frequencies at runtime
+//!
+//! Peripherals capture the frequency of their generator when they are
+//! created. [`GenericClockController::reconfigure_gclk`] only changes a
+//! generator once every peripheral channel it feeds is accounted for: either
+//! disconnected with
+//! [`disable_peripheral_clock`](GenericClockController::disable_peripheral_clock),
+//! or backed by a peripheral implementing [`Reclock`], which then recomputes
+//! its baud rate or prescaler.
+//!
+//! ```no_run
+//! # use atsamd_hal::clock::*;
+//! # use atsamd_hal::sercom::uart;
+//! # fn f<C: uart::ValidConfig>(clocks: &mut GenericClockController, gclk2: &mut GClock, src: ClockSource, uart: &mut uart::Uart<C, uart::Duplex>) {
+//! // Halve the frequency of GCLK2, which clocks the UART
+//! clocks
+//!     .reconfigure_gclk(gclk2, 2, src, false, &mut [uart])
+//!     .unwrap();
+//! # }
+//! ```
+//!
+//! [`enable_xosc`]: GenericClockController::enable_xosc
+//! [`enable_dpll`]: GenericClockController::enable_dpll
+//! [`configure_gclk_in`]: GenericClockController::configure_gclk_in
+//! [`configure_gclk_divider_and_source`]: GenericClockController::configure_gclk_divider_and_source
+//! [`enable_gclk_out`]: GenericClockController::enable_gclk_out
 #![allow(clippy::from_over_into)]
 
+use crate::bod33::Bod33Token;
+pub use crate::clock_params::GclkOutConfig;
+use crate::clock_params::{ClockParamsError, GclkLimits, GclkParams};
 use crate::pac::gclk::clkctrl::GEN_A::*;
 use crate::pac::gclk::clkctrl::ID_A::*;
 use crate::pac::gclk::genctrl::SRC_A::*;
 use crate::pac::{self, GCLK, NVMCTRL, PM, SYSCTRL};
 use crate::time::{Hertz, U32Ext};
+use crate::typelevel::Sealed;
+
+mod dfll;
+pub use dfll::*;
+
+mod dpll;
+pub use dpll::*;
+
+mod gclk_io;
+pub use gclk_io::*;
+
+mod xosc;
+pub use xosc::*;
 
 pub type ClockId = pac::gclk::clkctrl::ID_A;
 pub type ClockGenId = pac::gclk::clkctrl::GEN_A;
 pub type ClockSource = pac::gclk::genctrl::SRC_A;
 
+/// Number of variants of [`ClockSource`]
+const NUM_SOURCES: usize = 9;
+
+/// Number of peripheral channels tracked, one per bit of `used_clocks`
+const NUM_CHANNELS: usize = 64;
+
 /// Represents a configured clock generator.
 /// Can be converted into the effective clock frequency.
 /// Its primary purpose is to be passed in to methods
@@ -32,6 +114,74 @@ impl Into<Hertz> for GClock {
     }
 }
 
+/// Errors reported while configuring the clock sources
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ClockError {
+    /// A source, reference or output frequency is outside the range supported
+    /// by the hardware
+    FrequencyOutOfRange,
+    /// A divider or multiplier does not fit in its register field
+    InvalidDivider,
+    /// The generator or peripheral channel has already been configured
+    AlreadyConfigured,
+    /// The generator feeds a peripheral channel or generator that was not
+    /// accounted for
+    InUse,
+}
+
+/// A peripheral whose settings depend on the frequency of its peripheral
+/// channel
+///
+/// Passing a `Reclock` to
+/// [`GenericClockController::reconfigure_gclk`] proves that the peripheral
+/// will follow the new frequency of its generator.
+pub trait Reclock {
+    /// Peripheral channel clocking the peripheral
+    fn clock_id(&self) -> ClockId;
+    /// Recompute the frequency-dependent settings, e.g. the `BAUD` register
+    /// or the prescaler, for the new channel frequency
+    fn reclock(&mut self, freq: Hertz);
+    /// Peripherals of the channel driven by `self`, as a bit mask: `0b01` for
+    /// the first peripheral named by the channel, e.g. TC4 on `TC4_TC5`, and
+    /// `0b10` for the second one. Peripherals alone on their channel are
+    /// `0b01`.
+    fn channel_users(&self) -> u8 {
+        0b01
+    }
+}
+
+/// Channels clocking two peripherals, which must both be reclocked together
+#[cfg(feature = "samd11")]
+const SHARED_CHANNELS: &[ClockId] = &[ClockId::TC1_TC2];
+/// Channels clocking two peripherals, which must both be reclocked together
+#[cfg(feature = "samd21")]
+const SHARED_CHANNELS: &[ClockId] = &[
+    ClockId::TCC0_TCC1,
+    ClockId::TCC2_TC3,
+    ClockId::TC4_TC5,
+    ClockId::TC6_TC7,
+];
+
+/// Bit mask of the peripherals clocked by `channel`, see
+/// [`Reclock::channel_users`]
+fn channel_users(channel: usize) -> u8 {
+    if SHARED_CHANNELS
+        .iter()
+        .any(|clock| u8::from(*clock) as usize == channel)
+    {
+        0b11
+    } else {
+        0b01
+    }
+}
+
+/// A peripheral clock token, as returned by the peripheral clock methods of
+/// [`GenericClockController`]
+pub trait PeripheralClock: Sealed {
+    /// Peripheral channel of the token
+    const ID: ClockId;
+}
+
 struct State {
     gclk: GCLK,
 }
@@ -73,17 +223,28 @@ impl State {
             panic!("invalid divisor {} for GCLK {}", divider, gclk as u8);
         }
 
+        // divide directly by divider, rather than exponential
+        self.set_gclk_div_and_source(gclk, divider, false, src, improve_duty_cycle);
+    }
+
+    fn set_gclk_div_and_source(
+        &mut self,
+        gclk: ClockGenId,
+        div: u16,
+        divsel: bool,
+        src: ClockSource,
+        improve_duty_cycle: bool,
+    ) {
         self.gclk.gendiv.write(|w| unsafe {
             w.id().bits(u8::from(gclk));
-            w.div().bits(divider)
+            w.div().bits(div)
         });
         self.wait_for_sync();
 
         self.gclk.genctrl.write(|w| unsafe {
             w.id().bits(u8::from(gclk));
             w.src().bits(u8::from(src));
-            // divide directly by divider, rather than exponential
-            w.divsel().clear_bit();
+            w.divsel().bit(divsel);
             w.idc().bit(improve_duty_cycle);
             w.genen().set_bit();
             w.oe().set_bit()
@@ -91,6 +252,30 @@ impl State {
         self.wait_for_sync();
     }
 
+    /// Change the source and linear divider of a running generator, keeping
+    /// its other `GENCTRL` settings like `RUNSTDBY`, `OE` and `OOV`
+    fn modify_gclk_divider_and_source(
+        &mut self,
+        gclk: ClockGenId,
+        divider: u16,
+        src: ClockSource,
+        improve_duty_cycle: bool,
+    ) {
+        self.gclk.gendiv.write(|w| unsafe {
+            w.id().bits(u8::from(gclk));
+            w.div().bits(divider)
+        });
+        self.wait_for_sync();
+
+        self.select_genctrl(gclk);
+        self.gclk.genctrl.modify(|_, w| unsafe {
+            w.src().bits(u8::from(src));
+            w.divsel().clear_bit();
+            w.idc().bit(improve_duty_cycle)
+        });
+        self.wait_for_sync();
+    }
+
     fn enable_clock_generator(&mut self, clock: ClockId, generator: ClockGenId) {
         self.gclk.clkctrl.write(|w| unsafe {
             w.id().bits(u8::from(clock));
@@ -100,6 +285,14 @@ impl State {
         self.wait_for_sync();
     }
 
+    fn disable_clock_generator(&mut self, clock: ClockId) {
+        self.gclk.clkctrl.write(|w| unsafe {
+            w.id().bits(u8::from(clock));
+            w.clken().clear_bit()
+        });
+        self.wait_for_sync();
+    }
+
     fn configure_standby(&mut self, gclk: ClockGenId, enable: bool) {
         // We must first read out the configuration of genctrl to read/modify/write it.
         //   To do so, we must do an 8-bit write to GENCTRL.ID (ref 15.6.4.1 Indirect
@@ -114,6 +307,59 @@ impl State {
         self.gclk.genctrl.modify(|_, w| w.runstdby().bit(enable));
         self.wait_for_sync();
     }
+
+    /// Load the `GENCTRL` configuration of `gclk`, using the indirect access
+    /// described in `configure_standby`
+    fn select_genctrl(&mut self, gclk: ClockGenId) {
+        unsafe {
+            let genctrl_ptr_u8: *mut u8 = self.gclk.genctrl.as_ptr() as *mut u8;
+            *genctrl_ptr_u8 = u8::from(gclk);
+        }
+        self.wait_for_sync();
+    }
+
+    /// Returns the source of `gclk`, if it is enabled
+    fn gclk_source(&mut self, gclk: ClockGenId) -> Option<ClockSource> {
+        self.gclk_standby(gclk).map(|(src, _)| src)
+    }
+
+    /// Returns the source of `gclk` and its `RUNSTDBY` setting, if it is
+    /// enabled
+    fn gclk_standby(&mut self, gclk: ClockGenId) -> Option<(ClockSource, bool)> {
+        self.select_genctrl(gclk);
+        let genctrl = self.gclk.genctrl.read();
+        if genctrl.genen().bit_is_set() {
+            genctrl
+                .src()
+                .variant()
+                .map(|src| (src, genctrl.runstdby().bit()))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the output settings of `gclk`, and whether it takes its input
+    /// from GCLK_IO
+    fn gclk_output(&mut self, gclk: ClockGenId) -> (GclkOutConfig, bool) {
+        self.select_genctrl(gclk);
+        let genctrl = self.gclk.genctrl.read();
+        let config = GclkOutConfig {
+            output_enable: genctrl.oe().bit(),
+            off_value: genctrl.oov().bit(),
+            improve_duty_cycle: genctrl.idc().bit(),
+        };
+        (config, genctrl.src().variant() == Some(GCLKIN))
+    }
+
+    fn set_gclk_output(&mut self, gclk: ClockGenId, config: &GclkOutConfig) {
+        self.select_genctrl(gclk);
+        self.gclk.genctrl.modify(|_, w| {
+            w.oe().bit(config.output_enable);
+            w.oov().bit(config.off_value);
+            w.idc().bit(config.improve_duty_cycle)
+        });
+        self.wait_for_sync();
+    }
 }
 
 /// `GenericClockController` encapsulates the GCLK hardware.
@@ -126,6 +372,12 @@ pub struct GenericClockController {
     state: State,
     gclks: [Hertz; 8],
     used_clocks: u64,
+    /// Frequency of each running source, indexed by `ClockSource`
+    sources: [Hertz; NUM_SOURCES],
+    /// Generator feeding each peripheral channel
+    clock_gens: [Option<ClockGenId>; NUM_CHANNELS],
+    /// BOD33 token, until taken by the user
+    bod33_token: Option<Bod33Token>,
 }
 
 impl GenericClockController {
@@ -181,6 +433,8 @@ impl GenericClockController {
 
         // Feed 32khz into the DFLL48
         state.enable_clock_generator(DFLL48, GCLK1);
+        let mut clock_gens = [None; NUM_CHANNELS];
+        clock_gens[u8::from(DFLL48) as usize] = Some(GCLK1);
         // Enable the DFLL48
         configure_and_enable_dfll48m(sysctrl, use_external_crystal);
         // Feed DFLL48 into the main clock
@@ -197,6 +451,12 @@ impl GenericClockController {
         pm.apbbsel.write(|w| w.apbbdiv().div1());
         pm.apbcsel.write(|w| w.apbcdiv().div1());
 
+        let slow_src = if use_external_crystal {
+            XOSC32K
+        } else {
+            OSC32K
+        };
+
         Self {
             state,
             gclks: [
@@ -210,6 +470,90 @@ impl GenericClockController {
                 Hertz(0),
             ],
             used_clocks: 1u64 << u8::from(ClockId::DFLL48),
+            sources: initial_sources(Some(OSC48M_FREQ), Some(slow_src)),
+            clock_gens,
+            // Safety: GCLK is consumed, so only one controller can exist
+            bod33_token: Some(unsafe { Bod33Token::new() }),
+        }
+    }
+
+    /// Reset the clock controller, configure the system to run at 48Mhz from
+    /// the DFLL48M in USB clock recovery mode, and reset various clock
+    /// dividers.
+    ///
+    /// This is the setup for crystal-less USB devices: the DFLL runs closed
+    /// loop against the USB start of frame packets, so its output stays
+    /// within the USB specification once the host is sending them. gclk0 is
+    /// fed by the DFLL, and can clock the USB peripheral. gclk1 is fed by the
+    /// internal 32khz oscillator, but does not drive the DFLL.
+    ///
+    /// ```no_run
+    /// # use atsamd_hal::clock::*;
+    /// # use atsamd_hal::pac::Peripherals;
+    /// let mut peripherals = Peripherals::take().unwrap();
+    /// let mut clocks = GenericClockController::with_usb_clock_recovery(
+    ///     peripherals.GCLK,
+    ///     &mut peripherals.PM,
+    ///     &mut peripherals.SYSCTRL,
+    ///     &mut peripherals.NVMCTRL,
+    ///     DfllUsbRecovery::new(),
+    /// );
+    /// // Clock the USB peripheral from the DFLL, then pass the clock to
+    /// // `usb::UsbBus::new`
+    /// let gclk0 = clocks.gclk0();
+    /// let usb_clock = clocks.usb(&gclk0).unwrap();
+    /// ```
+    pub fn with_usb_clock_recovery(
+        gclk: GCLK,
+        pm: &mut PM,
+        sysctrl: &mut SYSCTRL,
+        nvmctrl: &mut NVMCTRL,
+        config: DfllUsbRecovery,
+    ) -> Self {
+        let mut state = State { gclk };
+
+        set_flash_to_half_auto_wait_state(nvmctrl);
+        #[cfg(feature = "samd21")]
+        set_flash_manual_write(nvmctrl);
+        enable_gclk_apb(pm);
+        enable_internal_32kosc(sysctrl);
+
+        state.reset_gclk();
+        state.set_gclk_divider_and_source(GCLK1, 1, OSC32K, false);
+
+        // The DFLL48 takes its reference from the USB SOF, not from a
+        // generator
+        enable_dfll48m_usb_recovery(sysctrl, &config);
+        // Feed DFLL48 into the main clock
+        state.set_gclk_divider_and_source(GCLK0, 1, DFLL48M, true);
+
+        // Reset various dividers back to 1
+        sysctrl.osc8m.modify(|_, w| {
+            w.presc()._0();
+            w.ondemand().clear_bit()
+        });
+        pm.cpusel.write(|w| w.cpudiv().div1());
+        pm.apbasel.write(|w| w.apbadiv().div1());
+        pm.apbbsel.write(|w| w.apbbdiv().div1());
+        pm.apbcsel.write(|w| w.apbcdiv().div1());
+
+        Self {
+            state,
+            gclks: [
+                OSC48M_FREQ,
+                OSC32K_FREQ,
+                Hertz(0),
+                Hertz(0),
+                Hertz(0),
+                Hertz(0),
+                Hertz(0),
+                Hertz(0),
+            ],
+            used_clocks: 0,
+            sources: initial_sources(Some(OSC48M_FREQ), Some(OSC32K)),
+            clock_gens: [None; NUM_CHANNELS],
+            // Safety: GCLK is consumed, so only one controller can exist
+            bod33_token: Some(unsafe { Bod33Token::new() }),
         }
     }
 
@@ -261,6 +605,10 @@ impl GenericClockController {
                 Hertz(0),
             ],
             used_clocks: 0,
+            sources: initial_sources(None, None),
+            clock_gens: [None; NUM_CHANNELS],
+            // Safety: GCLK is consumed, so only one controller can exist
+            bod33_token: Some(unsafe { Bod33Token::new() }),
         }
     }
 
@@ -303,7 +651,12 @@ impl GenericClockController {
     /// `improve_duty_cycle` is a boolean that, when set to true, enables
     /// a 5o/50 duty cycle for odd divider values.
     /// Returns a `GClock` for the configured clock generator.
-    /// Returns `None` if the clock generator has already been configured.
+    /// Returns `None` if the clock generator has already been configured,
+    /// or if `src` is not running. `XOSC` is only known to be running once it
+    /// has been enabled with [`enable_xosc`](Self::enable_xosc), and `GCLKIN`
+    /// requires [`configure_gclk_in`](Self::configure_gclk_in). `DPLL96M` is
+    /// assumed to run at 96MHz unless it was configured with
+    /// [`enable_dpll`](Self::enable_dpll).
     pub fn configure_gclk_divider_and_source(
         &mut self,
         gclk: ClockGenId,
@@ -315,24 +668,340 @@ impl GenericClockController {
         if self.gclks[idx].0 != 0 {
             return None;
         }
+        let freq = self.source_freq(src);
+        if freq.0 == 0 {
+            return None;
+        }
         self.state
             .set_gclk_divider_and_source(gclk, divider, src, improve_duty_cycle);
-        let freq: Hertz = match src {
-            XOSC32K | OSC32K | OSCULP32K => OSC32K_FREQ,
-            GCLKGEN1 => self.gclks[1],
-            OSC8M => OSC8M_FREQ,
-            DFLL48M => OSC48M_FREQ,
-            DPLL96M => 96.mhz().into(),
-            GCLKIN | XOSC => unimplemented!(),
-        };
         self.gclks[idx] = Hertz(freq.0 / divider as u32);
         Some(GClock { gclk, freq })
     }
 
+    /// Computes the divider settings that bring `src` closest to `target`,
+    /// within the limits of the generator `gclk`.
+    ///
+    /// Returns [`ClockParamsError::ZeroFrequency`] if `src` is not running.
+    /// The result can be passed to
+    /// [`configure_gclk_params`](Self::configure_gclk_params).
+    pub fn gclk_params(
+        &self,
+        gclk: ClockGenId,
+        src: ClockSource,
+        target: impl Into<Hertz>,
+    ) -> Result<GclkParams, ClockParamsError> {
+        GclkParams::new(self.source_freq(src), target, &gclk_limits(gclk))
+    }
+
+    /// Configures a clock generator with divider settings computed by the
+    /// [`GclkParams`] solver, including power of two dividers.
+    /// Returns `None` if the clock generator has already been configured, if
+    /// `src` is not running or if the settings do not fit the generator.
+    pub fn configure_gclk_params(
+        &mut self,
+        gclk: ClockGenId,
+        params: &GclkParams,
+        src: ClockSource,
+        improve_duty_cycle: bool,
+    ) -> Option<GClock> {
+        let idx = u8::from(gclk) as usize;
+        let limits = gclk_limits(gclk);
+        let div_valid = if params.divsel {
+            params.div <= limits.div_bits as u16
+        } else {
+            (params.div as u32) < 1 << limits.div_bits
+        };
+        if self.gclks[idx].0 != 0 || !div_valid {
+            return None;
+        }
+        let src_freq = self.source_freq(src);
+        if src_freq.0 == 0 {
+            return None;
+        }
+        self.state.set_gclk_div_and_source(
+            gclk,
+            params.div,
+            params.divsel,
+            src,
+            improve_duty_cycle,
+        );
+        let freq = params.output_freq(src_freq);
+        self.gclks[idx] = freq;
+        Some(GClock { gclk, freq })
+    }
+
+    /// Take the token needed to create a [`Bod33`](crate::bod33::Bod33).
+    ///
+    /// Returns `None` if it has already been taken.
+    pub fn take_bod33_token(&mut self) -> Option<Bod33Token> {
+        self.bod33_token.take()
+    }
+
     /// Enables or disables the given GClk from operation in standby.
     pub fn configure_standby(&mut self, gclk: ClockGenId, enable: bool) {
         self.state.configure_standby(gclk, enable)
     }
+
+    /// Enables or disables the given source from operation in standby.
+    ///
+    /// The OSCULP32K always runs, and GCLK_IO inputs are external, so they
+    /// are left untouched.
+    pub fn configure_source_standby(
+        &mut self,
+        sysctrl: &mut SYSCTRL,
+        src: ClockSource,
+        enable: bool,
+    ) {
+        match src {
+            OSC32K => sysctrl.osc32k.modify(|_, w| w.runstdby().bit(enable)),
+            XOSC32K => sysctrl.xosc32k.modify(|_, w| w.runstdby().bit(enable)),
+            OSC8M => sysctrl.osc8m.modify(|_, w| w.runstdby().bit(enable)),
+            XOSC => sysctrl.xosc.modify(|_, w| w.runstdby().bit(enable)),
+            DFLL48M => {
+                wait_for_dfllrdy(sysctrl);
+                sysctrl.dfllctrl.modify(|_, w| w.runstdby().bit(enable));
+                wait_for_dfllrdy(sysctrl);
+            }
+            DPLL96M => sysctrl.dpllctrla.modify(|_, w| w.runstdby().bit(enable)),
+            _ => (),
+        }
+    }
+
+    /// Returns true if the peripheral channel `clock` keeps its clock in
+    /// standby: it must be fed by a generator with `RUNSTDBY` set, whose
+    /// source also runs in standby.
+    pub fn runs_in_standby(&mut self, clock: ClockId) -> bool {
+        match self.clock_gens[u8::from(clock) as usize] {
+            Some(gclk) => self.gclk_runs_in_standby(gclk),
+            None => false,
+        }
+    }
+
+    fn gclk_runs_in_standby(&mut self, gclk: ClockGenId) -> bool {
+        match self.state.gclk_standby(gclk) {
+            Some((GCLKGEN1, true)) => gclk != GCLK1 && self.gclk_runs_in_standby(GCLK1),
+            Some((src, true)) => source_runs_in_standby(src),
+            _ => false,
+        }
+    }
+
+    /// Disconnect a peripheral channel from its generator.
+    ///
+    /// Giving back the token proves that the peripherals of the channel are
+    /// no longer clocked, so that the generator can be reconfigured with
+    /// [`reconfigure_gclk`](Self::reconfigure_gclk).
+    pub fn disable_peripheral_clock<T: PeripheralClock>(&mut self, clock: T) {
+        let _ = clock;
+        let idx = u8::from(T::ID) as usize;
+        self.used_clocks &= !(1u64 << idx);
+        self.clock_gens[idx] = None;
+        self.state.disable_clock_generator(T::ID);
+    }
+
+    /// Change the divider and source of a running generator at runtime.
+    ///
+    /// Every peripheral channel fed by the generator must either have been
+    /// disconnected with
+    /// [`disable_peripheral_clock`](Self::disable_peripheral_clock), or be
+    /// listed in `peripherals`. Once the generator runs at its new
+    /// frequency, `generator` and every peripheral are updated. Peripherals
+    /// sharing a channel, like TC4 and TC5, must all be listed.
+    ///
+    /// Changing GCLK0 changes the CPU frequency; a
+    /// [`Delay`](crate::delay::Delay) must be recreated afterwards.
+    ///
+    /// Returns [`ClockError::InUse`] if a channel, or one of the peripherals
+    /// sharing it, is not accounted for, if one of `peripherals` is not fed
+    /// by the generator, if the generator is GCLK1 and is the source of
+    /// another generator, or if it takes its input from GCLK_IO, so that the
+    /// frequency of its [`GclkIn`] stays up to date.
+    pub fn reconfigure_gclk(
+        &mut self,
+        generator: &mut GClock,
+        divider: u16,
+        src: ClockSource,
+        improve_duty_cycle: bool,
+        peripherals: &mut [&mut dyn Reclock],
+    ) -> Result<Hertz, ClockError> {
+        let gclk = generator.gclk;
+        let limits = gclk_limits(gclk);
+        if divider == 0 || divider as u32 >= 1 << limits.div_bits {
+            return Err(ClockError::InvalidDivider);
+        }
+        if gclk == GCLK1 && src == GCLKGEN1 {
+            return Err(ClockError::InvalidDivider);
+        }
+        let freq = self.source_freq(src).0 / divider as u32;
+        if freq == 0 || freq > limits.max_freq.0 {
+            return Err(ClockError::FrequencyOutOfRange);
+        }
+
+        if self.state.gclk_source(gclk) == Some(GCLKIN) {
+            return Err(ClockError::InUse);
+        }
+        if gclk == GCLK1 {
+            let others = [
+                GCLK0,
+                GCLK2,
+                GCLK3,
+                GCLK4,
+                GCLK5,
+                #[cfg(feature = "samd21")]
+                GCLK6,
+                #[cfg(feature = "samd21")]
+                GCLK7,
+            ];
+            for other in others {
+                if self.gclks[u8::from(other) as usize].0 != 0
+                    && self.state.gclk_source(other) == Some(GCLKGEN1)
+                {
+                    return Err(ClockError::InUse);
+                }
+            }
+        }
+        let fed = |clock: ClockId| self.clock_gens[u8::from(clock) as usize] == Some(gclk);
+        if !peripherals.iter().all(|p| fed(p.clock_id())) {
+            return Err(ClockError::InUse);
+        }
+        let accounted = |channel: usize| {
+            let users = peripherals
+                .iter()
+                .filter(|p| u8::from(p.clock_id()) as usize == channel)
+                .fold(0, |users, p| users | p.channel_users());
+            users == channel_users(channel)
+        };
+        let unaccounted = self
+            .clock_gens
+            .iter()
+            .enumerate()
+            .any(|(channel, gen)| *gen == Some(gclk) && !accounted(channel));
+        if unaccounted {
+            return Err(ClockError::InUse);
+        }
+
+        self.state
+            .modify_gclk_divider_and_source(gclk, divider, src, improve_duty_cycle);
+        let freq = Hertz(freq);
+        self.gclks[u8::from(gclk) as usize] = freq;
+        generator.freq = freq;
+        for peripheral in peripherals.iter_mut() {
+            peripheral.reclock(freq);
+        }
+        Ok(freq)
+    }
+
+    /// Returns the frequency of a running source, or zero
+    fn source_freq(&self, src: ClockSource) -> Hertz {
+        match src {
+            GCLKGEN1 => self.gclks[1],
+            // The input frequency depends on the generator
+            GCLKIN => Hertz(0),
+            _ => self.sources[u8::from(src) as usize],
+        }
+    }
+
+    /// Enable the XOSC and wait for it to become ready.
+    ///
+    /// Returns an error if the frequency is out of range for the selected
+    /// mode.
+    pub fn enable_xosc(
+        &mut self,
+        sysctrl: &mut SYSCTRL,
+        config: XoscConfig,
+    ) -> Result<(), ClockError> {
+        enable_xosc(sysctrl, &config)?;
+        self.sources[u8::from(XOSC) as usize] = config.freq();
+        Ok(())
+    }
+
+    /// Enable the FDPLL96M from the configured reference and wait for it to
+    /// lock.
+    ///
+    /// The reference must already be running: the XOSC must have been enabled
+    /// with [`enable_xosc`](Self::enable_xosc), the XOSC32K with
+    /// [`with_external_32kosc`](Self::with_external_32kosc), and a generator
+    /// must have been configured. A generator reference is connected to the
+    /// `FDPLL` peripheral channel. Returns the output frequency, or an error
+    /// if a frequency or the ratio is out of range, or if the `FDPLL` channel
+    /// has already been configured.
+    ///
+    /// The DPLL must not be feeding any generator while it is reconfigured.
+    pub fn enable_dpll(
+        &mut self,
+        sysctrl: &mut SYSCTRL,
+        config: DpllConfig,
+    ) -> Result<Hertz, ClockError> {
+        let ref_freq = match config.reference() {
+            DpllRef::Xosc32k => self.sources[u8::from(XOSC32K) as usize],
+            DpllRef::Xosc => self.sources[u8::from(XOSC) as usize],
+            DpllRef::Gclk(gclk) => self.gclks[u8::from(gclk) as usize],
+        };
+        let freq = config.validate(ref_freq)?;
+        if let DpllRef::Gclk(gclk) = config.reference() {
+            let bits: u64 = 1 << u8::from(ClockId::FDPLL) as u64;
+            if (self.used_clocks & bits) != 0 {
+                return Err(ClockError::AlreadyConfigured);
+            }
+            self.used_clocks |= bits;
+            self.clock_gens[u8::from(ClockId::FDPLL) as usize] = Some(gclk);
+            self.state.enable_clock_generator(ClockId::FDPLL, gclk);
+        }
+        enable_dpll(sysctrl, &config);
+        self.sources[u8::from(DPLL96M) as usize] = freq;
+        Ok(freq)
+    }
+
+    /// Configure the generator connected to a GCLK_IO pin to run from the
+    /// clock signal applied to that pin.
+    ///
+    /// The pin is put in the GCLK_IO peripheral function and kept in the
+    /// returned [`GclkIn`]. `freq` is the frequency of the external signal.
+    /// Returns the pin if the generator has already been configured, or if
+    /// the signal is faster than the 48MHz a generator can accept.
+    pub fn configure_gclk_in<I: GclkIo>(
+        &mut self,
+        pin: impl crate::gpio::AnyPin<Id = I>,
+        freq: impl Into<Hertz>,
+        divider: u16,
+        improve_duty_cycle: bool,
+    ) -> Result<GclkIn<I>, GclkIoPin<I>> {
+        let pin: GclkIoPin<I> = pin.into().into_alternate();
+        let freq = freq.into();
+        let idx = u8::from(I::GCLK) as usize;
+        if self.gclks[idx].0 != 0 || freq.0 == 0 || freq.0 > OSC48M_FREQ.0 {
+            return Err(pin);
+        }
+        self.state
+            .set_gclk_divider_and_source(I::GCLK, divider, GCLKIN, improve_duty_cycle);
+        let freq = Hertz(freq.0 / divider.max(1) as u32);
+        self.gclks[idx] = freq;
+        Ok(GclkIn::new(freq, pin))
+    }
+
+    /// Output a generator on one of its GCLK_IO pins.
+    ///
+    /// The pin is put in the GCLK_IO peripheral function and kept, along with
+    /// `generator`, in the returned [`GclkOut`]. Returns the pin if it is not
+    /// connected to the generator, or if the generator takes its input from
+    /// GCLK_IO.
+    pub fn enable_gclk_out<I: GclkIo>(
+        &mut self,
+        generator: GClock,
+        pin: impl crate::gpio::AnyPin<Id = I>,
+        config: GclkOutConfig,
+    ) -> Result<GclkOut<I>, GclkIoPin<I>> {
+        let pin: GclkIoPin<I> = pin.into().into_alternate();
+        if generator.gclk != I::GCLK {
+            return Err(pin);
+        }
+        let (previous, gclk_in) = self.state.gclk_output(I::GCLK);
+        if gclk_in {
+            return Err(pin);
+        }
+        self.state.set_gclk_output(I::GCLK, &config);
+        let freq = self.gclks[u8::from(I::GCLK) as usize];
+        Ok(GclkOut::new(generator, freq, pin, config, previous))
+    }
 }
 
 macro_rules! clock_generator {
@@ -362,6 +1031,12 @@ impl Into<Hertz> for $Type {
         self.freq
     }
 }
+
+impl Sealed for $Type {}
+
+impl PeripheralClock for $Type {
+    const ID: ClockId = ClockId::$clock;
+}
 )+
 
 impl GenericClockController {
@@ -384,6 +1059,7 @@ impl GenericClockController {
             return None;
         }
         self.used_clocks |= bits;
+        self.clock_gens[u8::from(ClockId::$clock) as usize] = Some(generator.gclk);
 
         self.state.enable_clock_generator(ClockId::$clock, generator.gclk);
         let freq = self.gclks[u8::from(generator.gclk) as usize];
@@ -460,6 +1136,42 @@ pub const OSC8M_FREQ: Hertz = Hertz(8_000_000);
 /// The frequency of the 32Khz source.
 pub const OSC32K_FREQ: Hertz = Hertz(32_768);
 
+/// The maximum output frequency of GCLK0, which drives the CPU.
+pub const GCLK0_MAX_FREQ: Hertz = Hertz(48_000_000);
+/// The maximum output frequency of the other generators.
+pub const GCLK_MAX_FREQ: Hertz = Hertz(96_000_000);
+
+/// Returns the divider and frequency limits of a generator, for use with the
+/// [`GclkParams`] solver.
+pub fn gclk_limits(gclk: ClockGenId) -> GclkLimits {
+    let div_bits = match gclk {
+        GCLK1 => 16,
+        GCLK2 => 5,
+        _ => 8,
+    };
+    let max_freq = if gclk == GCLK0 {
+        GCLK0_MAX_FREQ
+    } else {
+        GCLK_MAX_FREQ
+    };
+    GclkLimits { div_bits, max_freq }
+}
+
+/// Frequencies of the sources running after construction of the controller
+fn initial_sources(dfll: Option<Hertz>, osc32k: Option<ClockSource>) -> [Hertz; NUM_SOURCES] {
+    let mut sources = [Hertz(0); NUM_SOURCES];
+    sources[u8::from(OSCULP32K) as usize] = OSC32K_FREQ;
+    sources[u8::from(OSC8M) as usize] = OSC8M_FREQ;
+    sources[u8::from(DPLL96M) as usize] = 96.mhz().into();
+    if let Some(freq) = dfll {
+        sources[u8::from(DFLL48M) as usize] = freq;
+    }
+    if let Some(src) = osc32k {
+        sources[u8::from(src) as usize] = OSC32K_FREQ;
+    }
+    sources
+}
+
 fn set_flash_to_half_auto_wait_state(nvmctrl: &mut NVMCTRL) {
     nvmctrl.ctrlb.modify(|_, w| w.rws().half());
 }
@@ -513,6 +1225,22 @@ pub fn enable_external_32kosc(sysctrl: &mut SYSCTRL) {
     }
 }
 
+/// Returns true if the source keeps running in standby
+fn source_runs_in_standby(src: ClockSource) -> bool {
+    // Safety: read-only access to the oscillator configuration registers
+    let sysctrl = unsafe { &*SYSCTRL::ptr() };
+    match src {
+        OSC32K => sysctrl.osc32k.read().runstdby().bit(),
+        XOSC32K => sysctrl.xosc32k.read().runstdby().bit(),
+        OSC8M => sysctrl.osc8m.read().runstdby().bit(),
+        XOSC => sysctrl.xosc.read().runstdby().bit(),
+        DFLL48M => sysctrl.dfllctrl.read().runstdby().bit(),
+        DPLL96M => sysctrl.dpllctrla.read().runstdby().bit(),
+        // The OSCULP32K always runs, GCLK_IO inputs are external
+        _ => true,
+    }
+}
+
 fn wait_for_dfllrdy(sysctrl: &mut SYSCTRL) {
     while sysctrl.pclksr.read().dfllrdy().bit_is_clear() {}
 }
@@ -575,10 +1303,9 @@ fn configure_and_enable_dfll48m(sysctrl: &mut SYSCTRL, use_external_crystal: boo
             // chill cycle disable
             w.ccdis().set_bit();
 
-            // usb correction is not set due to instability issues around
-            // USB bus resets. TODO(twitchyliquid64): Maybe switch to OSC8M?
-            //
-            // TODO usb correction (still active for samd11??)
+            // usb correction is not set here, the DFLL locks on GCLK1. Use
+            // `with_usb_clock_recovery` to lock it on the USB SOF instead.
+            // It is kept on the SAMD11 for compatibility.
             #[cfg(feature = "samd11")]
             w.usbcrm().set_bit();
 
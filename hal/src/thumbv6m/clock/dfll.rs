@@ -0,0 +1,103 @@
+//! USB clock recovery mode of the DFLL48M
+//!
+//! Without a 32 kHz crystal, the DFLL48M can run closed loop against the 1 kHz
+//! start of frame (SOF) packets sent by the USB host, which is accurate enough
+//! for a full speed USB device. Until the host starts sending SOFs, the DFLL
+//! keeps running from its factory calibration.
+
+use crate::pac::SYSCTRL;
+
+/// Multiplication factor between the 1 kHz USB SOF and the 48 MHz output
+const USB_SOF_MUL: u16 = 48_000;
+
+/// Configuration of the DFLL48M USB clock recovery mode
+///
+/// The step sizes bound how far the DFLL tuning can move on each SOF. Small
+/// steps lower the jitter once locked, at the cost of a slower lock.
+#[derive(Clone, Copy, Debug)]
+pub struct DfllUsbRecovery {
+    coarse_step: u8,
+    fine_step: u16,
+}
+
+impl DfllUsbRecovery {
+    /// Default configuration, with a coarse step of 1 and a fine step of 10
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            coarse_step: 1,
+            fine_step: 10,
+        }
+    }
+
+    /// Set the maximum step of the `COARSE` tuning value
+    ///
+    /// Only the low six bits are used. The coarse value is taken from the
+    /// factory calibration, so this step is only used if the DFLL drifts by
+    /// more than the fine range.
+    #[inline]
+    pub fn coarse_step(mut self, step: u8) -> Self {
+        self.coarse_step = step & 0x3F;
+        self
+    }
+
+    /// Set the maximum step of the `FINE` tuning value
+    ///
+    /// Only the low ten bits are used.
+    #[inline]
+    pub fn fine_step(mut self, step: u16) -> Self {
+        self.fine_step = step & 0x3FF;
+        self
+    }
+}
+
+impl Default for DfllUsbRecovery {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn wait_for_dfllrdy(sysctrl: &mut SYSCTRL) {
+    while sysctrl.pclksr.read().dfllrdy().bit_is_clear() {}
+}
+
+/// Enable the DFLL48M in USB clock recovery mode
+///
+/// The DFLL must not be feeding any generator. The lock flags are never set
+/// before the host sends SOFs, so this does not wait for lock.
+pub(super) fn enable_dfll48m_usb_recovery(sysctrl: &mut SYSCTRL, config: &DfllUsbRecovery) {
+    // Turn it off while we configure it, see `configure_and_enable_dfll48m`
+    sysctrl.dfllctrl.write(|w| w.ondemand().clear_bit());
+    wait_for_dfllrdy(sysctrl);
+
+    // Start from the factory calibration
+    let coarse = crate::calibration::dfll48m_coarse_cal();
+    sysctrl.dfllval.write(|w| unsafe {
+        w.coarse().bits(coarse);
+        w.fine().bits(0x1ff)
+    });
+    wait_for_dfllrdy(sysctrl);
+
+    sysctrl.dfllmul.write(|w| unsafe {
+        w.cstep().bits(config.coarse_step);
+        w.fstep().bits(config.fine_step);
+        w.mul().bits(USB_SOF_MUL)
+    });
+    wait_for_dfllrdy(sysctrl);
+
+    sysctrl.dfllctrl.write(|w| {
+        w.ondemand().clear_bit();
+        // USB clock recovery runs in closed loop mode
+        w.mode().set_bit();
+        w.usbcrm().set_bit();
+        // chill cycle disable, the SOF period is long enough
+        w.ccdis().set_bit();
+        // bypass coarse lock (have calibration data)
+        w.bplckc().set_bit()
+    });
+    wait_for_dfllrdy(sysctrl);
+
+    sysctrl.dfllctrl.modify(|_, w| w.enable().set_bit());
+    wait_for_dfllrdy(sysctrl);
+}
@@ -0,0 +1,191 @@
+//! Fractional digital phase locked loop (FDPLL96M)
+//!
+//! The FDPLL96M multiplies a reference clock by `LDR + 1 + LDRFRAC / 16`. The
+//! reference can be the XOSC32K, the XOSC (optionally divided by
+//! `2 * (DIV + 1)`), or a GCLK generator through the `FDPLL` peripheral
+//! channel.
+//!
+//! The reference frequency must lie between 32 kHz and 2 MHz, and the output
+//! frequency between 48 MHz and 96 MHz.
+
+use core::ops::RangeInclusive;
+
+use crate::clock_params::{DpllLimits, DpllParams};
+use crate::pac::SYSCTRL;
+use crate::time::Hertz;
+
+use super::{ClockError, ClockGenId};
+
+/// Allowed range of the FDPLL96M reference frequency
+pub const DPLL_REF_FREQ_RANGE: RangeInclusive<u32> = 32_000..=2_000_000;
+
+/// Allowed range of the FDPLL96M output frequency
+pub const DPLL_OUT_FREQ_RANGE: RangeInclusive<u32> = 48_000_000..=96_000_000;
+
+/// Limits of the FDPLL96M, for use with the [`DpllParams`] solver
+pub const DPLL_LIMITS: DpllLimits = DpllLimits {
+    max_ldr: 0xFFF,
+    ldrfrac_steps: 16,
+    max_xosc_div: 0x7FF,
+    ref_freq: DPLL_REF_FREQ_RANGE,
+    out_freq: DPLL_OUT_FREQ_RANGE,
+};
+
+/// Reference clock selection of the FDPLL96M
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DpllRef {
+    /// The 32.768 kHz external crystal oscillator
+    Xosc32k,
+    /// The XOSC, after the DPLL XOSC divider
+    Xosc,
+    /// The `FDPLL` peripheral channel, fed by the given GCLK generator
+    Gclk(ClockGenId),
+}
+
+/// Configuration of the FDPLL96M
+///
+/// The output frequency is `f_ref * (LDR + 1 + LDRFRAC / 16)`, where `f_ref`
+/// is the reference frequency after the optional XOSC divider.
+#[derive(Clone, Copy, Debug)]
+pub struct DpllConfig {
+    reference: DpllRef,
+    ldr: u16,
+    ldrfrac: u8,
+    xosc_div: u16,
+    on_demand: bool,
+    run_standby: bool,
+}
+
+impl DpllConfig {
+    /// Create a configuration with the given reference and loop divider ratio
+    ///
+    /// `ldr` is the integer part (12 bits) and `ldrfrac` the fractional part
+    /// in 1/16 steps (4 bits).
+    #[inline]
+    pub fn new(reference: DpllRef, ldr: u16, ldrfrac: u8) -> Self {
+        Self {
+            reference,
+            ldr,
+            ldrfrac,
+            xosc_div: 0,
+            on_demand: false,
+            run_standby: false,
+        }
+    }
+
+    /// Create a configuration from the settings computed by the
+    /// [`DpllParams`] solver
+    ///
+    /// ```no_run
+    /// # use atsamd_hal::clock::*;
+    /// # use atsamd_hal::clock_params::DpllParams;
+    /// # use atsamd_hal::time::U32Ext;
+    /// let params = DpllParams::new_xosc(16.mhz(), 48.mhz(), &DPLL_LIMITS).unwrap();
+    /// let config = DpllConfig::from_params(DpllRef::Xosc, &params);
+    /// ```
+    #[inline]
+    pub fn from_params(reference: DpllRef, params: &DpllParams) -> Self {
+        Self::new(reference, params.ldr, params.ldrfrac).xosc_divider(params.xosc_div)
+    }
+
+    /// Divide an XOSC reference by `2 * (div + 1)`
+    ///
+    /// This setting is ignored for XOSC32K and GCLK references.
+    #[inline]
+    pub fn xosc_divider(mut self, div: u16) -> Self {
+        self.xosc_div = div;
+        self
+    }
+
+    /// Only run the DPLL when a peripheral requests it
+    #[inline]
+    pub fn on_demand(mut self, on_demand: bool) -> Self {
+        self.on_demand = on_demand;
+        self
+    }
+
+    /// Keep the DPLL running in standby sleep mode
+    ///
+    /// It is disabled by default.
+    #[inline]
+    pub fn run_standby(mut self, run_standby: bool) -> Self {
+        self.run_standby = run_standby;
+        self
+    }
+
+    /// Return the reference clock selection
+    #[inline]
+    pub fn reference(&self) -> DpllRef {
+        self.reference
+    }
+
+    /// Compute the output frequency for a given loop reference frequency
+    fn out_freq(&self, ref_freq: Hertz) -> Hertz {
+        let f = ref_freq.0 as u64;
+        let out = f * (self.ldr as u64 + 1) + f * self.ldrfrac as u64 / 16;
+        Hertz(out as u32)
+    }
+
+    /// Check the register ranges and frequency limits, given the frequency of
+    /// the reference clock, and return the output frequency
+    pub(super) fn validate(&self, freq: Hertz) -> Result<Hertz, ClockError> {
+        if self.ldr > 0xFFF || self.ldrfrac > 15 || self.xosc_div > 0x7FF {
+            return Err(ClockError::InvalidDivider);
+        }
+        let ref_freq = match self.reference {
+            DpllRef::Xosc => Hertz(freq.0 / (2 * (self.xosc_div as u32 + 1))),
+            DpllRef::Xosc32k | DpllRef::Gclk(_) => freq,
+        };
+        if !DPLL_REF_FREQ_RANGE.contains(&ref_freq.0) {
+            return Err(ClockError::FrequencyOutOfRange);
+        }
+        let out = self.out_freq(ref_freq);
+        if !DPLL_OUT_FREQ_RANGE.contains(&out.0) {
+            return Err(ClockError::FrequencyOutOfRange);
+        }
+        Ok(out)
+    }
+}
+
+/// Enable the FDPLL96M and wait for it to lock
+///
+/// The configuration must have been validated beforehand. In on-demand mode
+/// the DPLL only runs once a generator requests it, so the lock is not
+/// awaited.
+pub(super) fn enable_dpll(sysctrl: &mut SYSCTRL, config: &DpllConfig) {
+    // The ratio and reference can only be changed while the DPLL is disabled
+    disable_dpll(sysctrl);
+    sysctrl.dpllratio.write(|w| unsafe {
+        w.ldr().bits(config.ldr);
+        w.ldrfrac().bits(config.ldrfrac)
+    });
+    sysctrl.dpllctrlb.write(|w| {
+        match config.reference {
+            DpllRef::Xosc32k => w.refclk().ref0(),
+            DpllRef::Xosc => w.refclk().ref1(),
+            DpllRef::Gclk(_) => w.refclk().gclk(),
+        };
+        unsafe { w.div().bits(config.xosc_div) }
+    });
+    sysctrl.dpllctrla.write(|w| {
+        w.ondemand().bit(config.on_demand);
+        w.runstdby().bit(config.run_standby);
+        w.enable().set_bit()
+    });
+    while sysctrl.dpllstatus.read().enable().bit_is_clear() {}
+    if config.on_demand {
+        return;
+    }
+    while {
+        let status = sysctrl.dpllstatus.read();
+        status.lock().bit_is_clear() || status.clkrdy().bit_is_clear()
+    } {
+        // Wait for the DPLL to lock
+    }
+}
+
+/// Disable the FDPLL96M
+pub(super) fn disable_dpll(sysctrl: &mut SYSCTRL) {
+    sysctrl.dpllctrla.modify(|_, w| w.enable().clear_bit());
+    while sysctrl.dpllstatus.read().enable().bit_is_set() {}
+}
@@ -0,0 +1,185 @@
+//! Generic clock generator inputs and outputs on GCLK_IO pins
+//!
+//! Each generator can take its input from, or be output on, the GCLK_IO pins
+//! it is multiplexed to, in peripheral function H.
+
+use crate::gpio::*;
+use crate::time::Hertz;
+
+use super::{ClockGenId, GClock, GclkOutConfig, GenericClockController};
+
+/// Maps a [`PinId`] to the generator whose GCLK_IO signal it carries
+pub trait GclkIo: PinId {
+    /// Generator connected to this pin
+    const GCLK: ClockGenId;
+}
+
+/// GCLK_IO pin type, in the correct peripheral function
+pub type GclkIoPin<I> = Pin<I, AlternateH>;
+
+/// A generator running from the clock signal applied to its GCLK_IO pin
+///
+/// Create it with
+/// [`GenericClockController::configure_gclk_in`](super::GenericClockController::configure_gclk_in).
+/// The pin stays in the GCLK_IO peripheral function for as long as this
+/// struct exists.
+pub struct GclkIn<I: GclkIo> {
+    clock: GClock,
+    pin: GclkIoPin<I>,
+}
+
+impl<I: GclkIo> GclkIn<I> {
+    #[inline]
+    pub(super) fn new(freq: Hertz, pin: GclkIoPin<I>) -> Self {
+        let clock = GClock {
+            gclk: I::GCLK,
+            freq,
+        };
+        Self { clock, pin }
+    }
+
+    /// Returns the `GClock` of the generator, to be passed to the peripheral
+    /// clock methods of
+    /// [`GenericClockController`](super::GenericClockController)
+    #[inline]
+    pub fn gclk(&self) -> GClock {
+        self.clock
+    }
+
+    /// Returns the output frequency of the generator
+    #[inline]
+    pub fn freq(&self) -> Hertz {
+        self.clock.freq
+    }
+
+    /// Returns a reference to the GCLK_IO pin
+    #[inline]
+    pub fn pin(&self) -> &GclkIoPin<I> {
+        &self.pin
+    }
+}
+
+/// A generator output on one of its GCLK_IO pins
+///
+/// Create it with
+/// [`GenericClockController::enable_gclk_out`](super::GenericClockController::enable_gclk_out).
+pub struct GclkOut<I: GclkIo> {
+    clock: GClock,
+    freq: Hertz,
+    pin: GclkIoPin<I>,
+    config: GclkOutConfig,
+    previous: GclkOutConfig,
+}
+
+impl<I: GclkIo> GclkOut<I> {
+    #[inline]
+    pub(super) fn new(
+        clock: GClock,
+        freq: Hertz,
+        pin: GclkIoPin<I>,
+        config: GclkOutConfig,
+        previous: GclkOutConfig,
+    ) -> Self {
+        Self {
+            clock,
+            freq,
+            pin,
+            config,
+            previous,
+        }
+    }
+
+    /// Returns the frequency of the output clock, i.e. the frequency of the
+    /// generator source divided by the generator divider
+    #[inline]
+    pub fn freq(&self) -> Hertz {
+        self.freq
+    }
+
+    /// Enable or disable the clock output; while disabled, the pin is driven
+    /// to the off value
+    #[inline]
+    pub fn set_output_enable(&mut self, clocks: &mut GenericClockController, enable: bool) {
+        self.config.output_enable = enable;
+        clocks.state.set_gclk_output(I::GCLK, &self.config);
+    }
+
+    /// Set the level of the pin while the generator or its output is off
+    #[inline]
+    pub fn set_off_value(&mut self, clocks: &mut GenericClockController, high: bool) {
+        self.config.off_value = high;
+        clocks.state.set_gclk_output(I::GCLK, &self.config);
+    }
+
+    /// Restore the previous output settings of the generator, and return the
+    /// generator and the pin
+    #[inline]
+    pub fn free(self, clocks: &mut GenericClockController) -> (GClock, GclkIoPin<I>) {
+        clocks.state.set_gclk_output(I::GCLK, &self.previous);
+        (self.clock, self.pin)
+    }
+}
+
+macro_rules! gclk_io {
+    ( $( $( #[$attr:meta] )? ($PinId:ident, $GCLK:ident), )+ ) => {
+        $(
+            $( #[$attr] )?
+            impl GclkIo for $PinId {
+                const GCLK: ClockGenId = ClockGenId::$GCLK;
+            }
+        )+
+    };
+}
+
+gclk_io!(
+    #[cfg(feature = "samd21")]
+    (PA10, GCLK4),
+    #[cfg(feature = "samd21")]
+    (PA11, GCLK5),
+    #[cfg(feature = "samd11")]
+    (PA14, GCLK4),
+    #[cfg(feature = "samd21")]
+    (PA14, GCLK0),
+    (PA15, GCLK1),
+    #[cfg(not(feature = "samd11c"))]
+    (PA16, GCLK2),
+    #[cfg(feature = "samd21")]
+    (PA17, GCLK3),
+    #[cfg(feature = "min-samd21g")]
+    (PA20, GCLK4),
+    #[cfg(feature = "min-samd21g")]
+    (PA21, GCLK5),
+    #[cfg(feature = "samd11d")]
+    (PA22, GCLK4),
+    #[cfg(feature = "samd21")]
+    (PA22, GCLK6),
+    #[cfg(feature = "samd11d")]
+    (PA23, GCLK5),
+    #[cfg(feature = "samd21")]
+    (PA23, GCLK7),
+    #[cfg(feature = "samd21")]
+    (PA27, GCLK0),
+    #[cfg(not(feature = "samd21el"))]
+    (PA28, GCLK0),
+    (PA30, GCLK0),
+    #[cfg(feature = "min-samd21g")]
+    (PB10, GCLK4),
+    #[cfg(feature = "min-samd21g")]
+    (PB11, GCLK5),
+    #[cfg(feature = "min-samd21j")]
+    (PB12, GCLK6),
+    #[cfg(feature = "min-samd21j")]
+    (PB13, GCLK7),
+    #[cfg(feature = "min-samd21j")]
+    (PB14, GCLK0),
+    #[cfg(feature = "min-samd21j")]
+    (PB15, GCLK1),
+    #[cfg(feature = "min-samd21j")]
+    (PB16, GCLK2),
+    #[cfg(feature = "min-samd21j")]
+    (PB17, GCLK3),
+    #[cfg(all(feature = "min-samd21g", not(feature = "samd21gl")))]
+    (PB22, GCLK0),
+    #[cfg(all(feature = "min-samd21g", not(feature = "samd21gl")))]
+    (PB23, GCLK1),
+);
@@ -0,0 +1,145 @@
+//! External multipurpose crystal oscillator (XOSC)
+//!
+//! The XOSC can be driven either by a crystal between XIN and XOUT (PA14 and
+//! PA15), or by an external clock signal applied to XIN.
+
+use crate::pac::sysctrl::xosc::GAIN_A;
+use crate::pac::SYSCTRL;
+use crate::time::Hertz;
+
+use super::ClockError;
+
+/// Signal connected to the XOSC
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum XoscMode {
+    /// A crystal between XIN and XOUT
+    Crystal,
+    /// An external clock signal on XIN, XOUT is free to be used as GPIO
+    ExternalClock,
+}
+
+/// Configuration of the XOSC
+///
+/// Build it with [`XoscConfig::crystal`] or [`XoscConfig::external_clock`]
+/// and adjust the defaults with the builder methods.
+#[derive(Clone, Copy, Debug)]
+pub struct XoscConfig {
+    freq: Hertz,
+    mode: XoscMode,
+    start_up: u8,
+    auto_gain: bool,
+    on_demand: bool,
+    run_standby: bool,
+}
+
+impl XoscConfig {
+    /// Configuration for a crystal of the given frequency
+    ///
+    /// Crystals between 400 kHz and 32 MHz are supported.
+    #[inline]
+    pub fn crystal(freq: impl Into<Hertz>) -> Self {
+        Self::new(freq.into(), XoscMode::Crystal)
+    }
+
+    /// Configuration for an external clock signal of the given frequency
+    ///
+    /// External clocks up to 32 MHz are supported.
+    #[inline]
+    pub fn external_clock(freq: impl Into<Hertz>) -> Self {
+        Self::new(freq.into(), XoscMode::ExternalClock)
+    }
+
+    #[inline]
+    fn new(freq: Hertz, mode: XoscMode) -> Self {
+        Self {
+            freq,
+            mode,
+            start_up: 0xD,
+            auto_gain: false,
+            on_demand: false,
+            run_standby: false,
+        }
+    }
+
+    /// Set the `STARTUP` field; the XOSC is reported ready after
+    /// `2^start_up` OSCULP32K cycles
+    ///
+    /// Only the low four bits are used. The default of `0xD` gives 250ms.
+    #[inline]
+    pub fn start_up_delay(mut self, start_up: u8) -> Self {
+        self.start_up = start_up & 0xF;
+        self
+    }
+
+    /// Enable the automatic amplitude gain control of the crystal driver
+    #[inline]
+    pub fn auto_gain(mut self, auto_gain: bool) -> Self {
+        self.auto_gain = auto_gain;
+        self
+    }
+
+    /// Only run the oscillator when a peripheral requests it
+    #[inline]
+    pub fn on_demand(mut self, on_demand: bool) -> Self {
+        self.on_demand = on_demand;
+        self
+    }
+
+    /// Keep the oscillator running in standby sleep mode
+    ///
+    /// It is disabled by default.
+    #[inline]
+    pub fn run_standby(mut self, run_standby: bool) -> Self {
+        self.run_standby = run_standby;
+        self
+    }
+
+    /// Return the configured frequency
+    #[inline]
+    pub fn freq(&self) -> Hertz {
+        self.freq
+    }
+
+    /// Check the frequency against the limits of the selected mode
+    fn validate(&self) -> Result<(), ClockError> {
+        let range = match self.mode {
+            XoscMode::Crystal => 400_000..=32_000_000,
+            XoscMode::ExternalClock => 1..=32_000_000,
+        };
+        if range.contains(&self.freq.0) {
+            Ok(())
+        } else {
+            Err(ClockError::FrequencyOutOfRange)
+        }
+    }
+
+    /// Return the `GAIN` setting recommended for the crystal frequency
+    fn gain(&self) -> GAIN_A {
+        match self.freq.0 {
+            f if f <= 2_000_000 => GAIN_A::_0,
+            f if f <= 4_000_000 => GAIN_A::_1,
+            f if f <= 8_000_000 => GAIN_A::_2,
+            f if f <= 16_000_000 => GAIN_A::_3,
+            _ => GAIN_A::_4,
+        }
+    }
+}
+
+/// Validate the configuration, enable the XOSC and wait until it is ready
+pub(super) fn enable_xosc(sysctrl: &mut SYSCTRL, config: &XoscConfig) -> Result<(), ClockError> {
+    config.validate()?;
+    let crystal = config.mode == XoscMode::Crystal;
+    sysctrl.xosc.write(|w| unsafe {
+        w.startup().bits(config.start_up);
+        w.gain().variant(config.gain());
+        w.ampgc().bit(crystal && config.auto_gain);
+        w.xtalen().bit(crystal);
+        w.ondemand().bit(config.on_demand);
+        w.runstdby().bit(config.run_standby);
+        w.enable().set_bit()
+    });
+    while sysctrl.pclksr.read().xoscrdy().bit_is_clear() {
+        // Wait for the oscillator to stabilize
+    }
+    Ok(())
+}
@@ -3,6 +3,11 @@ use crate::pac;
 
 pub mod pin;
 
+#[cfg(feature = "async")]
+mod async_api;
+#[cfg(feature = "async")]
+pub use async_api::on_interrupt;
+
 pub struct EIC {
     eic: pac::EIC,
 }
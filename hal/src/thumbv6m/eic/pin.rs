@@ -58,6 +58,12 @@ crate::paste::item! {
     // impl !Send for [<$PadType $num>]<GPIO> {}
     // impl !Sync for [<$PadType $num>]<GPIO> {}
 
+    impl<GPIO: AnyPin> ExternalInterrupt for [<$PadType $num>]<GPIO> {
+        fn id(&self) -> ExternalInterruptID {
+            $num
+        }
+    }
+
     impl<GPIO: AnyPin> [<$PadType $num>]<GPIO> {
         /// Construct pad from the appropriate pin in any mode.
         /// You may find it more convenient to use the `into_pad` trait
@@ -146,6 +152,44 @@ crate::paste::item! {
         }
     }
 
+    #[cfg(feature = "async")]
+    impl<GPIO: AnyPin> crate::ehal_1::digital::ErrorType for [<$PadType $num>]<GPIO> {
+        type Error = core::convert::Infallible;
+    }
+
+    /// Wait for the line asynchronously
+    ///
+    /// The `EIC` interrupt handler(s) must call
+    /// [`eic::on_interrupt`](super::on_interrupt). The edges are detected with
+    /// the configured [`Sense`], which should be `BOTH`.
+    #[cfg(feature = "async")]
+    impl<GPIO: AnyPin> embedded_hal_async::digital::Wait for [<$PadType $num>]<GPIO> {
+        async fn wait_for_high(&mut self) -> Result<(), Self::Error> {
+            super::async_api::wait_for_level($num, || self._pin._is_high(), true).await;
+            Ok(())
+        }
+
+        async fn wait_for_low(&mut self) -> Result<(), Self::Error> {
+            super::async_api::wait_for_level($num, || self._pin._is_high(), false).await;
+            Ok(())
+        }
+
+        async fn wait_for_rising_edge(&mut self) -> Result<(), Self::Error> {
+            super::async_api::wait_for_edge($num, || self._pin._is_high(), Some(true)).await;
+            Ok(())
+        }
+
+        async fn wait_for_falling_edge(&mut self) -> Result<(), Self::Error> {
+            super::async_api::wait_for_edge($num, || self._pin._is_high(), Some(false)).await;
+            Ok(())
+        }
+
+        async fn wait_for_any_edge(&mut self) -> Result<(), Self::Error> {
+            super::async_api::wait_for_edge($num, || self._pin._is_high(), None).await;
+            Ok(())
+        }
+    }
+
     $(
         $(#[$attr])*
         impl<M: PinMode> EicPin for Pin<gpio::$PinType, M> {
@@ -1,3 +1,4 @@
+pub mod bod33;
 pub mod eic;
 
 mod reset_cause;
@@ -8,6 +9,7 @@ pub use serial_number::*;
 
 pub mod calibration;
 pub mod clock;
+pub mod pm;
 pub mod timer;
 
 #[cfg(feature = "unproven")]
@@ -0,0 +1,256 @@
+//! # Sleep modes
+//!
+//! On SAMD11/SAMD21, the sleep mode entered by the `WFI` instruction is
+//! selected by the `SLEEPDEEP` bit of the Cortex-M0+ `SCB.SCR` register and,
+//! for the IDLE modes, by `PM.SLEEP.IDLE`:
+//!
+//! * `IDLE0`: the CPU clock domain is stopped.
+//! * `IDLE1`: the CPU and AHB clock domains are stopped.
+//! * `IDLE2`: the CPU, AHB and APB clock domains are stopped.
+//! * `STANDBY`: all clocks are stopped, except the generators and sources
+//!   with `RUNSTDBY` set.
+//!
+//! A peripheral can only wake the device up from STANDBY if it keeps running,
+//! i.e. if its own `RUNSTDBY` bit is set (SERCOM), and if its peripheral
+//! channel is fed by a generator and a source that both run in standby, see
+//! [`GenericClockController::configure_standby`] and
+//! [`GenericClockController::configure_source_standby`]. [`standby`] checks
+//! this for every [`StandbyWakeSource`] before entering STANDBY.
+//!
+//! The previous sleep configuration is restored on wake up, so that a later
+//! `WFI`, e.g. in [`SleepingDelay`](crate::sleeping_delay::SleepingDelay),
+//! enters the same mode as before.
+//!
+//! ```no_run
+//! # use atsamd_hal::clock::{ClockGenId, GenericClockController};
+//! # use atsamd_hal::pac::gclk::genctrl::SRC_A::OSCULP32K;
+//! # use atsamd_hal::pac::{CorePeripherals, Peripherals};
+//! # use atsamd_hal::pm::*;
+//! # use atsamd_hal::eic::EIC;
+//! let mut peripherals = Peripherals::take().unwrap();
+//! let mut core = CorePeripherals::take().unwrap();
+//! let mut clocks = GenericClockController::with_internal_32kosc(
+//!     peripherals.GCLK,
+//!     &mut peripherals.PM,
+//!     &mut peripherals.SYSCTRL,
+//!     &mut peripherals.NVMCTRL,
+//! );
+//! // Clock the EIC from the OSCULP32K, which always runs
+//! let gclk2 = clocks
+//!     .configure_gclk_divider_and_source(ClockGenId::GCLK2, 1, OSCULP32K, false)
+//!     .unwrap();
+//! clocks.configure_standby(ClockGenId::GCLK2, true);
+//! let eic_clock = clocks.eic(&gclk2).unwrap();
+//! let eic = EIC::init(&mut peripherals.PM, eic_clock, peripherals.EIC);
+//! standby(&mut peripherals.PM, &mut core.SCB, &mut clocks, &[&eic]).unwrap();
+//! ```
+
+use cortex_m::asm;
+use cortex_m::interrupt;
+use cortex_m::peripheral::{NVIC, SCB};
+
+use crate::clock::{ClockId, GenericClockController};
+use crate::eic::EIC;
+use crate::pac::PM;
+use crate::rtc::{Rtc, RtcMode};
+use crate::sercom::{i2c, spi, uart, Sercom};
+
+/// `SLEEPDEEP` bit of `SCB.SCR`
+const SCR_SLEEPDEEP: u32 = 1 << 2;
+
+/// Sleep mode entered by [`sleep`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SleepMode {
+    /// The CPU clock domain is stopped
+    Idle0,
+    /// The CPU and AHB clock domains are stopped
+    Idle1,
+    /// The CPU, AHB and APB clock domains are stopped
+    Idle2,
+    /// All clocks are stopped, except those running in standby
+    Standby,
+}
+
+/// Source of the wake up
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WakeSource {
+    /// The pending interrupt with the lowest number, to compare with
+    /// `pac::Interrupt::X as u16`
+    Interrupt(u16),
+    /// No interrupt was pending, e.g. the wake up was requested by the
+    /// debugger
+    Unknown,
+}
+
+/// Reasons why a wake source would not run in STANDBY
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StandbyError {
+    /// The peripheral itself is not configured to run in standby
+    PeripheralStopped(ClockId),
+    /// The peripheral channel is not fed by a generator and a source that
+    /// both run in standby
+    ClockStopped(ClockId),
+}
+
+//==============================================================================
+// StandbyWakeSource
+//==============================================================================
+
+/// A peripheral that can wake the device up from STANDBY
+pub trait StandbyWakeSource {
+    /// Peripheral channel clocking the peripheral
+    fn clock_id(&self) -> ClockId;
+
+    /// Returns true if the peripheral is configured to keep running in
+    /// standby
+    ///
+    /// Peripherals without a `RUNSTDBY` bit always run as long as their clock
+    /// does.
+    #[inline]
+    fn runs_in_standby(&self) -> bool {
+        true
+    }
+}
+
+impl StandbyWakeSource for EIC {
+    #[inline]
+    fn clock_id(&self) -> ClockId {
+        ClockId::EIC
+    }
+}
+
+impl<Mode: RtcMode> StandbyWakeSource for Rtc<Mode> {
+    #[inline]
+    fn clock_id(&self) -> ClockId {
+        ClockId::RTC
+    }
+}
+
+impl<C, D> StandbyWakeSource for uart::Uart<C, D>
+where
+    C: uart::ValidConfig,
+    D: uart::Capability,
+{
+    #[inline]
+    fn clock_id(&self) -> ClockId {
+        <C::Sercom as Sercom>::CORE_CLOCK
+    }
+
+    #[inline]
+    fn runs_in_standby(&self) -> bool {
+        self.as_ref().get_run_in_standby()
+    }
+}
+
+impl<C, A> StandbyWakeSource for spi::Spi<C, A>
+where
+    C: spi::ValidConfig,
+    A: spi::Capability,
+{
+    #[inline]
+    fn clock_id(&self) -> ClockId {
+        <C::Sercom as Sercom>::CORE_CLOCK
+    }
+
+    #[inline]
+    fn runs_in_standby(&self) -> bool {
+        AsRef::<spi::SpecificConfig<C>>::as_ref(self).get_run_in_standby()
+    }
+}
+
+impl<P: i2c::PadSet> StandbyWakeSource for i2c::I2c<i2c::Config<P>> {
+    #[inline]
+    fn clock_id(&self) -> ClockId {
+        <P::Sercom as Sercom>::CORE_CLOCK
+    }
+
+    #[inline]
+    fn runs_in_standby(&self) -> bool {
+        self.as_ref().get_run_in_standby()
+    }
+}
+
+//==============================================================================
+// Sleep
+//==============================================================================
+
+/// Check that every wake source keeps running in STANDBY
+pub fn check_standby(
+    clocks: &mut GenericClockController,
+    wake_sources: &[&dyn StandbyWakeSource],
+) -> Result<(), StandbyError> {
+    for source in wake_sources {
+        let id = source.clock_id();
+        if !source.runs_in_standby() {
+            return Err(StandbyError::PeripheralStopped(id));
+        }
+        if !clocks.runs_in_standby(id) {
+            return Err(StandbyError::ClockStopped(id));
+        }
+    }
+    Ok(())
+}
+
+/// Enter STANDBY, after checking that every wake source keeps running
+///
+/// See [`sleep`] for the wake up.
+pub fn standby(
+    pm: &mut PM,
+    scb: &mut SCB,
+    clocks: &mut GenericClockController,
+    wake_sources: &[&dyn StandbyWakeSource],
+) -> Result<WakeSource, StandbyError> {
+    check_standby(clocks, wake_sources)?;
+    Ok(sleep(pm, scb, SleepMode::Standby))
+}
+
+/// Enter the sleep mode `mode`
+///
+/// Interrupts are masked while sleeping: the device wakes up on any enabled
+/// interrupt, which is reported as the [`WakeSource`] and then handled as soon
+/// as this returns. The previous `PM.SLEEP` and `SLEEPDEEP` settings are
+/// restored on wake up.
+///
+/// Nothing wakes the device up from STANDBY if no wake source keeps running,
+/// use [`standby`] to check them.
+pub fn sleep(pm: &mut PM, scb: &mut SCB, mode: SleepMode) -> WakeSource {
+    let idle = pm.sleep.read().idle().bits();
+    let sleepdeep = scb.scr.read() & SCR_SLEEPDEEP != 0;
+
+    match mode {
+        SleepMode::Idle0 => pm.sleep.write(|w| w.idle().cpu()),
+        SleepMode::Idle1 => pm.sleep.write(|w| w.idle().ahb()),
+        SleepMode::Idle2 => pm.sleep.write(|w| w.idle().apb()),
+        SleepMode::Standby => (),
+    }
+    if mode == SleepMode::Standby {
+        scb.set_sleepdeep();
+    } else {
+        scb.clear_sleepdeep();
+    }
+
+    let source = interrupt::free(|_| {
+        asm::dsb();
+        asm::wfi();
+        pending_interrupt()
+    });
+
+    pm.sleep.write(|w| unsafe { w.idle().bits(idle) });
+    if sleepdeep {
+        scb.set_sleepdeep();
+    } else {
+        scb.clear_sleepdeep();
+    }
+    source
+}
+
+/// Returns the lowest pending interrupt number
+fn pending_interrupt() -> WakeSource {
+    // Safety: read-only access to the NVIC pending register
+    let pending = unsafe { (*NVIC::PTR).ispr[0].read() };
+    if pending != 0 {
+        WakeSource::Interrupt(pending.trailing_zeros() as u16)
+    } else {
+        WakeSource::Unknown
+    }
+}
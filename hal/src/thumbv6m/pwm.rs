@@ -1,5 +1,9 @@
+#![allow(non_snake_case)]
+
 use crate::clock;
 use crate::ehal::{Pwm, PwmPin};
+use crate::gpio::*;
+use crate::gpio::{AlternateE, AnyPin, Pin};
 use crate::time::Hertz;
 use crate::timer_params::TimerParams;
 
@@ -13,22 +17,89 @@ use crate::pac::{TC6, TC7};
 
 // Timer/Counter (TCx)
 
+/// Declare the pinout of a TC, i.e. the pins of its waveform output 1, which
+/// carries the PWM signal
+///
+/// Each pin has a constructor named after it, which puts the pin in the right
+/// alternate function.
+macro_rules! impl_tc_pinout {
+    (
+        $Type:ident: [ $(
+            $( #[$attr:meta] )?
+            ($func: ident, $Id: ident)
+        ),+ ]
+    ) => {
+        pub struct $Type<I: PinId> {
+            pin: Pin<I, AlternateE>,
+        }
+
+        impl<I: PinId> $Type<I> {
+            /// Release the pin
+            #[inline]
+            pub fn free(self) -> Pin<I, AlternateE> {
+                self.pin
+            }
+        }
+
+        $(
+            $( #[$attr] )?
+            impl $Type<$Id> {
+                #[inline]
+                pub fn $func(pin: impl AnyPin<Id = $Id>) -> Self {
+                    let pin = pin.into().into_alternate();
+                    Self { pin }
+                }
+            }
+        )+
+    };
+}
+
+#[cfg(feature = "samd11")]
+impl_tc_pinout!(TC1Pinout: [(Pa15, PA15)]);
+#[cfg(feature = "samd11")]
+impl_tc_pinout!(TC2Pinout: [(Pa31, PA31)]);
+
+#[cfg(feature = "samd21")]
+impl_tc_pinout!(TC3Pinout: [(Pa15, PA15), (Pa19, PA19)]);
+#[cfg(feature = "samd21")]
+impl_tc_pinout!(TC4Pinout: [
+    (Pa23, PA23),
+    #[cfg(feature = "min-samd21g")]
+    (Pb9, PB09),
+    #[cfg(feature = "min-samd21j")]
+    (Pb13, PB13)
+]);
+#[cfg(feature = "samd21")]
+impl_tc_pinout!(TC5Pinout: [
+    (Pa25, PA25),
+    #[cfg(feature = "min-samd21g")]
+    (Pb11, PB11),
+    #[cfg(feature = "min-samd21j")]
+    (Pb15, PB15)
+]);
+#[cfg(feature = "samd21j")]
+impl_tc_pinout!(TC6Pinout: [(Pb3, PB03), (Pb17, PB17)]);
+#[cfg(feature = "samd21j")]
+impl_tc_pinout!(TC7Pinout: [(Pa21, PA21), (Pb1, PB01), (Pb23, PB23)]);
+
 macro_rules! pwm {
-    ($($TYPE:ident: ($TC:ident, $clock:ident, $apmask:ident, $apbits:ident, $wrapper:ident),)+) => {
+    ($($TYPE:ident: ($TC:ident, $pinout:ident, $clock:ident, $apmask:ident, $apbits:ident, $wrapper:ident),)+) => {
         $(
 
-pub struct $TYPE {
+pub struct $TYPE<I: PinId> {
     /// The frequency of the attached clock, not the period of the pwm.
     /// Used to calculate the period of the pwm.
     clock_freq: Hertz,
     tc: $TC,
+    pinout: $pinout<I>,
 }
 
-impl $TYPE {
+impl<I: PinId> $TYPE<I> {
     pub fn new<F: Into<Hertz>> (
         clock: &clock::$clock,
         freq: F,
         tc: $TC,
+        pinout: $pinout<I>,
         pm: &mut PM,
     ) -> Self {
         let freq = freq.into();
@@ -61,6 +132,7 @@ impl $TYPE {
         Self {
             clock_freq: clock.freq(),
             tc,
+            pinout,
         }
     }
 
@@ -95,9 +167,16 @@ impl $TYPE {
         let top = count.cc[0].read().cc().bits();
         Hertz(self.clock_freq.0 / divisor as u32 / (top + 1) as u32)
     }
+
+    /// Disable the TC and release it with the pinout
+    pub fn free(self) -> ($TC, $pinout<I>) {
+        let count = self.tc.count16();
+        count.ctrla.modify(|_, w| w.enable().clear_bit());
+        (self.tc, self.pinout)
+    }
 }
 
-impl PwmPin for $TYPE {
+impl<I: PinId> PwmPin for $TYPE<I> {
     type Duty = u16;
 
     fn disable(&mut self) {
@@ -128,25 +207,42 @@ impl PwmPin for $TYPE {
     }
 }
 
+#[cfg(feature = "ehal-1")]
+impl<I: PinId> crate::ehal_1::pwm::ErrorType for $TYPE<I> {
+    type Error = core::convert::Infallible;
+}
+
+#[cfg(feature = "ehal-1")]
+impl<I: PinId> crate::ehal_1::pwm::SetDutyCycle for $TYPE<I> {
+    fn max_duty_cycle(&self) -> u16 {
+        self.get_max_duty()
+    }
+
+    fn set_duty_cycle(&mut self, duty: u16) -> Result<(), Self::Error> {
+        self.set_duty(duty);
+        Ok(())
+    }
+}
+
 )+}}
 
 #[cfg(feature = "samd11")]
 pwm! {
-    Pwm1: (TC1, Tc1Tc2Clock, apbcmask, tc1_, Pwm1Wrapper),
-    Pwm2: (TC2, Tc1Tc2Clock, apbcmask, tc2_, Pwm2Wrapper),
+    Pwm1: (TC1, TC1Pinout, Tc1Tc2Clock, apbcmask, tc1_, Pwm1Wrapper),
+    Pwm2: (TC2, TC2Pinout, Tc1Tc2Clock, apbcmask, tc2_, Pwm2Wrapper),
 }
 
 #[cfg(feature = "samd21")]
 pwm! {
-    Pwm3: (TC3, Tcc2Tc3Clock, apbcmask, tc3_, Pwm3Wrapper),
-    Pwm4: (TC4, Tc4Tc5Clock, apbcmask, tc4_, Pwm4Wrapper),
-    Pwm5: (TC5, Tc4Tc5Clock, apbcmask, tc5_, Pwm5Wrapper),
+    Pwm3: (TC3, TC3Pinout, Tcc2Tc3Clock, apbcmask, tc3_, Pwm3Wrapper),
+    Pwm4: (TC4, TC4Pinout, Tc4Tc5Clock, apbcmask, tc4_, Pwm4Wrapper),
+    Pwm5: (TC5, TC5Pinout, Tc4Tc5Clock, apbcmask, tc5_, Pwm5Wrapper),
 }
 
 #[cfg(feature = "samd21j")]
 pwm! {
-    Pwm6: (TC6, Tc6Tc7Clock, apbcmask, tc6_, Pwm6Wrapper),
-    Pwm7: (TC7, Tc6Tc7Clock, apbcmask, tc7_, Pwm7Wrapper),
+    Pwm6: (TC6, TC6Pinout, Tc6Tc7Clock, apbcmask, tc6_, Pwm6Wrapper),
+    Pwm7: (TC7, TC7Pinout, Tc6Tc7Clock, apbcmask, tc7_, Pwm7Wrapper),
 }
 
 // Timer/Counter for Control Applications (TCCx)
@@ -159,22 +255,139 @@ pub enum Channel {
     _3,
 }
 
+/// Declare the pinout of a TCC, i.e. the pins of its waveform outputs with
+/// their alternate function
+///
+/// Each pin has a constructor named after it, which puts the pin in the right
+/// alternate function. The other waveform outputs can still be configured by
+/// hand.
+macro_rules! impl_tcc_pinout {
+    (
+        $Type:ident: [ $(
+            $( #[$attr:meta] )?
+            ($func: ident, $Id: ident, $Mode:ident)
+        ),+ ]
+    ) => {
+        pub struct $Type<I: PinId, M: PinMode> {
+            pin: Pin<I, M>,
+        }
+
+        impl<I: PinId, M: PinMode> $Type<I, M> {
+            /// Release the pin
+            #[inline]
+            pub fn free(self) -> Pin<I, M> {
+                self.pin
+            }
+        }
+
+        $(
+            $( #[$attr] )?
+            impl $Type<$Id, $Mode> {
+                #[inline]
+                pub fn $func(pin: impl AnyPin<Id = $Id>) -> Self {
+                    let pin = pin.into().into_alternate();
+                    Self { pin }
+                }
+            }
+        )+
+    };
+}
+
+#[cfg(feature = "samd11")]
+impl_tcc_pinout!(TCC0Pinout: [(Pa4, PA04, AlternateF), (Pa5, PA05, AlternateF)]);
+
+#[cfg(feature = "samd21")]
+impl_tcc_pinout!(TCC0Pinout: [
+    (Pa4, PA04, AlternateE),
+    (Pa5, PA05, AlternateE),
+    (Pa8, PA08, AlternateE),
+    (Pa9, PA09, AlternateE),
+    (Pa10, PA10, AlternateF),
+    (Pa11, PA11, AlternateF),
+    #[cfg(feature = "min-samd21g")]
+    (Pa12, PA12, AlternateF),
+    #[cfg(feature = "min-samd21g")]
+    (Pa13, PA13, AlternateF),
+    (Pa14, PA14, AlternateF),
+    (Pa15, PA15, AlternateF),
+    (Pa16, PA16, AlternateF),
+    (Pa17, PA17, AlternateF),
+    (Pa18, PA18, AlternateF),
+    (Pa19, PA19, AlternateF),
+    #[cfg(feature = "min-samd21g")]
+    (Pa20, PA20, AlternateF),
+    #[cfg(feature = "min-samd21g")]
+    (Pa21, PA21, AlternateF),
+    (Pa22, PA22, AlternateF),
+    (Pa23, PA23, AlternateF),
+    #[cfg(feature = "min-samd21g")]
+    (Pb10, PB10, AlternateF),
+    #[cfg(feature = "min-samd21g")]
+    (Pb11, PB11, AlternateF),
+    #[cfg(feature = "min-samd21j")]
+    (Pb12, PB12, AlternateF),
+    #[cfg(feature = "min-samd21j")]
+    (Pb13, PB13, AlternateF),
+    #[cfg(feature = "min-samd21j")]
+    (Pb16, PB16, AlternateF),
+    #[cfg(feature = "min-samd21j")]
+    (Pb17, PB17, AlternateF),
+    #[cfg(feature = "min-samd21j")]
+    (Pb30, PB30, AlternateE),
+    #[cfg(feature = "min-samd21j")]
+    (Pb31, PB31, AlternateE)
+]);
+
+#[cfg(feature = "samd21")]
+impl_tcc_pinout!(TCC1Pinout: [
+    (Pa6, PA06, AlternateE),
+    (Pa7, PA07, AlternateE),
+    (Pa8, PA08, AlternateF),
+    (Pa9, PA09, AlternateF),
+    (Pa10, PA10, AlternateE),
+    (Pa11, PA11, AlternateE),
+    (Pa24, PA24, AlternateF),
+    (Pa25, PA25, AlternateF),
+    (Pa30, PA30, AlternateE),
+    (Pa31, PA31, AlternateE),
+    #[cfg(feature = "min-samd21j")]
+    (Pb30, PB30, AlternateF),
+    #[cfg(feature = "min-samd21j")]
+    (Pb31, PB31, AlternateF)
+]);
+
+#[cfg(feature = "samd21")]
+impl_tcc_pinout!(TCC2Pinout: [
+    #[cfg(not(any(feature = "samd21el", feature = "samd21gl")))]
+    (Pa0, PA00, AlternateE),
+    #[cfg(not(any(feature = "samd21el", feature = "samd21gl")))]
+    (Pa1, PA01, AlternateE),
+    #[cfg(feature = "min-samd21g")]
+    (Pa12, PA12, AlternateE),
+    #[cfg(feature = "min-samd21g")]
+    (Pa13, PA13, AlternateE),
+    (Pa16, PA16, AlternateE),
+    (Pa17, PA17, AlternateE)
+]);
+
 macro_rules! pwm_tcc {
-    ($($TYPE:ident: ($TCC:ident, $clock:ident, $apmask:ident, $apbits:ident, $wrapper:ident),)+) => {
+    ($($TYPE:ident: ($TCC:ident, $pinout:ident, $clock:ident, $apmask:ident, $apbits:ident, $wrapper:ident),)+) => {
         $(
 
-pub struct $TYPE {
+pub struct $TYPE<I: PinId, M: PinMode> {
     /// The frequency of the attached clock, not the period of the pwm.
     /// Used to calculate the period of the pwm.
     clock_freq: Hertz,
     tcc: $TCC,
+    pinout: $pinout<I, M>,
 }
 
-impl $TYPE {
+impl<I: PinId, M: PinMode> $TYPE<I, M> {
     pub fn new<F: Into<Hertz>> (
         clock: &clock::$clock,
         freq: F,
         tcc: $TCC,
+        pinout: $pinout<I, M>,
         pm: &mut PM,
     ) -> Self {
         let freq = freq.into();
@@ -209,11 +422,19 @@ impl $TYPE {
         Self {
             clock_freq: clock.freq(),
             tcc,
+            pinout,
         }
     }
+
+    /// Disable the TCC and release it with the pinout
+    pub fn free(self) -> ($TCC, $pinout<I, M>) {
+        self.tcc.ctrla.modify(|_, w| w.enable().clear_bit());
+        while self.tcc.syncbusy.read().enable().bit_is_set() {}
+        (self.tcc, self.pinout)
+    }
 }
 
-impl Pwm for $TYPE {
+impl<I: PinId, M: PinMode> Pwm for $TYPE<I, M> {
     type Channel = Channel;
     type Time = Hertz;
     type Duty = u32;
@@ -278,12 +499,12 @@ impl Pwm for $TYPE {
 
 #[cfg(feature = "samd11")]
 pwm_tcc! {
-    Pwm0: (TCC0, Tcc0Clock, apbcmask, tcc0_, Pwm0Wrapper),
+    Pwm0: (TCC0, TCC0Pinout, Tcc0Clock, apbcmask, tcc0_, Pwm0Wrapper),
 }
 
 #[cfg(feature = "samd21")]
 pwm_tcc! {
-    Pwm0: (TCC0, Tcc0Tcc1Clock, apbcmask, tcc0_, Pwm0Wrapper),
-    Pwm1: (TCC1, Tcc0Tcc1Clock, apbcmask, tcc1_, Pwm1Wrapper),
-    Pwm2: (TCC2, Tcc2Tc3Clock, apbcmask, tcc2_, Pwm2Wrapper),
+    Pwm0: (TCC0, TCC0Pinout, Tcc0Tcc1Clock, apbcmask, tcc0_, Pwm0Wrapper),
+    Pwm1: (TCC1, TCC1Pinout, Tcc0Tcc1Clock, apbcmask, tcc1_, Pwm1Wrapper),
+    Pwm2: (TCC2, TCC2Pinout, Tcc2Tc3Clock, apbcmask, tcc2_, Pwm2Wrapper),
 }
@@ -572,6 +572,13 @@ impl Inner {
 }
 
 impl UsbBus {
+    /// Enable the USB peripheral clocks and take over the D-/D+ pins.
+    ///
+    /// The USB peripheral must be clocked at 48MHz. Without a 32khz crystal,
+    /// create the controller with
+    /// [`GenericClockController::with_usb_clock_recovery`](clock::GenericClockController::with_usb_clock_recovery)
+    /// and clock the USB from gclk0: the DFLL48M then locks on the start of
+    /// frame packets of the host once the bus is enabled.
     pub fn new(
         _clock: &clock::UsbClock,
         pm: &mut PM,
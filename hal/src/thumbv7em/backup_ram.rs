@@ -0,0 +1,207 @@
+//! # Backup RAM
+//!
+//! SAMx5x has 8 KB of backup RAM at `0x4700_0000`, which is retained in every
+//! sleep mode, including BACKUP and HIBERNATE when configured so with
+//! [`crate::pm::Pm::configure_backup`], and across resets other than a
+//! power-on reset. Data placed there survives the deep sleep, but is garbage
+//! after a power loss.
+//!
+//! [`BackupRam<T>`] wraps a value with a magic number and a CRC32, which are
+//! checked before the value is handed out again. The [`backup_ram!`] macro
+//! reserves such a struct in the `.bkupram` linker section. That section must
+//! be added to the `memory.x` of the application, as `NOLOAD` since the
+//! startup code must neither initialize nor zero it:
+//!
+//! ```text
+//! MEMORY
+//! {
+//!   /* ... */
+//!   BKUPRAM (rw) : ORIGIN = 0x47000000, LENGTH = 8K
+//! }
+//!
+//! SECTIONS
+//! {
+//!   .bkupram (NOLOAD) : { *(.bkupram .bkupram.*); } > BKUPRAM
+//! }
+//! ```
+//!
+//! For a few words, the eight RTC backup registers are also retained in
+//! BACKUP, see [`Rtc::backup_register`](crate::rtc::Rtc::backup_register).
+//!
+//! ```no_run
+//! use atsamd_hal::backup_ram;
+//!
+//! backup_ram!(fn boot_count() -> u32);
+//!
+//! let count = boot_count().unwrap();
+//! let boots = count.read().unwrap_or(0) + 1;
+//! count.write(boots);
+//! ```
+//!
+//! [`backup_ram!`]: crate::backup_ram
+
+use core::mem::{size_of, MaybeUninit};
+use core::ptr;
+
+/// Magic number marking a valid [`BackupRam`], combined with the size of the
+/// stored type so that a layout change invalidates it
+const MAGIC: u32 = 0x424B_5550;
+
+/// Types that can be stored in a [`BackupRam`]
+///
+/// # Safety
+///
+/// The type must not have any padding byte, since the CRC32 is computed over
+/// all the bytes of the value, and every bit pattern must be a valid value.
+pub unsafe trait BackupData: Copy {}
+
+macro_rules! impl_backup_data {
+    ($($ty:ty),*) => {
+        $(
+            unsafe impl BackupData for $ty {}
+        )*
+    };
+}
+
+impl_backup_data!(u8, u16, u32, u64, i8, i16, i32, i64);
+
+unsafe impl<T: BackupData, const N: usize> BackupData for [T; N] {}
+
+/// A value of type `T` in backup RAM, validated by a magic number and a
+/// CRC32
+///
+/// The value is only returned if it was written by [`write`](Self::write)
+/// before the last reset or sleep, and was not corrupted since.
+#[repr(C)]
+pub struct BackupRam<T> {
+    magic: u32,
+    crc: u32,
+    value: MaybeUninit<T>,
+}
+
+impl<T> BackupRam<T> {
+    /// An uninitialized `BackupRam`, to be placed in a `NOLOAD` section
+    #[inline]
+    pub const fn uninit() -> Self {
+        Self {
+            magic: 0,
+            crc: 0,
+            value: MaybeUninit::uninit(),
+        }
+    }
+}
+
+impl<T: BackupData> BackupRam<T> {
+    #[inline]
+    fn magic() -> u32 {
+        MAGIC ^ size_of::<T>() as u32
+    }
+
+    /// Compute the CRC32 of the stored value
+    fn value_crc(&self) -> u32 {
+        let start = self.value.as_ptr() as *const u8;
+        // The backup RAM is not initialized from the point of view of the
+        // compiler, so every byte is read with a volatile access
+        crc32((0..size_of::<T>()).map(|i| unsafe { ptr::read_volatile(start.add(i)) }))
+    }
+
+    /// Returns true if the stored value is valid
+    pub fn is_valid(&self) -> bool {
+        // Safety: the magic and CRC are plain integers
+        let magic = unsafe { ptr::read_volatile(&self.magic) };
+        let crc = unsafe { ptr::read_volatile(&self.crc) };
+        magic == Self::magic() && crc == self.value_crc()
+    }
+
+    /// Returns the stored value, or `None` if it is not valid
+    pub fn read(&self) -> Option<T> {
+        if self.is_valid() {
+            // Safety: the value was written by `write`, as checked by the CRC
+            Some(unsafe { ptr::read_volatile(self.value.as_ptr()) })
+        } else {
+            None
+        }
+    }
+
+    /// Store `value`, along with its magic number and CRC32
+    pub fn write(&mut self, value: T) {
+        unsafe {
+            ptr::write_volatile(&mut self.magic, 0);
+            ptr::write_volatile(self.value.as_mut_ptr(), value);
+        }
+        let crc = self.value_crc();
+        unsafe {
+            ptr::write_volatile(&mut self.crc, crc);
+            ptr::write_volatile(&mut self.magic, Self::magic());
+        }
+    }
+
+    /// Apply `f` to the stored value, or to `default` if it is not valid, and
+    /// store the result. Returns the new value.
+    pub fn update(&mut self, default: T, f: impl FnOnce(&mut T)) -> T {
+        let mut value = self.read().unwrap_or(default);
+        f(&mut value);
+        self.write(value);
+        value
+    }
+
+    /// Mark the stored value as not valid
+    pub fn invalidate(&mut self) {
+        unsafe { ptr::write_volatile(&mut self.magic, 0) };
+    }
+}
+
+/// Declare a function returning a [`BackupRam`] placed in the `.bkupram`
+/// linker section
+///
+/// The function returns `Some` on the first call only, so that the
+/// `&'static mut` reference is unique.
+///
+/// ```no_run
+/// atsamd_hal::backup_ram!(pub fn crash_context() -> [u32; 16]);
+/// ```
+#[macro_export]
+macro_rules! backup_ram {
+    ($vis:vis fn $name:ident() -> $ty:ty) => {
+        $vis fn $name() -> Option<&'static mut $crate::backup_ram::BackupRam<$ty>> {
+            use ::core::sync::atomic::{AtomicBool, Ordering};
+
+            #[link_section = ".bkupram"]
+            static mut VALUE: $crate::backup_ram::BackupRam<$ty> =
+                $crate::backup_ram::BackupRam::uninit();
+            static TAKEN: AtomicBool = AtomicBool::new(false);
+
+            if TAKEN.swap(true, Ordering::AcqRel) {
+                None
+            } else {
+                // Safety: the reference is only handed out once
+                Some(unsafe { &mut *::core::ptr::addr_of_mut!(VALUE) })
+            }
+        }
+    };
+}
+
+/// CRC32 (IEEE 802.3), the same algorithm as the DSU
+/// [`crc32`](crate::dsu::Dsu::crc32)
+fn crc32(bytes: impl Iterator<Item = u8>) -> u32 {
+    let mut crc = 0xFFFF_FFFF_u32;
+    for byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::crc32;
+
+    #[test]
+    fn crc32_matches_check_value() {
+        assert_eq!(crc32(b"123456789".iter().copied()), 0xCBF4_3926);
+        assert_eq!(crc32(core::iter::empty()), 0);
+    }
+}
@@ -3,17 +3,95 @@
 //! before you can set up most of the peripherals on the atsamd51 device.
 //! The other types in this module are used to enforce at compile time
 //! that the peripherals have been correctly configured.
+//!
+//! # Clock tree
+//!
+//! Besides the fixed 120MHz configuration, the clock tree can be built from
+//! typed tokens. [`GenericClockController::with_clock_tokens`] leaves the
+//! system running at 48MHz from the DFLL48M and hands out a [`Tokens`] struct
+//! with one token per oscillator and per GCLK generator. Each token is
+//! consumed when the corresponding clock is enabled, and the resulting
+//! [`Gclk`]s feed the usual peripheral clock methods. The oscillators and
+//! DPLLs only run in standby sleep mode if their configuration asks for it.
+//!
+//! ```no_run
+//! # use atsamd_hal::clock::*;
+//! # use atsamd_hal::pac::Peripherals;
+//! # use atsamd_hal::time::U32Ext;
+//! let mut peripherals = Peripherals::take().unwrap();
+//! let (mut clocks, tokens) = GenericClockController::with_clock_tokens(
+//!     peripherals.GCLK,
+//!     &mut peripherals.MCLK,
+//!     peripherals.OSC32KCTRL,
+//!     peripherals.OSCCTRL,
+//!     &mut peripherals.NVMCTRL,
+//! );
+//! // 12MHz crystal, divided by 6 to a 2MHz DPLL reference, times 60
+//! let xosc0 = clocks
+//!     .enable_xosc(tokens.xosc0, XoscConfig::crystal(12.mhz()))
+//!     .ok()
+//!     .unwrap();
+//! let dpll0 = clocks
+//!     .enable_dpll(tokens.dpll0, &xosc0, DpllConfig::new(59, 0).xosc_divider(2))
+//!     .ok()
+//!     .unwrap();
+//! // Run the CPU at 120MHz and SERCOM0 at 12MHz
+//! let gclk0 = clocks.configure_gclk(tokens.gclks.gclk0, &dpll0, 1, false).ok().unwrap();
+//! let gclk2 = clocks.configure_gclk(tokens.gclks.gclk2, &xosc0, 1, false).ok().unwrap();
+//! let sercom0_clock = clocks.sercom0_core(gclk2.as_ref()).unwrap();
+//! ```
+//!
+//! # Changing frequencies at runtime
+//!
+//! Peripherals capture the frequency of their generator when they are
+//! created. [`GenericClockController::reconfigure_gclk`] only changes a
+//! generator once every peripheral channel it feeds is accounted for: either
+//! disconnected with
+//! [`disable_peripheral_clock`](GenericClockController::disable_peripheral_clock),
+//! or backed by a peripheral implementing [`Reclock`], which then recomputes
+//! its baud rate or prescaler.
+//!
+//! ```no_run
+//! # use atsamd_hal::clock::*;
+//! # use atsamd_hal::sercom::uart;
+//! # fn f<C: uart::ValidConfig>(clocks: &mut GenericClockController, gclk2: &mut GClock, src: ClockSource, uart: &mut uart::Uart<C, uart::Duplex>) {
+//! // Halve the frequency of GCLK2, which clocks the UART
+//! clocks
+//!     .reconfigure_gclk(gclk2, 2, src, false, &mut [uart])
+//!     .unwrap();
+//! # }
+//! ```
 #![allow(clippy::from_over_into)]
 
+pub use crate::clock_params::GclkOutConfig;
+use crate::clock_params::{ClockParamsError, GclkLimits, GclkParams};
+use crate::gpio::{AnyPin, OptionalPin};
 use crate::pac::gclk::genctrl::SRC_A::*;
 use crate::pac::gclk::pchctrl::GEN_A::*;
 use crate::pac::{self, GCLK, MCLK, NVMCTRL, OSC32KCTRL, OSCCTRL};
 use crate::time::{Hertz, MegaHertz};
+use crate::typelevel::{NoneT, Sealed};
+
+mod dfll;
+pub use dfll::*;
+
+mod dpll;
+pub use dpll::*;
+
+mod gclk;
+pub use gclk::*;
+
+mod osc32k;
+pub use osc32k::*;
+
+mod xosc;
+pub use xosc::*;
 
 pub type ClockGenId = pac::gclk::pchctrl::GEN_A;
 pub type ClockSource = pac::gclk::genctrl::SRC_A;
 
 #[allow(non_camel_case_types)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum ClockId {
     DFLL48 = 0,
     FDPLL0,
@@ -71,6 +149,19 @@ impl From<ClockId> for u8 {
     }
 }
 
+/// Number of peripheral channels in `GCLK.PCHCTRL`
+const NUM_CHANNELS: usize = 48;
+
+/// Number of variants of [`ClockSource`]
+const NUM_SOURCES: usize = 9;
+
+/// USB clock recovery setting of `with_internal_32kosc` and
+/// `with_external_32kosc`, which enable it with the `usb` feature
+#[cfg(feature = "usb")]
+const USB_RECOVERY: Option<DfllUsbRecovery> = Some(DfllUsbRecovery::new());
+#[cfg(not(feature = "usb"))]
+const USB_RECOVERY: Option<DfllUsbRecovery> = None;
+
 /// Represents a configured clock generator.
 /// Can be converted into the effective clock frequency.
 /// Its primary purpose is to be passed in to methods
@@ -88,6 +179,119 @@ impl Into<Hertz> for GClock {
     }
 }
 
+impl AsRef<GClock> for GClock {
+    fn as_ref(&self) -> &GClock {
+        self
+    }
+}
+
+/// Errors reported while configuring the clock tree
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ClockError {
+    /// A source, reference or output frequency is outside the range supported
+    /// by the hardware
+    FrequencyOutOfRange,
+    /// A divider or multiplier does not fit in its register field
+    InvalidDivider,
+    /// The generator or peripheral channel has already been configured
+    AlreadyConfigured,
+    /// The generator feeds a peripheral channel, DPLL or generator that was
+    /// not accounted for
+    InUse,
+    /// The selected oscillator, or the selected output of the oscillator, is
+    /// not running
+    NotRunning,
+}
+
+/// A running clock that can be selected as the source of a GCLK generator
+pub trait Source: Sealed {
+    /// The `GENCTRL.SRC` selection for this clock
+    fn source(&self) -> ClockSource;
+    /// The frequency of the clock
+    fn freq(&self) -> Hertz;
+}
+
+impl<P: OptionalPin> Source for Gclk<Gclk1Id, P> {
+    #[inline]
+    fn source(&self) -> ClockSource {
+        GCLKGEN1
+    }
+
+    #[inline]
+    fn freq(&self) -> Hertz {
+        Gclk::freq(self)
+    }
+}
+
+impl<G: GclkId, P: OptionalPin> Sealed for Gclk<G, P> {}
+
+/// A peripheral whose settings depend on the frequency of its peripheral
+/// channel
+///
+/// Passing a `Reclock` to
+/// [`GenericClockController::reconfigure_gclk`] proves that the peripheral
+/// will follow the new frequency of its generator.
+pub trait Reclock {
+    /// Peripheral channel clocking the peripheral
+    fn clock_id(&self) -> ClockId;
+    /// Recompute the frequency-dependent settings, e.g. the `BAUD` register
+    /// or the prescaler, for the new channel frequency
+    fn reclock(&mut self, freq: Hertz);
+    /// Peripherals of the channel driven by `self`, as a bit mask: `0b01` for
+    /// the first peripheral named by the channel, e.g. TC4 on `TC4_TC5`, and
+    /// `0b10` for the second one. Peripherals alone on their channel are
+    /// `0b01`.
+    fn channel_users(&self) -> u8 {
+        0b01
+    }
+}
+
+/// Channels clocking two peripherals, which must both be reclocked together
+const SHARED_CHANNELS: &[ClockId] = &[
+    ClockId::TC0_TC1,
+    ClockId::TCC0_TCC1,
+    ClockId::TC2_TC3,
+    ClockId::TCC2_TCC3,
+    ClockId::TC4_TC5,
+    ClockId::TC6_TC7,
+];
+
+/// Bit mask of the peripherals clocked by `channel`, see
+/// [`Reclock::channel_users`]
+fn channel_users(channel: usize) -> u8 {
+    if SHARED_CHANNELS
+        .iter()
+        .any(|clock| u8::from(*clock) as usize == channel)
+    {
+        0b11
+    } else {
+        0b01
+    }
+}
+
+/// A peripheral clock token, as returned by the peripheral clock methods of
+/// [`GenericClockController`]
+pub trait PeripheralClock: Sealed {
+    /// Peripheral channel of the token
+    const ID: ClockId;
+}
+
+/// The tokens for every configurable clock of the clock tree
+///
+/// They are returned by [`GenericClockController::with_clock_tokens`] and
+/// each one can only exist once.
+pub struct Tokens {
+    pub xosc0: XoscToken<Xosc0Id>,
+    pub xosc1: XoscToken<Xosc1Id>,
+    pub xosc32k: Xosc32kToken,
+    pub osculp32k: OscUlp32k,
+    pub dfll: Dfll,
+    pub dpll0: DpllToken<Dpll0Id>,
+    pub dpll1: DpllToken<Dpll1Id>,
+    pub gclks: GclkTokens,
+    pub rtc: RtcClockToken,
+}
+
 struct State {
     gclk: GCLK,
 }
@@ -103,6 +307,21 @@ impl State {
         while self.gclk.syncbusy.read().bits() != 0 {}
     }
 
+    /// Run `f` with GCLK0 temporarily switched from the DFLL to the
+    /// OSCULP32K, so that the DFLL can be stopped
+    fn with_gclk0_off_dfll(&mut self, f: impl FnOnce()) {
+        let on_dfll = self.gclk.genctrl[0].read().src().is_dfll();
+        if on_dfll {
+            self.gclk.genctrl[0].modify(|_, w| w.src().osculp32k());
+            while self.gclk.syncbusy.read().genctrl().is_gclk0() {}
+        }
+        f();
+        if on_dfll {
+            self.gclk.genctrl[0].modify(|_, w| w.src().dfll());
+            while self.gclk.syncbusy.read().genctrl().is_gclk0() {}
+        }
+    }
+
     fn set_gclk_divider_and_source(
         &mut self,
         gclk: ClockGenId,
@@ -123,11 +342,22 @@ impl State {
             panic!("invalid divisor {} for GCLK {}", divider, gclk as u8);
         }
 
+        // divide directly by divider, rather than 2^(n+1)
+        self.set_gclk_div_and_source(gclk, divider, false, src, improve_duty_cycle);
+    }
+
+    fn set_gclk_div_and_source(
+        &mut self,
+        gclk: ClockGenId,
+        div: u16,
+        divsel: bool,
+        src: ClockSource,
+        improve_duty_cycle: bool,
+    ) {
         self.gclk.genctrl[u8::from(gclk) as usize].write(|w| unsafe {
             w.src().variant(src);
-            w.div().bits(divider);
-            // divide directly by divider, rather than 2^(n+1)
-            w.divsel().clear_bit();
+            w.div().bits(div);
+            w.divsel().bit(divsel);
             w.idc().bit(improve_duty_cycle);
             w.genen().set_bit();
             w.oe().set_bit()
@@ -136,6 +366,24 @@ impl State {
         self.wait_for_sync();
     }
 
+    /// Change the source and linear divider of a running generator, keeping
+    /// its other `GENCTRL` settings like `RUNSTDBY`, `OE` and `OOV`
+    fn modify_gclk_divider_and_source(
+        &mut self,
+        gclk: ClockGenId,
+        divider: u16,
+        src: ClockSource,
+        improve_duty_cycle: bool,
+    ) {
+        self.gclk.genctrl[u8::from(gclk) as usize].modify(|_, w| unsafe {
+            w.src().variant(src);
+            w.div().bits(divider);
+            w.divsel().clear_bit();
+            w.idc().bit(improve_duty_cycle)
+        });
+        self.wait_for_sync();
+    }
+
     fn enable_clock_generator(&mut self, clock: ClockId, generator: ClockGenId) {
         self.gclk.pchctrl[u8::from(clock) as usize].write(|w| unsafe {
             w.gen().bits(generator.into());
@@ -144,10 +392,38 @@ impl State {
         self.wait_for_sync();
     }
 
+    fn disable_clock_generator(&mut self, clock: ClockId) {
+        self.gclk.pchctrl[u8::from(clock) as usize].write(|w| w.chen().clear_bit());
+        self.wait_for_sync();
+    }
+
+    fn disable_gclk(&mut self, gclk: ClockGenId) {
+        self.gclk.genctrl[u8::from(gclk) as usize].modify(|_, w| w.genen().clear_bit());
+        self.wait_for_sync();
+    }
+
     fn configure_standby(&mut self, gclk: ClockGenId, enable: bool) {
         self.gclk.genctrl[u8::from(gclk) as usize].modify(|_, w| w.runstdby().bit(enable));
         self.wait_for_sync();
     }
+
+    fn gclk_output(&mut self, gclk: ClockGenId) -> GclkOutConfig {
+        let genctrl = self.gclk.genctrl[u8::from(gclk) as usize].read();
+        GclkOutConfig {
+            output_enable: genctrl.oe().bit(),
+            off_value: genctrl.oov().bit(),
+            improve_duty_cycle: genctrl.idc().bit(),
+        }
+    }
+
+    fn set_gclk_output(&mut self, gclk: ClockGenId, config: &GclkOutConfig) {
+        self.gclk.genctrl[u8::from(gclk) as usize].modify(|_, w| {
+            w.oe().bit(config.output_enable);
+            w.oov().bit(config.off_value);
+            w.idc().bit(config.improve_duty_cycle)
+        });
+        self.wait_for_sync();
+    }
 }
 
 /// `GenericClockController` encapsulates the GCLK hardware.
@@ -160,6 +436,20 @@ pub struct GenericClockController {
     state: State,
     gclks: [Hertz; 12],
     used_clocks: u64,
+    /// Frequency of each running source, indexed by `ClockSource`
+    sources: [Hertz; NUM_SOURCES],
+    /// Source of each configured generator
+    gclk_srcs: [Option<ClockSource>; 12],
+    /// Bit mask of the generators owned by a typed [`Gclk`]
+    typed_gclks: u16,
+    /// Reference of each enabled DPLL
+    dpll_refs: [Option<DpllRef>; 2],
+    /// Generator feeding each peripheral channel
+    clock_gens: [Option<ClockGenId>; NUM_CHANNELS],
+    /// RTC clock token, until taken by the user
+    rtc_token: Option<RtcClockToken>,
+    /// Output selected for the RTC, while an `RtcClock` exists
+    rtc_source: Option<RtcClockSource>,
 }
 
 impl GenericClockController {
@@ -172,7 +462,15 @@ impl GenericClockController {
         oscctrl: &mut OSCCTRL,
         nvmctrl: &mut NVMCTRL,
     ) -> Self {
-        Self::new(gclk, mclk, osc32kctrl, oscctrl, nvmctrl, false)
+        Self::new(
+            gclk,
+            mclk,
+            osc32kctrl,
+            oscctrl,
+            nvmctrl,
+            false,
+            USB_RECOVERY,
+        )
     }
 
     /// Reset the clock controller, configure the system to run
@@ -184,7 +482,57 @@ impl GenericClockController {
         oscctrl: &mut OSCCTRL,
         nvmctrl: &mut NVMCTRL,
     ) -> Self {
-        Self::new(gclk, mclk, osc32kctrl, oscctrl, nvmctrl, true)
+        Self::new(gclk, mclk, osc32kctrl, oscctrl, nvmctrl, true, USB_RECOVERY)
+    }
+
+    /// Reset the clock controller, configure the system to run at 120Mhz
+    /// with the DFLL48M in USB clock recovery mode, and reset various clock
+    /// dividers.
+    ///
+    /// This is the setup for crystal-less USB devices: the DFLL runs closed
+    /// loop against the USB start of frame packets, so its output stays
+    /// within the USB specification once the host is sending them. gclk1 is
+    /// fed by the internal 32khz oscillator. The USB peripheral must be
+    /// clocked from a generator fed by the DFLL.
+    ///
+    /// ```no_run
+    /// # use atsamd_hal::clock::*;
+    /// # use atsamd_hal::pac::Peripherals;
+    /// # use atsamd_hal::pac::gclk::genctrl::SRC_A::DFLL;
+    /// # use atsamd_hal::pac::gclk::pchctrl::GEN_A::GCLK2;
+    /// let mut peripherals = Peripherals::take().unwrap();
+    /// let mut clocks = GenericClockController::with_usb_clock_recovery(
+    ///     peripherals.GCLK,
+    ///     &mut peripherals.MCLK,
+    ///     &mut peripherals.OSC32KCTRL,
+    ///     &mut peripherals.OSCCTRL,
+    ///     &mut peripherals.NVMCTRL,
+    ///     DfllUsbRecovery::new(),
+    /// );
+    /// // Clock the USB peripheral from the DFLL, then pass the clock to
+    /// // `usb::UsbBus::new`
+    /// let gclk2 = clocks
+    ///     .configure_gclk_divider_and_source(GCLK2, 1, DFLL, false)
+    ///     .unwrap();
+    /// let usb_clock = clocks.usb(&gclk2).unwrap();
+    /// ```
+    pub fn with_usb_clock_recovery(
+        gclk: GCLK,
+        mclk: &mut MCLK,
+        osc32kctrl: &mut OSC32KCTRL,
+        oscctrl: &mut OSCCTRL,
+        nvmctrl: &mut NVMCTRL,
+        config: DfllUsbRecovery,
+    ) -> Self {
+        Self::new(
+            gclk,
+            mclk,
+            osc32kctrl,
+            oscctrl,
+            nvmctrl,
+            false,
+            Some(config),
+        )
     }
 
     fn new(
@@ -194,6 +542,7 @@ impl GenericClockController {
         oscctrl: &mut OSCCTRL,
         nvmctrl: &mut NVMCTRL,
         use_external_crystal: bool,
+        usb_recovery: Option<DfllUsbRecovery>,
     ) -> Self {
         let mut state = State { gclk };
 
@@ -212,8 +561,9 @@ impl GenericClockController {
 
         while state.gclk.syncbusy.read().genctrl().is_gclk0() {}
 
-        #[cfg(feature = "usb")]
-        configure_usb_correction(oscctrl);
+        if let Some(config) = usb_recovery {
+            state.with_gclk0_off_dfll(|| enable_usb_recovery(oscctrl, &config));
+        }
 
         // GCLK5 set to 2MHz
         unsafe {
@@ -243,6 +593,23 @@ impl GenericClockController {
 
         mclk.cpudiv.write(|w| w.div().div1());
 
+        let slow_src = if use_external_crystal {
+            XOSC32K
+        } else {
+            OSCULP32K
+        };
+        let mut sources = [Hertz(0); NUM_SOURCES];
+        sources[u8::from(OSCULP32K) as usize] = OSC32K_FREQ;
+        sources[u8::from(slow_src) as usize] = OSC32K_FREQ;
+        sources[u8::from(DFLL) as usize] = OSC48M_FREQ;
+        sources[u8::from(DPLL0) as usize] = OSC120M_FREQ;
+        let mut gclk_srcs = [None; 12];
+        gclk_srcs[0] = Some(DPLL0);
+        gclk_srcs[1] = Some(slow_src);
+        gclk_srcs[5] = Some(DFLL);
+        let mut clock_gens = [None; NUM_CHANNELS];
+        clock_gens[u8::from(ClockId::FDPLL0) as usize] = Some(GCLK5);
+
         Self {
             state,
             gclks: [
@@ -260,9 +627,82 @@ impl GenericClockController {
                 Hertz(0),
             ],
             used_clocks: 1u64 << u8::from(ClockId::FDPLL0),
+            sources,
+            gclk_srcs,
+            typed_gclks: 0,
+            dpll_refs: [Some(DpllRef::Gclk(GCLK5)), None],
+            clock_gens,
+            // Safety: the token is only created here and in `with_clock_tokens`
+            rtc_token: Some(unsafe { RtcClockToken::new() }),
+            rtc_source: None,
         }
     }
 
+    /// Reset the clock controller and hand out the tokens needed to build a
+    /// custom clock tree.
+    ///
+    /// The system is left running at 48MHz: GCLK0 is fed by the DFLL48M in
+    /// open-loop mode, and all other generators are disabled. The
+    /// `OSC32KCTRL` and `OSCCTRL` peripherals are consumed, because the
+    /// returned [`Tokens`] take over their registers.
+    ///
+    /// This must be called with the oscillators in their reset state.
+    pub fn with_clock_tokens(
+        gclk: GCLK,
+        mclk: &mut MCLK,
+        osc32kctrl: OSC32KCTRL,
+        oscctrl: OSCCTRL,
+        nvmctrl: &mut NVMCTRL,
+    ) -> (Self, Tokens) {
+        let mut state = State { gclk };
+
+        set_flash_to_half_auto_wait_state(nvmctrl);
+        enable_gclk_apb(mclk);
+        state.reset_gclk();
+        mclk.cpudiv.write(|w| w.div().div1());
+
+        let mut sources = [Hertz(0); NUM_SOURCES];
+        sources[u8::from(OSCULP32K) as usize] = OSC32K_FREQ;
+        sources[u8::from(DFLL) as usize] = OSC48M_FREQ;
+        let mut gclk_srcs = [None; 12];
+        gclk_srcs[0] = Some(DFLL);
+
+        let mut gclks = [Hertz(0); 12];
+        gclks[0] = OSC48M_FREQ;
+
+        let clocks = Self {
+            state,
+            gclks,
+            used_clocks: 0,
+            sources,
+            gclk_srcs,
+            typed_gclks: 0,
+            dpll_refs: [None, None],
+            clock_gens: [None; NUM_CHANNELS],
+            rtc_token: None,
+            rtc_source: None,
+        };
+
+        // The tokens own the oscillator registers from now on
+        let _ = (osc32kctrl, oscctrl);
+
+        // Safety: the PAC peripherals were consumed, so this can only happen once
+        let tokens = unsafe {
+            Tokens {
+                xosc0: XoscToken::new(),
+                xosc1: XoscToken::new(),
+                xosc32k: Xosc32kToken::new(),
+                osculp32k: OscUlp32k::new(),
+                dfll: Dfll::new(),
+                dpll0: DpllToken::new(),
+                dpll1: DpllToken::new(),
+                gclks: GclkTokens::new(),
+                rtc: RtcClockToken::new(),
+            }
+        };
+        (clocks, tokens)
+    }
+
     /// Returns a `GClock` for gclk0, the 120MHz oscillator.
     pub fn gclk0(&mut self) -> GClock {
         GClock {
@@ -302,7 +742,11 @@ impl GenericClockController {
     /// `improve_duty_cycle` is a boolean that, when set to true, enables
     /// a 50/50 duty cycle for odd divider values.
     /// Returns a `GClock` for the configured clock generator.
-    /// Returns `None` if the clock generator has already been configured.
+    /// Returns `None` if the clock generator has already been configured,
+    /// or if `src` is not running. `XOSC0`, `XOSC1`, `XOSC32K` and `DPLL1`
+    /// are only known to be running once they have been enabled through the
+    /// clock tokens, and `GCLKIN` requires
+    /// [`configure_gclk_in`](Self::configure_gclk_in).
     pub fn configure_gclk_divider_and_source(
         &mut self,
         gclk: ClockGenId,
@@ -314,16 +758,67 @@ impl GenericClockController {
         if self.gclks[idx].0 != 0 {
             return None;
         }
+        let freq = self.source_freq(src);
+        if freq.0 == 0 {
+            return None;
+        }
         self.state
             .set_gclk_divider_and_source(gclk, divider, src, improve_duty_cycle);
-        let freq: Hertz = match src {
-            XOSC32K | OSCULP32K => OSC32K_FREQ,
-            GCLKGEN1 => self.gclks[1],
-            DFLL => OSC48M_FREQ,
-            DPLL0 => OSC120M_FREQ,
-            XOSC0 | XOSC1 | GCLKIN | DPLL1 => unimplemented!(),
-        };
         self.gclks[idx] = Hertz(freq.0 / divider as u32);
+        self.gclk_srcs[idx] = Some(src);
+        Some(GClock { gclk, freq })
+    }
+
+    /// Computes the divider settings that bring `src` closest to `target`,
+    /// within the limits of the generator `gclk`.
+    ///
+    /// Returns [`ClockParamsError::ZeroFrequency`] if `src` is not running.
+    /// The result can be passed to
+    /// [`configure_gclk_params`](Self::configure_gclk_params).
+    pub fn gclk_params(
+        &self,
+        gclk: ClockGenId,
+        src: ClockSource,
+        target: impl Into<Hertz>,
+    ) -> Result<GclkParams, ClockParamsError> {
+        GclkParams::new(self.source_freq(src), target, &gclk_limits(gclk))
+    }
+
+    /// Configures a clock generator with divider settings computed by the
+    /// [`GclkParams`] solver, including power of two dividers.
+    /// Returns `None` if the clock generator has already been configured, if
+    /// `src` is not running or if the settings do not fit the generator.
+    pub fn configure_gclk_params(
+        &mut self,
+        gclk: ClockGenId,
+        params: &GclkParams,
+        src: ClockSource,
+        improve_duty_cycle: bool,
+    ) -> Option<GClock> {
+        let idx = u8::from(gclk) as usize;
+        let limits = gclk_limits(gclk);
+        let div_valid = if params.divsel {
+            params.div <= limits.div_bits as u16
+        } else {
+            (params.div as u32) < 1 << limits.div_bits
+        };
+        if self.gclks[idx].0 != 0 || !div_valid {
+            return None;
+        }
+        let src_freq = self.source_freq(src);
+        if src_freq.0 == 0 {
+            return None;
+        }
+        self.state.set_gclk_div_and_source(
+            gclk,
+            params.div,
+            params.divsel,
+            src,
+            improve_duty_cycle,
+        );
+        let freq = params.output_freq(src_freq);
+        self.gclks[idx] = freq;
+        self.gclk_srcs[idx] = Some(src);
         Some(GClock { gclk, freq })
     }
 
@@ -331,6 +826,486 @@ impl GenericClockController {
     pub fn configure_standby(&mut self, gclk: ClockGenId, enable: bool) {
         self.state.configure_standby(gclk, enable)
     }
+
+    /// Returns the frequency of a running source, or zero
+    fn source_freq(&self, src: ClockSource) -> Hertz {
+        match src {
+            GCLKGEN1 => self.gclks[1],
+            // The input frequency depends on the generator
+            GCLKIN => Hertz(0),
+            _ => self.sources[u8::from(src) as usize],
+        }
+    }
+
+    /// Returns true if a generator, a DPLL or the RTC is fed by `src`
+    fn source_in_use(&self, src: ClockSource) -> bool {
+        let dpll_ref = match src {
+            XOSC0 => Some(DpllRef::Xosc0),
+            XOSC1 => Some(DpllRef::Xosc1),
+            XOSC32K => Some(DpllRef::Xosc32k),
+            _ => None,
+        };
+        let rtc = match self.rtc_source {
+            Some(RtcClockSource::XOSC1K | RtcClockSource::XOSC32K) => src == XOSC32K,
+            _ => false,
+        };
+        self.gclk_srcs.contains(&Some(src))
+            || (dpll_ref.is_some() && self.dpll_refs.contains(&dpll_ref))
+            || rtc
+    }
+
+    /// Returns true if a peripheral channel, a DPLL or another generator is fed
+    /// by `gclk`
+    fn gclk_in_use(&self, gclk: ClockGenId) -> bool {
+        gclk == GCLK0
+            || (gclk == GCLK1 && self.source_in_use(GCLKGEN1))
+            || self.clock_gens.contains(&Some(gclk))
+            || self.dpll_refs.contains(&Some(DpllRef::Gclk(gclk)))
+    }
+
+    /// Returns true if the source is switched to a safe clock by a clock
+    /// failure detector, directly or through a DPLL or GCLK1
+    fn source_on_safe_clock(&self, src: ClockSource) -> bool {
+        let dpll_ref = match src {
+            XOSC0 => return xosc_on_safe_clock(0),
+            XOSC1 => return xosc_on_safe_clock(1),
+            XOSC32K => return xosc32k_on_safe_clock(),
+            GCLKGEN1 => return self.gclk_on_safe_clock(GCLK1),
+            DPLL0 => self.dpll_refs[0],
+            DPLL1 => self.dpll_refs[1],
+            _ => None,
+        };
+        match dpll_ref {
+            Some(DpllRef::Xosc0) => xosc_on_safe_clock(0),
+            Some(DpllRef::Xosc1) => xosc_on_safe_clock(1),
+            Some(DpllRef::Xosc32k) => xosc32k_on_safe_clock(),
+            Some(DpllRef::Gclk(gclk)) => self.gclk_on_safe_clock(gclk),
+            None => false,
+        }
+    }
+
+    /// Returns true if the given generator is currently fed by the safe clock
+    /// of a clock failure detector, because one of the oscillators upstream
+    /// of it has failed.
+    pub fn gclk_on_safe_clock(&self, gclk: ClockGenId) -> bool {
+        self.gclk_srcs[u8::from(gclk) as usize].map_or(false, |src| self.source_on_safe_clock(src))
+    }
+
+    /// Returns true if the system is currently running on the safe clock of
+    /// a clock failure detector, i.e. if an oscillator feeding GCLK0 has
+    /// failed.
+    pub fn is_running_on_safe_clock(&self) -> bool {
+        self.gclk_on_safe_clock(GCLK0)
+    }
+
+    /// Enable an XOSC and wait for it to become ready.
+    ///
+    /// Returns the token and the cause if the configuration is invalid.
+    pub fn enable_xosc<X: XoscId>(
+        &mut self,
+        token: XoscToken<X>,
+        config: XoscConfig,
+    ) -> Result<Xosc<X>, (XoscToken<X>, ClockError)> {
+        let xosc = Xosc::enable(token, config)?;
+        self.sources[u8::from(X::SOURCE) as usize] = xosc.freq();
+        Ok(xosc)
+    }
+
+    /// Disable an XOSC and return its token.
+    ///
+    /// Returns the `Xosc` unchanged if it still feeds a generator or DPLL.
+    pub fn disable_xosc<X: XoscId>(&mut self, xosc: Xosc<X>) -> Result<XoscToken<X>, Xosc<X>> {
+        if self.source_in_use(X::SOURCE) {
+            return Err(xosc);
+        }
+        self.sources[u8::from(X::SOURCE) as usize] = Hertz(0);
+        Ok(xosc.disable())
+    }
+
+    /// Enable the XOSC32K and wait for it to become ready.
+    pub fn enable_xosc32k(&mut self, token: Xosc32kToken, config: Xosc32kConfig) -> Xosc32k {
+        let xosc32k = Xosc32k::enable(token, config);
+        self.sources[u8::from(XOSC32K) as usize] = xosc32k.freq();
+        xosc32k
+    }
+
+    /// Disable the XOSC32K and return its token.
+    ///
+    /// Returns the `Xosc32k` unchanged if it still feeds a generator, DPLL or
+    /// the RTC.
+    pub fn disable_xosc32k(&mut self, xosc32k: Xosc32k) -> Result<Xosc32kToken, Xosc32k> {
+        if self.source_in_use(XOSC32K) {
+            return Err(xosc32k);
+        }
+        self.sources[u8::from(XOSC32K) as usize] = Hertz(0);
+        Ok(xosc32k.disable())
+    }
+
+    /// Take the RTC clock token.
+    ///
+    /// The fixed 120MHz configurations select the 1 kHz output of their
+    /// 32 kHz oscillator for the RTC, and keep the token in the controller.
+    /// Returns `None` if it has already been taken, or if the controller was
+    /// created by [`with_clock_tokens`](Self::with_clock_tokens), which
+    /// returns it in [`Tokens`].
+    pub fn take_rtc_clock_token(&mut self) -> Option<RtcClockToken> {
+        self.rtc_token.take()
+    }
+
+    /// Select the clock of the RTC.
+    ///
+    /// The OSCULP32K outputs are always available. The XOSC32K outputs
+    /// require the oscillator to be running, and the 1 kHz output must have
+    /// been enabled with [`Xosc32kConfig::output_1k`]. Returns the token and
+    /// [`ClockError::NotRunning`] otherwise.
+    ///
+    /// While the returned [`RtcClock`] exists, the XOSC32K can't be disabled
+    /// if it clocks the RTC.
+    pub fn enable_rtc_clock(
+        &mut self,
+        token: RtcClockToken,
+        source: RtcClockSource,
+    ) -> Result<RtcClock, (RtcClockToken, ClockError)> {
+        let running = match source {
+            RtcClockSource::ULP1K | RtcClockSource::ULP32K => true,
+            RtcClockSource::XOSC1K => self.source_freq(XOSC32K).0 != 0 && xosc32k_1k_enabled(),
+            RtcClockSource::XOSC32K => self.source_freq(XOSC32K).0 != 0,
+        };
+        if !running {
+            return Err((token, ClockError::NotRunning));
+        }
+        self.rtc_source = Some(source);
+        Ok(RtcClock::enable(token, source))
+    }
+
+    /// Release the RTC clock and return its token.
+    ///
+    /// The RTC keeps running from the selected output until another one is
+    /// selected.
+    pub fn disable_rtc_clock(&mut self, clock: RtcClock) -> RtcClockToken {
+        self.rtc_source = None;
+        clock.disable()
+    }
+
+    /// Switch the DFLL48M to USB clock recovery mode.
+    ///
+    /// The DFLL is stopped during the switch: if GCLK0 is fed by the DFLL, it
+    /// runs from the OSCULP32K in the meantime. Other generators fed by the
+    /// DFLL stop for the duration of the switch.
+    pub fn enable_dfll_usb_recovery(&mut self, dfll: &mut Dfll, config: DfllUsbRecovery) {
+        self.state
+            .with_gclk0_off_dfll(|| dfll.enable_usb_recovery(&config));
+    }
+
+    /// Enable a DPLL from the given reference clock and wait for it to lock.
+    ///
+    /// When the reference is a [`Gclk`], the corresponding `FDPLL0`/`FDPLL1`
+    /// peripheral channel is connected to it. Returns the token and the cause
+    /// if the reference or output frequency is out of range, or if the
+    /// peripheral channel has already been configured.
+    pub fn enable_dpll<D: DpllId, S: DpllSource>(
+        &mut self,
+        token: DpllToken<D>,
+        reference: &S,
+        config: DpllConfig,
+    ) -> Result<Dpll<D>, (DpllToken<D>, ClockError)> {
+        let dpll_ref = reference.dpll_ref();
+        if let DpllRef::Gclk(gen) = dpll_ref {
+            let bits = 1u64 << u8::from(D::CLOCK);
+            if (self.used_clocks & bits) != 0 {
+                return Err((token, ClockError::AlreadyConfigured));
+            }
+            self.used_clocks |= bits;
+            self.clock_gens[u8::from(D::CLOCK) as usize] = Some(gen);
+            self.state.enable_clock_generator(D::CLOCK, gen);
+        }
+        match Dpll::enable(token, reference, config) {
+            Ok(dpll) => {
+                self.sources[u8::from(D::SOURCE) as usize] = dpll.freq();
+                self.dpll_refs[D::NUM] = Some(dpll_ref);
+                Ok(dpll)
+            }
+            Err(err) => {
+                if let DpllRef::Gclk(_) = dpll_ref {
+                    self.release_channel(D::CLOCK);
+                }
+                Err(err)
+            }
+        }
+    }
+
+    /// Disable a DPLL and return its token.
+    ///
+    /// Returns the `Dpll` unchanged if it still feeds a generator.
+    pub fn disable_dpll<D: DpllId>(&mut self, dpll: Dpll<D>) -> Result<DpllToken<D>, Dpll<D>> {
+        if self.source_in_use(D::SOURCE) {
+            return Err(dpll);
+        }
+        let dpll_ref = dpll.reference();
+        let token = dpll.disable();
+        if let DpllRef::Gclk(_) = dpll_ref {
+            self.release_channel(D::CLOCK);
+        }
+        self.sources[u8::from(D::SOURCE) as usize] = Hertz(0);
+        self.dpll_refs[D::NUM] = None;
+        Ok(token)
+    }
+
+    /// Disconnect a peripheral channel from its generator
+    fn release_channel(&mut self, clock: ClockId) {
+        let idx = u8::from(clock) as usize;
+        self.used_clocks &= !(1u64 << idx);
+        self.clock_gens[idx] = None;
+        self.state.disable_clock_generator(clock);
+    }
+
+    /// Temporarily connect the `FREQM_MSR` channel to `gclk`, for the
+    /// frequency meter to measure it
+    ///
+    /// Returns the generator frequency, or `None` if the generator is not
+    /// configured or the channel is already in use.
+    pub(crate) fn connect_freqm_msr(&mut self, gclk: ClockGenId) -> Option<Hertz> {
+        let freq = self.get_gclk(gclk)?.freq;
+        let bits: u64 = 1 << u8::from(ClockId::FREQM_MSR) as u64;
+        if (self.used_clocks & bits) != 0 {
+            return None;
+        }
+        self.used_clocks |= bits;
+        self.clock_gens[u8::from(ClockId::FREQM_MSR) as usize] = Some(gclk);
+        self.state.enable_clock_generator(ClockId::FREQM_MSR, gclk);
+        Some(freq)
+    }
+
+    /// Disconnect the `FREQM_MSR` channel connected by
+    /// [`connect_freqm_msr`](Self::connect_freqm_msr)
+    pub(crate) fn release_freqm_msr(&mut self) {
+        self.release_channel(ClockId::FREQM_MSR);
+    }
+
+    /// Disconnect a peripheral channel from its generator.
+    ///
+    /// Giving back the token proves that the peripherals of the channel are
+    /// no longer clocked, so that the generator can be reconfigured with
+    /// [`reconfigure_gclk`](Self::reconfigure_gclk).
+    pub fn disable_peripheral_clock<T: PeripheralClock>(&mut self, clock: T) {
+        let _ = clock;
+        self.release_channel(T::ID);
+    }
+
+    /// Change the divider and source of a running generator at runtime.
+    ///
+    /// `generator` is either a [`GClock`], or a typed [`Gclk`] that does not
+    /// take its input from a GCLK_IO pin. A generator owned by a typed `Gclk`
+    /// can only be changed through it, so that [`Gclk::freq`] stays up to
+    /// date.
+    ///
+    /// Every peripheral channel fed by the generator must either have been
+    /// disconnected with
+    /// [`disable_peripheral_clock`](Self::disable_peripheral_clock), or be
+    /// listed in `peripherals`. Once the generator runs at its new
+    /// frequency, `generator` and every peripheral are updated. Peripherals
+    /// sharing a channel, like TC2 and TC3, must all be listed.
+    ///
+    /// Changing GCLK0 changes the CPU frequency; a
+    /// [`Delay`](crate::delay::Delay) must be recreated afterwards.
+    ///
+    /// Returns [`ClockError::InUse`] if a channel, or one of the peripherals
+    /// sharing it, is not accounted for, if one of `peripherals` is not fed
+    /// by the generator, if the generator is the reference of a DPLL or the
+    /// source of another generator, or if a `GClock` is passed for a
+    /// generator owned by a typed `Gclk`.
+    pub fn reconfigure_gclk<R: ReconfigurableGclk>(
+        &mut self,
+        generator: &mut R,
+        divider: u16,
+        src: ClockSource,
+        improve_duty_cycle: bool,
+        peripherals: &mut [&mut dyn Reclock],
+    ) -> Result<Hertz, ClockError> {
+        let gclk = generator.gclock().gclk;
+        if !R::TYPED && self.typed_gclks & 1 << u8::from(gclk) != 0 {
+            return Err(ClockError::InUse);
+        }
+        let limits = gclk_limits(gclk);
+        if divider == 0 || divider as u32 >= 1 << limits.div_bits {
+            return Err(ClockError::InvalidDivider);
+        }
+        if gclk == GCLK1 && src == GCLKGEN1 {
+            return Err(ClockError::InvalidDivider);
+        }
+        let freq = self.source_freq(src).0 / divider as u32;
+        if freq == 0 || freq > limits.max_freq.0 {
+            return Err(ClockError::FrequencyOutOfRange);
+        }
+
+        if (gclk == GCLK1 && self.source_in_use(GCLKGEN1))
+            || self.dpll_refs.contains(&Some(DpllRef::Gclk(gclk)))
+        {
+            return Err(ClockError::InUse);
+        }
+        let fed = |clock: ClockId| self.clock_gens[u8::from(clock) as usize] == Some(gclk);
+        if !peripherals.iter().all(|p| fed(p.clock_id())) {
+            return Err(ClockError::InUse);
+        }
+        let accounted = |channel: usize| {
+            let users = peripherals
+                .iter()
+                .filter(|p| u8::from(p.clock_id()) as usize == channel)
+                .fold(0, |users, p| users | p.channel_users());
+            users == channel_users(channel)
+        };
+        let unaccounted = self
+            .clock_gens
+            .iter()
+            .enumerate()
+            .any(|(channel, gen)| *gen == Some(gclk) && !accounted(channel));
+        if unaccounted {
+            return Err(ClockError::InUse);
+        }
+
+        self.state
+            .modify_gclk_divider_and_source(gclk, divider, src, improve_duty_cycle);
+        let idx = u8::from(gclk) as usize;
+        let freq = Hertz(freq);
+        self.gclks[idx] = freq;
+        self.gclk_srcs[idx] = Some(src);
+        generator.gclock().freq = freq;
+        for peripheral in peripherals.iter_mut() {
+            peripheral.reclock(freq);
+        }
+        Ok(freq)
+    }
+
+    /// Check a generator divider and output frequency
+    fn validate_gclk<G: GclkId>(&self, freq: Hertz, divider: u16) -> Result<Hertz, ClockError> {
+        if divider == 0 || divider as u32 > G::MAX_DIV {
+            return Err(ClockError::InvalidDivider);
+        }
+        let out = freq.0 / divider as u32;
+        let max = if G::GEN == GCLK0 {
+            OSC120M_FREQ.0
+        } else {
+            GCLK_MAX_FREQ.0
+        };
+        if out == 0 || out > max {
+            return Err(ClockError::FrequencyOutOfRange);
+        }
+        if G::GEN != GCLK0 && self.gclks[u8::from(G::GEN) as usize].0 != 0 {
+            return Err(ClockError::AlreadyConfigured);
+        }
+        Ok(Hertz(out))
+    }
+
+    /// Configure a generator from a running source.
+    ///
+    /// `divider` is a linear divider; it may be up to 255, or 65535 for
+    /// GCLK1. `improve_duty_cycle` enables a 50/50 duty cycle for odd
+    /// dividers.
+    ///
+    /// GCLK0 runs the CPU, and its token can be used to switch it to another
+    /// source, as long as the result does not exceed 120MHz. Peripheral
+    /// clocks previously taken from GCLK0 keep the frequency they were
+    /// created with. The resulting `Gclk` can be changed again with
+    /// [`reconfigure_gclk`](Self::reconfigure_gclk).
+    ///
+    /// Returns the token and the cause if the divider or output frequency is
+    /// invalid, or if the generator was already configured through
+    /// [`configure_gclk_divider_and_source`](Self::configure_gclk_divider_and_source).
+    pub fn configure_gclk<G: GclkId, S: Source>(
+        &mut self,
+        token: GclkToken<G>,
+        source: &S,
+        divider: u16,
+        improve_duty_cycle: bool,
+    ) -> Result<Gclk<G>, (GclkToken<G>, ClockError)> {
+        let freq = match self.validate_gclk::<G>(source.freq(), divider) {
+            Ok(freq) => freq,
+            Err(err) => return Err((token, err)),
+        };
+        let src = source.source();
+        self.state
+            .set_gclk_divider_and_source(G::GEN, divider, src, improve_duty_cycle);
+        let idx = u8::from(G::GEN) as usize;
+        self.gclks[idx] = freq;
+        self.gclk_srcs[idx] = Some(src);
+        self.typed_gclks |= 1 << idx;
+        Ok(Gclk::new(token, freq, NoneT))
+    }
+
+    /// Configure a generator from the clock applied to its GCLK_IO pin.
+    ///
+    /// The pin is put in the GCLK_IO peripheral function and kept in the
+    /// returned [`Gclk`]. `freq` is the frequency of the external signal.
+    /// Returns the token, the pin and the cause on error, see
+    /// [`configure_gclk`](Self::configure_gclk).
+    #[allow(clippy::type_complexity)]
+    pub fn configure_gclk_in<G: GclkId, I: GclkIo<GclkId = G>>(
+        &mut self,
+        token: GclkToken<G>,
+        pin: impl AnyPin<Id = I>,
+        freq: impl Into<Hertz>,
+        divider: u16,
+        improve_duty_cycle: bool,
+    ) -> Result<Gclk<G, GclkIoPin<I>>, (GclkToken<G>, GclkIoPin<I>, ClockError)> {
+        let pin = into_gclk_io(pin);
+        let freq = match self.validate_gclk::<G>(freq.into(), divider) {
+            Ok(freq) => freq,
+            Err(err) => return Err((token, pin, err)),
+        };
+        self.state
+            .set_gclk_divider_and_source(G::GEN, divider, GCLKIN, improve_duty_cycle);
+        let idx = u8::from(G::GEN) as usize;
+        self.gclks[idx] = freq;
+        self.gclk_srcs[idx] = Some(GCLKIN);
+        self.typed_gclks |= 1 << idx;
+        Ok(Gclk::new(token, freq, pin))
+    }
+
+    /// Output a generator on one of its GCLK_IO pins.
+    ///
+    /// `generator` is either a [`GClock`] or a typed [`Gclk`], and is kept in
+    /// the returned [`GclkOut`] until it is [freed](GclkOut::free). The pin
+    /// is put in the GCLK_IO peripheral function.
+    ///
+    /// Returns the generator and the pin if the pin is not connected to the
+    /// generator, or if the generator takes its input from GCLK_IO.
+    #[allow(clippy::type_complexity)]
+    pub fn enable_gclk_out<I: GclkIo, G: AsRef<GClock>>(
+        &mut self,
+        generator: G,
+        pin: impl AnyPin<Id = I>,
+        config: GclkOutConfig,
+    ) -> Result<GclkOut<I, G>, (G, GclkIoPin<I>)> {
+        let pin = into_gclk_io(pin);
+        let gclk = generator.as_ref().gclk;
+        if gclk != I::GclkId::GEN || self.gclk_srcs[u8::from(gclk) as usize] == Some(GCLKIN) {
+            return Err((generator, pin));
+        }
+        let previous = self.state.gclk_output(gclk);
+        self.state.set_gclk_output(gclk, &config);
+        let freq = self.gclks[u8::from(gclk) as usize];
+        Ok(GclkOut::new(generator, freq, pin, config, previous))
+    }
+
+    /// Disable a generator and return its token, along with its GCLK_IO input
+    /// pin, if any.
+    ///
+    /// Returns the `Gclk` unchanged if it is GCLK0, or if it still feeds a
+    /// peripheral channel, a DPLL or another generator.
+    pub fn disable_gclk<G: GclkId, P: OptionalPin>(
+        &mut self,
+        gclk: Gclk<G, P>,
+    ) -> Result<(GclkToken<G>, P), Gclk<G, P>> {
+        if self.gclk_in_use(G::GEN) {
+            return Err(gclk);
+        }
+        self.state.disable_gclk(G::GEN);
+        let idx = u8::from(G::GEN) as usize;
+        self.gclks[idx] = Hertz(0);
+        self.gclk_srcs[idx] = None;
+        self.typed_gclks &= !(1 << idx);
+        Ok(gclk.free())
+    }
 }
 
 macro_rules! clock_generator {
@@ -369,6 +1344,14 @@ impl Into<Hertz> for $Type {
         self.freq
     }
 }
+
+$(#[$attr])*
+impl Sealed for $Type {}
+
+$(#[$attr])*
+impl PeripheralClock for $Type {
+    const ID: ClockId = ClockId::$clock;
+}
 )+
 
 impl GenericClockController {
@@ -392,6 +1375,7 @@ impl GenericClockController {
             return None;
         }
         self.used_clocks |= bits;
+        self.clock_gens[u8::from(ClockId::$clock) as usize] = Some(generator.gclk);
 
         self.state.enable_clock_generator(ClockId::$clock, generator.gclk);
         let freq = self.gclks[u8::from(generator.gclk) as usize];
@@ -457,6 +1441,21 @@ pub const OSC48M_FREQ: Hertz = Hertz(48_000_000);
 pub const OSC32K_FREQ: Hertz = Hertz(32_768);
 /// The frequency of the 120Mhz source.
 pub const OSC120M_FREQ: Hertz = Hertz(120_000_000);
+/// The maximum output frequency of a generator other than GCLK0.
+pub const GCLK_MAX_FREQ: Hertz = Hertz(200_000_000);
+
+/// Returns the divider and frequency limits of a generator, for use with the
+/// [`GclkParams`] solver.
+pub fn gclk_limits(gclk: ClockGenId) -> GclkLimits {
+    GclkLimits {
+        div_bits: if gclk == GCLK1 { 16 } else { 8 },
+        max_freq: if gclk == GCLK0 {
+            OSC120M_FREQ
+        } else {
+            GCLK_MAX_FREQ
+        },
+    }
+}
 
 fn set_flash_to_half_auto_wait_state(nvmctrl: &mut NVMCTRL) {
     // Zero indicates zero wait states, one indicates one wait state, etc.,
@@ -521,25 +1520,3 @@ fn configure_and_enable_dpll0(oscctrl: &mut OSCCTRL, gclk: &mut GCLK) {
         w.ondemand().clear_bit()
     });
 }
-
-#[cfg(feature = "usb")]
-/// Configure the dfll48m to calibrate against the 1Khz USB SOF reference.
-fn configure_usb_correction(oscctrl: &mut OSCCTRL) {
-    oscctrl.dfllmul.write(|w| unsafe {
-        w.cstep().bits(0x1)
-        .fstep().bits(0x1)
-        // scaling factor for 1Khz SOF signal.
-        .mul().bits((48_000_000u32 / 1000) as u16)
-    });
-    while oscctrl.dfllsync.read().dfllmul().bit_is_set() {}
-
-    oscctrl.dfllctrlb.write(|w| {
-        // closed loop mode
-        w.mode().set_bit()
-        // chill cycle disable
-        .ccdis().set_bit()
-        // usb correction
-        .usbcrm().set_bit()
-    });
-    while oscctrl.dfllsync.read().dfllctrlb().bit_is_set() {}
-}
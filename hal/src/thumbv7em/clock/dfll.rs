@@ -0,0 +1,155 @@
+//! Digital frequency locked loop (DFLL48M)
+//!
+//! The DFLL48M comes out of reset enabled, in open-loop mode, running at
+//! 48 MHz from its factory calibration. It drives GCLK0 until the clock tree
+//! is reconfigured.
+//!
+//! Without a 32 kHz crystal, the DFLL48M can run closed loop against the 1 kHz
+//! start of frame (SOF) packets sent by the USB host, which is accurate enough
+//! for a full speed USB device. See [`DfllUsbRecovery`].
+
+use crate::pac::oscctrl::RegisterBlock;
+use crate::pac::OSCCTRL;
+use crate::time::Hertz;
+use crate::typelevel::Sealed;
+
+use super::{ClockSource, Source, OSC48M_FREQ};
+
+//==============================================================================
+// DfllUsbRecovery
+//==============================================================================
+
+/// Multiplication factor between the 1 kHz USB SOF and the 48 MHz output
+const USB_SOF_MUL: u16 = 48_000;
+
+/// Configuration of the DFLL48M USB clock recovery mode
+///
+/// The step sizes bound how far the DFLL tuning can move on each SOF. Small
+/// steps lower the jitter once locked, at the cost of a slower lock. Until
+/// the host starts sending SOFs, the DFLL keeps running from its factory
+/// calibration.
+#[derive(Clone, Copy, Debug)]
+pub struct DfllUsbRecovery {
+    coarse_step: u8,
+    fine_step: u8,
+}
+
+impl DfllUsbRecovery {
+    /// Default configuration, with coarse and fine steps of 1
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            coarse_step: 1,
+            fine_step: 1,
+        }
+    }
+
+    /// Set the maximum step of the `COARSE` tuning value
+    ///
+    /// Only the low six bits are used.
+    #[inline]
+    pub fn coarse_step(mut self, step: u8) -> Self {
+        self.coarse_step = step & 0x3F;
+        self
+    }
+
+    /// Set the maximum step of the `FINE` tuning value
+    #[inline]
+    pub fn fine_step(mut self, step: u8) -> Self {
+        self.fine_step = step;
+        self
+    }
+}
+
+impl Default for DfllUsbRecovery {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Restart the DFLL48M in USB clock recovery mode
+///
+/// The DFLL is stopped during the sequence, so it must not feed GCLK0. The
+/// lock flags are never set before the host sends SOFs, so this does not wait
+/// for lock.
+pub(super) fn enable_usb_recovery(oscctrl: &RegisterBlock, config: &DfllUsbRecovery) {
+    oscctrl.dfllctrla.write(|w| w.enable().clear_bit());
+    while oscctrl.dfllsync.read().enable().bit_is_set() {}
+
+    oscctrl.dfllmul.write(|w| unsafe {
+        w.cstep().bits(config.coarse_step);
+        w.fstep().bits(config.fine_step);
+        w.mul().bits(USB_SOF_MUL)
+    });
+    while oscctrl.dfllsync.read().dfllmul().bit_is_set() {}
+
+    // Restart in open loop mode
+    oscctrl.dfllctrlb.reset();
+    while oscctrl.dfllsync.read().dfllctrlb().bit_is_set() {}
+    oscctrl.dfllctrla.write(|w| w.enable().set_bit());
+    while oscctrl.dfllsync.read().enable().bit_is_set() {}
+
+    // Reload the tuning value before closing the loop
+    let dfllval = oscctrl.dfllval.read().bits();
+    oscctrl.dfllval.write(|w| unsafe { w.bits(dfllval) });
+    while oscctrl.dfllsync.read().dfllval().bit_is_set() {}
+
+    oscctrl.dfllctrlb.write(|w| {
+        // USB clock recovery runs in closed loop mode
+        w.mode().set_bit();
+        w.usbcrm().set_bit();
+        // chill cycle disable, the SOF period is long enough
+        w.ccdis().set_bit()
+    });
+    while oscctrl.dfllsync.read().dfllctrlb().bit_is_set() {}
+    while oscctrl.status.read().dfllrdy().bit_is_clear() {}
+}
+
+//==============================================================================
+// Dfll
+//==============================================================================
+
+/// The running DFLL48M
+pub struct Dfll {
+    _private: (),
+}
+
+impl Dfll {
+    /// Create the DFLL48M instance
+    ///
+    /// # Safety
+    ///
+    /// There must never be more than one instance of the DFLL at any time.
+    #[inline]
+    pub(super) unsafe fn new() -> Self {
+        Self { _private: () }
+    }
+
+    /// Return the DFLL output frequency
+    #[inline]
+    pub fn freq(&self) -> Hertz {
+        OSC48M_FREQ
+    }
+
+    /// Restart the DFLL in USB clock recovery mode
+    pub(super) fn enable_usb_recovery(&mut self, config: &DfllUsbRecovery) {
+        // Safety: the DFLL instance has exclusive access to the DFLL registers
+        let oscctrl = unsafe { &*OSCCTRL::ptr() };
+        enable_usb_recovery(oscctrl, config);
+    }
+}
+
+impl Sealed for Dfll {}
+
+impl Source for Dfll {
+    #[inline]
+    fn source(&self) -> ClockSource {
+        ClockSource::DFLL
+    }
+
+    #[inline]
+    fn freq(&self) -> Hertz {
+        OSC48M_FREQ
+    }
+}
@@ -0,0 +1,370 @@
+//! Fractional digital phase locked loops (DPLL0 & DPLL1)
+//!
+//! Each DPLL multiplies a reference clock by `LDR + 1 + LDRFRAC / 32`. The
+//! reference can be the XOSC32K, one of the XOSCs (optionally divided by
+//! `2 * (DIV + 1)`), or a GCLK generator.
+//!
+//! The reference frequency must lie between 32 kHz and 3.2 MHz, and the output
+//! frequency between 96 MHz and 200 MHz.
+
+use core::marker::PhantomData;
+use core::ops::RangeInclusive;
+
+use crate::clock_params::{DpllLimits, DpllParams};
+use crate::gpio::OptionalPin;
+use crate::pac::oscctrl::DPLL;
+use crate::pac::OSCCTRL;
+use crate::time::Hertz;
+use crate::typelevel::Sealed;
+
+use super::{
+    ClockError, ClockGenId, ClockId, ClockSource, Gclk, GclkId, Source, Xosc, Xosc32k, XoscId,
+};
+
+/// Allowed range of the DPLL reference frequency
+pub const DPLL_REF_FREQ_RANGE: RangeInclusive<u32> = 32_000..=3_200_000;
+
+/// Allowed range of the DPLL output frequency
+pub const DPLL_OUT_FREQ_RANGE: RangeInclusive<u32> = 96_000_000..=200_000_000;
+
+/// Limits of the DPLLs, for use with the [`DpllParams`] solver
+pub const DPLL_LIMITS: DpllLimits = DpllLimits {
+    max_ldr: 0x1FFF,
+    ldrfrac_steps: 32,
+    max_xosc_div: 0x7FF,
+    ref_freq: DPLL_REF_FREQ_RANGE,
+    out_freq: DPLL_OUT_FREQ_RANGE,
+};
+
+//==============================================================================
+// DpllId
+//==============================================================================
+
+/// Type-level enum identifying one of the two DPLL instances
+pub trait DpllId: Sealed {
+    /// Index into `OSCCTRL.DPLL`
+    const NUM: usize;
+    /// Corresponding [`ClockSource`] variant
+    const SOURCE: ClockSource;
+    /// Peripheral channel used when a GCLK is the reference
+    const CLOCK: ClockId;
+}
+
+/// Type-level variant of [`DpllId`] for DPLL0
+pub enum Dpll0Id {}
+impl Sealed for Dpll0Id {}
+impl DpllId for Dpll0Id {
+    const NUM: usize = 0;
+    const SOURCE: ClockSource = ClockSource::DPLL0;
+    const CLOCK: ClockId = ClockId::FDPLL0;
+}
+
+/// Type-level variant of [`DpllId`] for DPLL1
+pub enum Dpll1Id {}
+impl Sealed for Dpll1Id {}
+impl DpllId for Dpll1Id {
+    const NUM: usize = 1;
+    const SOURCE: ClockSource = ClockSource::DPLL1;
+    const CLOCK: ClockId = ClockId::FDPLL1;
+}
+
+//==============================================================================
+// DpllSource
+//==============================================================================
+
+/// Reference clock selection of a DPLL
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DpllRef {
+    /// The DPLL peripheral channel, fed by the given GCLK generator
+    Gclk(ClockGenId),
+    /// The 32.768 kHz external crystal oscillator
+    Xosc32k,
+    /// XOSC0, after the DPLL XOSC divider
+    Xosc0,
+    /// XOSC1, after the DPLL XOSC divider
+    Xosc1,
+}
+
+/// A clock that can be used as the reference of a DPLL
+pub trait DpllSource: Sealed {
+    /// Reference clock selection for this source
+    fn dpll_ref(&self) -> DpllRef;
+    /// Frequency of the source
+    fn ref_freq(&self) -> Hertz;
+}
+
+impl<X: XoscId> DpllSource for Xosc<X> {
+    #[inline]
+    fn dpll_ref(&self) -> DpllRef {
+        match X::NUM {
+            0 => DpllRef::Xosc0,
+            _ => DpllRef::Xosc1,
+        }
+    }
+
+    #[inline]
+    fn ref_freq(&self) -> Hertz {
+        self.freq()
+    }
+}
+
+impl DpllSource for Xosc32k {
+    #[inline]
+    fn dpll_ref(&self) -> DpllRef {
+        DpllRef::Xosc32k
+    }
+
+    #[inline]
+    fn ref_freq(&self) -> Hertz {
+        self.freq()
+    }
+}
+
+impl<G: GclkId, P: OptionalPin> DpllSource for Gclk<G, P> {
+    #[inline]
+    fn dpll_ref(&self) -> DpllRef {
+        DpllRef::Gclk(G::GEN)
+    }
+
+    #[inline]
+    fn ref_freq(&self) -> Hertz {
+        self.freq()
+    }
+}
+
+//==============================================================================
+// DpllConfig
+//==============================================================================
+
+/// Configuration of a DPLL
+///
+/// The output frequency is `f_ref * (LDR + 1 + LDRFRAC / 32)`, where `f_ref`
+/// is the reference frequency after the optional XOSC divider.
+#[derive(Clone, Copy, Debug)]
+pub struct DpllConfig {
+    ldr: u16,
+    ldrfrac: u8,
+    xosc_div: u16,
+    on_demand: bool,
+    run_standby: bool,
+}
+
+impl DpllConfig {
+    /// Create a configuration with the given loop divider ratio
+    ///
+    /// `ldr` is the integer part (13 bits) and `ldrfrac` the fractional part
+    /// in 1/32 steps (5 bits).
+    #[inline]
+    pub fn new(ldr: u16, ldrfrac: u8) -> Self {
+        Self {
+            ldr,
+            ldrfrac,
+            xosc_div: 0,
+            on_demand: false,
+            run_standby: false,
+        }
+    }
+
+    /// Create a configuration from the settings computed by the
+    /// [`DpllParams`] solver
+    ///
+    /// ```no_run
+    /// # use atsamd_hal::clock::*;
+    /// # use atsamd_hal::clock_params::DpllParams;
+    /// # use atsamd_hal::time::U32Ext;
+    /// let params = DpllParams::new_xosc(12.mhz(), 120.mhz(), &DPLL_LIMITS).unwrap();
+    /// let config = DpllConfig::from_params(&params);
+    /// ```
+    #[inline]
+    pub fn from_params(params: &DpllParams) -> Self {
+        Self::new(params.ldr, params.ldrfrac).xosc_divider(params.xosc_div)
+    }
+
+    /// Divide an XOSC reference by `2 * (div + 1)`
+    ///
+    /// This setting is ignored for XOSC32K and GCLK references.
+    #[inline]
+    pub fn xosc_divider(mut self, div: u16) -> Self {
+        self.xosc_div = div;
+        self
+    }
+
+    /// Only run the DPLL when a peripheral requests it
+    #[inline]
+    pub fn on_demand(mut self, on_demand: bool) -> Self {
+        self.on_demand = on_demand;
+        self
+    }
+
+    /// Keep the DPLL running in standby sleep mode
+    ///
+    /// It is disabled by default.
+    #[inline]
+    pub fn run_standby(mut self, run_standby: bool) -> Self {
+        self.run_standby = run_standby;
+        self
+    }
+
+    /// Compute the reference frequency seen by the loop for a given source
+    fn ref_freq(&self, dpll_ref: DpllRef, freq: Hertz) -> Hertz {
+        match dpll_ref {
+            DpllRef::Xosc0 | DpllRef::Xosc1 => Hertz(freq.0 / (2 * (self.xosc_div as u32 + 1))),
+            DpllRef::Xosc32k | DpllRef::Gclk(_) => freq,
+        }
+    }
+
+    /// Compute the output frequency for a given loop reference frequency
+    fn out_freq(&self, ref_freq: Hertz) -> Hertz {
+        let f = ref_freq.0 as u64;
+        let out = f * (self.ldr as u64 + 1) + f * self.ldrfrac as u64 / 32;
+        Hertz(out as u32)
+    }
+
+    /// Check the register ranges and frequency limits, and return the output
+    /// frequency
+    fn validate(&self, dpll_ref: DpllRef, freq: Hertz) -> Result<Hertz, ClockError> {
+        if self.ldr > 0x1FFF || self.ldrfrac > 31 || self.xosc_div > 0x7FF {
+            return Err(ClockError::InvalidDivider);
+        }
+        let ref_freq = self.ref_freq(dpll_ref, freq);
+        if !DPLL_REF_FREQ_RANGE.contains(&ref_freq.0) {
+            return Err(ClockError::FrequencyOutOfRange);
+        }
+        let out = self.out_freq(ref_freq);
+        if !DPLL_OUT_FREQ_RANGE.contains(&out.0) {
+            return Err(ClockError::FrequencyOutOfRange);
+        }
+        Ok(out)
+    }
+}
+
+//==============================================================================
+// DpllToken
+//==============================================================================
+
+/// Singleton token granting the right to configure the DPLL `D`
+pub struct DpllToken<D: DpllId> {
+    dpll: PhantomData<D>,
+}
+
+impl<D: DpllId> DpllToken<D> {
+    /// Create a new token
+    ///
+    /// # Safety
+    ///
+    /// There must never be more than one instance of a token for a given DPLL
+    /// at any time.
+    #[inline]
+    pub(super) unsafe fn new() -> Self {
+        Self { dpll: PhantomData }
+    }
+
+    #[inline]
+    fn dpll(&self) -> &DPLL {
+        // Safety: the token has exclusive access to the registers of its DPLL
+        unsafe { &(*OSCCTRL::ptr()).dpll[D::NUM] }
+    }
+
+    fn enable(&mut self, dpll_ref: DpllRef, config: &DpllConfig) {
+        let dpll = self.dpll();
+        dpll.dpllratio.write(|w| unsafe {
+            w.ldr().bits(config.ldr);
+            w.ldrfrac().bits(config.ldrfrac)
+        });
+        while dpll.dpllsyncbusy.read().dpllratio().bit_is_set() {}
+        dpll.dpllctrlb.write(|w| {
+            match dpll_ref {
+                DpllRef::Gclk(_) => w.refclk().gclk(),
+                DpllRef::Xosc32k => w.refclk().xosc32(),
+                DpllRef::Xosc0 => w.refclk().xosc0(),
+                DpllRef::Xosc1 => w.refclk().xosc1(),
+            };
+            unsafe { w.div().bits(config.xosc_div) }
+        });
+        dpll.dpllctrla.write(|w| {
+            w.ondemand().bit(config.on_demand);
+            w.runstdby().bit(config.run_standby);
+            w.enable().set_bit()
+        });
+        while dpll.dpllsyncbusy.read().enable().bit_is_set() {}
+    }
+
+    fn is_locked(&self) -> bool {
+        let status = self.dpll().dpllstatus.read();
+        status.lock().bit_is_set() && status.clkrdy().bit_is_set()
+    }
+
+    fn disable(&mut self) {
+        let dpll = self.dpll();
+        dpll.dpllctrla.modify(|_, w| w.enable().clear_bit());
+        while dpll.dpllsyncbusy.read().enable().bit_is_set() {}
+    }
+}
+
+//==============================================================================
+// Dpll
+//==============================================================================
+
+/// An enabled and locked DPLL
+///
+/// Create it with
+/// [`GenericClockController::enable_dpll`](super::GenericClockController::enable_dpll).
+pub struct Dpll<D: DpllId> {
+    token: DpllToken<D>,
+    dpll_ref: DpllRef,
+    freq: Hertz,
+}
+
+impl<D: DpllId> Dpll<D> {
+    /// Validate the configuration, enable the DPLL and wait for lock
+    pub(super) fn enable<S: DpllSource>(
+        mut token: DpllToken<D>,
+        reference: &S,
+        config: DpllConfig,
+    ) -> Result<Self, (DpllToken<D>, ClockError)> {
+        let dpll_ref = reference.dpll_ref();
+        let freq = match config.validate(dpll_ref, reference.ref_freq()) {
+            Ok(freq) => freq,
+            Err(err) => return Err((token, err)),
+        };
+        token.enable(dpll_ref, &config);
+        while !token.is_locked() {}
+        Ok(Self {
+            token,
+            dpll_ref,
+            freq,
+        })
+    }
+
+    /// Disable the DPLL and return the token
+    pub(super) fn disable(mut self) -> DpllToken<D> {
+        self.token.disable();
+        self.token
+    }
+
+    /// Return the reference clock selection
+    #[inline]
+    pub fn reference(&self) -> DpllRef {
+        self.dpll_ref
+    }
+
+    /// Return the DPLL output frequency
+    #[inline]
+    pub fn freq(&self) -> Hertz {
+        self.freq
+    }
+}
+
+impl<D: DpllId> Sealed for Dpll<D> {}
+
+impl<D: DpllId> Source for Dpll<D> {
+    #[inline]
+    fn source(&self) -> ClockSource {
+        D::SOURCE
+    }
+
+    #[inline]
+    fn freq(&self) -> Hertz {
+        self.freq
+    }
+}
@@ -0,0 +1,329 @@
+//! Typed generic clock generators
+//!
+//! Each of the twelve GCLK generators is represented by a [`GclkId`] type. A
+//! [`GclkToken`] grants the right to configure a generator; once configured
+//! with [`GenericClockController::configure_gclk`], it becomes a [`Gclk`].
+//!
+//! Generators can be output on their GCLK_IO pins with
+//! [`GenericClockController::enable_gclk_out`], which returns a [`GclkOut`].
+//!
+//! [`GenericClockController::configure_gclk`]: super::GenericClockController::configure_gclk
+//! [`GenericClockController::enable_gclk_out`]: super::GenericClockController::enable_gclk_out
+
+use core::marker::PhantomData;
+
+use crate::gpio::*;
+use crate::time::Hertz;
+use crate::typelevel::{NoneT, Sealed};
+
+use super::{ClockGenId, GClock, GclkOutConfig, GenericClockController};
+
+//==============================================================================
+// GclkId
+//==============================================================================
+
+/// Type-level enum identifying one of the GCLK generators
+pub trait GclkId: Sealed {
+    /// Corresponding variant of [`ClockGenId`]
+    const GEN: ClockGenId;
+    /// Largest divider supported by the generator in `GENCTRL.DIV`
+    const MAX_DIV: u32;
+}
+
+macro_rules! gclk_ids {
+    ( $( ($Id:ident, $GEN:ident, $max_div:expr), )+ ) => {
+        $(
+            #[doc = concat!("Type-level variant of [`GclkId`] for ", stringify!($GEN))]
+            pub enum $Id {}
+            impl Sealed for $Id {}
+            impl GclkId for $Id {
+                const GEN: ClockGenId = ClockGenId::$GEN;
+                const MAX_DIV: u32 = $max_div;
+            }
+        )+
+    };
+}
+
+gclk_ids!(
+    (Gclk0Id, GCLK0, 255),
+    (Gclk1Id, GCLK1, 65535),
+    (Gclk2Id, GCLK2, 255),
+    (Gclk3Id, GCLK3, 255),
+    (Gclk4Id, GCLK4, 255),
+    (Gclk5Id, GCLK5, 255),
+    (Gclk6Id, GCLK6, 255),
+    (Gclk7Id, GCLK7, 255),
+    (Gclk8Id, GCLK8, 255),
+    (Gclk9Id, GCLK9, 255),
+    (Gclk10Id, GCLK10, 255),
+    (Gclk11Id, GCLK11, 255),
+);
+
+//==============================================================================
+// GclkToken
+//==============================================================================
+
+/// Singleton token granting the right to configure the generator `G`
+pub struct GclkToken<G: GclkId> {
+    gen: PhantomData<G>,
+}
+
+impl<G: GclkId> GclkToken<G> {
+    /// Create a new token
+    ///
+    /// # Safety
+    ///
+    /// There must never be more than one instance of a token for a given
+    /// generator at any time.
+    #[inline]
+    pub(super) unsafe fn new() -> Self {
+        Self { gen: PhantomData }
+    }
+}
+
+/// Set of [`GclkToken`]s for every generator
+pub struct GclkTokens {
+    pub gclk0: GclkToken<Gclk0Id>,
+    pub gclk1: GclkToken<Gclk1Id>,
+    pub gclk2: GclkToken<Gclk2Id>,
+    pub gclk3: GclkToken<Gclk3Id>,
+    pub gclk4: GclkToken<Gclk4Id>,
+    pub gclk5: GclkToken<Gclk5Id>,
+    pub gclk6: GclkToken<Gclk6Id>,
+    pub gclk7: GclkToken<Gclk7Id>,
+    pub gclk8: GclkToken<Gclk8Id>,
+    pub gclk9: GclkToken<Gclk9Id>,
+    pub gclk10: GclkToken<Gclk10Id>,
+    pub gclk11: GclkToken<Gclk11Id>,
+}
+
+impl GclkTokens {
+    /// # Safety
+    ///
+    /// Must only be called once, see [`GclkToken::new`].
+    #[inline]
+    pub(super) unsafe fn new() -> Self {
+        Self {
+            gclk0: GclkToken::new(),
+            gclk1: GclkToken::new(),
+            gclk2: GclkToken::new(),
+            gclk3: GclkToken::new(),
+            gclk4: GclkToken::new(),
+            gclk5: GclkToken::new(),
+            gclk6: GclkToken::new(),
+            gclk7: GclkToken::new(),
+            gclk8: GclkToken::new(),
+            gclk9: GclkToken::new(),
+            gclk10: GclkToken::new(),
+            gclk11: GclkToken::new(),
+        }
+    }
+}
+
+//==============================================================================
+// Gclk
+//==============================================================================
+
+/// A configured and running generic clock generator
+///
+/// Use [`AsRef<GClock>`](GClock) to pass the generator to the
+/// peripheral clock methods of
+/// [`GenericClockController`](super::GenericClockController), e.g.
+/// `clocks.sercom0_core(gclk2.as_ref())`.
+///
+/// The type parameter `P` holds the GCLK_IO pin when the generator takes its
+/// input from a pin (see
+/// [`configure_gclk_in`](super::GenericClockController::configure_gclk_in)),
+/// and is [`NoneT`] otherwise.
+pub struct Gclk<G: GclkId, P: OptionalPin = NoneT> {
+    token: GclkToken<G>,
+    clock: GClock,
+    pin: P,
+}
+
+impl<G: GclkId, P: OptionalPin> Gclk<G, P> {
+    #[inline]
+    pub(super) fn new(token: GclkToken<G>, freq: Hertz, pin: P) -> Self {
+        let clock = GClock { gclk: G::GEN, freq };
+        Self { token, clock, pin }
+    }
+
+    #[inline]
+    pub(super) fn free(self) -> (GclkToken<G>, P) {
+        (self.token, self.pin)
+    }
+
+    /// Return the output frequency of the generator
+    #[inline]
+    pub fn freq(&self) -> Hertz {
+        self.clock.freq
+    }
+}
+
+impl<G: GclkId, P: OptionalPin> AsRef<GClock> for Gclk<G, P> {
+    #[inline]
+    fn as_ref(&self) -> &GClock {
+        &self.clock
+    }
+}
+
+/// A generator that
+/// [`GenericClockController::reconfigure_gclk`](super::GenericClockController::reconfigure_gclk)
+/// can change: a [`GClock`], or a [`Gclk`] that does not take its input from
+/// a GCLK_IO pin
+pub trait ReconfigurableGclk: Sealed {
+    #[doc(hidden)]
+    const TYPED: bool;
+    #[doc(hidden)]
+    fn gclock(&mut self) -> &mut GClock;
+}
+
+impl Sealed for GClock {}
+
+impl ReconfigurableGclk for GClock {
+    const TYPED: bool = false;
+    #[inline]
+    fn gclock(&mut self) -> &mut GClock {
+        self
+    }
+}
+
+impl<G: GclkId> ReconfigurableGclk for Gclk<G> {
+    const TYPED: bool = true;
+    #[inline]
+    fn gclock(&mut self) -> &mut GClock {
+        &mut self.clock
+    }
+}
+
+//==============================================================================
+// GclkIo
+//==============================================================================
+
+/// Maps a [`PinId`] to the generator whose GCLK_IO signal it carries
+///
+/// GCLK_IO is peripheral function M on SAMx5x chips.
+pub trait GclkIo: PinId {
+    /// Generator connected to this pin
+    type GclkId: GclkId;
+}
+
+/// GCLK_IO pin type, in the correct peripheral function
+pub type GclkIoPin<I> = Pin<I, AlternateM>;
+
+/// Convert any [`AnyPin`] for a [`GclkIo`] pin into a [`GclkIoPin`]
+#[inline]
+pub(super) fn into_gclk_io<I: GclkIo>(pin: impl AnyPin<Id = I>) -> GclkIoPin<I> {
+    pin.into().into_alternate()
+}
+
+//==============================================================================
+// GclkOut
+//==============================================================================
+
+/// A generator output on one of its GCLK_IO pins
+///
+/// Create it with
+/// [`GenericClockController::enable_gclk_out`](super::GenericClockController::enable_gclk_out).
+pub struct GclkOut<I: GclkIo, G: AsRef<GClock>> {
+    generator: G,
+    freq: Hertz,
+    pin: GclkIoPin<I>,
+    config: GclkOutConfig,
+    previous: GclkOutConfig,
+}
+
+impl<I: GclkIo, G: AsRef<GClock>> GclkOut<I, G> {
+    #[inline]
+    pub(super) fn new(
+        generator: G,
+        freq: Hertz,
+        pin: GclkIoPin<I>,
+        config: GclkOutConfig,
+        previous: GclkOutConfig,
+    ) -> Self {
+        Self {
+            generator,
+            freq,
+            pin,
+            config,
+            previous,
+        }
+    }
+
+    /// Return the frequency of the output clock, i.e. the frequency of the
+    /// generator source divided by the generator divider
+    #[inline]
+    pub fn freq(&self) -> Hertz {
+        self.freq
+    }
+
+    /// Enable or disable the clock output; while disabled, the pin is driven
+    /// to the off value
+    #[inline]
+    pub fn set_output_enable(&mut self, clocks: &mut GenericClockController, enable: bool) {
+        self.config.output_enable = enable;
+        clocks.state.set_gclk_output(I::GclkId::GEN, &self.config);
+    }
+
+    /// Set the level of the pin while the generator or its output is off
+    #[inline]
+    pub fn set_off_value(&mut self, clocks: &mut GenericClockController, high: bool) {
+        self.config.off_value = high;
+        clocks.state.set_gclk_output(I::GclkId::GEN, &self.config);
+    }
+
+    /// Restore the previous output settings of the generator, and return the
+    /// generator and the pin
+    #[inline]
+    pub fn free(self, clocks: &mut GenericClockController) -> (G, GclkIoPin<I>) {
+        clocks.state.set_gclk_output(I::GclkId::GEN, &self.previous);
+        (self.generator, self.pin)
+    }
+}
+
+macro_rules! gclk_io {
+    ( $( $( #[$attr:meta] )? ($PinId:ident, $GclkId:ident), )+ ) => {
+        $(
+            $( #[$attr] )?
+            impl GclkIo for $PinId {
+                type GclkId = $GclkId;
+            }
+        )+
+    };
+}
+
+gclk_io!(
+    (PA10, Gclk4Id),
+    (PA11, Gclk5Id),
+    (PA14, Gclk0Id),
+    (PA15, Gclk1Id),
+    (PA16, Gclk2Id),
+    (PA17, Gclk3Id),
+    (PA27, Gclk1Id),
+    (PA30, Gclk0Id),
+    (PB10, Gclk4Id),
+    (PB11, Gclk5Id),
+    #[cfg(feature = "min-samd51j")]
+    (PB12, Gclk6Id),
+    #[cfg(feature = "min-samd51j")]
+    (PB13, Gclk7Id),
+    #[cfg(feature = "min-samd51j")]
+    (PB14, Gclk0Id),
+    #[cfg(feature = "min-samd51j")]
+    (PB15, Gclk1Id),
+    #[cfg(feature = "min-samd51j")]
+    (PB16, Gclk2Id),
+    #[cfg(feature = "min-samd51j")]
+    (PB17, Gclk3Id),
+    #[cfg(feature = "min-samd51n")]
+    (PB18, Gclk4Id),
+    #[cfg(feature = "min-samd51n")]
+    (PB19, Gclk5Id),
+    #[cfg(feature = "min-samd51n")]
+    (PB20, Gclk6Id),
+    #[cfg(feature = "min-samd51n")]
+    (PB21, Gclk7Id),
+    (PB22, Gclk0Id),
+    (PB23, Gclk1Id),
+);
@@ -0,0 +1,443 @@
+//! 32 kHz oscillators (XOSC32K & OSCULP32K) and RTC clock selection
+//!
+//! The XOSC32K is driven by a 32.768 kHz crystal between XIN32 and XOUT32
+//! (PA00/PA01), or by an external clock applied to XIN32. Its 32 kHz output is
+//! always enabled, the 1 kHz output is optional.
+//!
+//! The XOSC32K has a clock failure detector (CFD). When enabled, it replaces
+//! the XOSC32K output with the OSCULP32K if the crystal stops, and flags the
+//! failure. See [`Xosc32kConfig::clock_failure_detection`].
+//!
+//! The OSCULP32K is always running and needs no configuration.
+//!
+//! The RTC is clocked by the 1 kHz or 32 kHz output of one of the two
+//! oscillators, selected in `OSC32KCTRL.RTCCTRL`. The selection is made by
+//! turning an [`RtcClockToken`] into an [`RtcClock`], which is then consumed
+//! by the RTC constructors, e.g.
+//! [`Rtc::count32_mode_with_clock`](crate::rtc::Rtc::count32_mode_with_clock).
+
+use crate::pac::osc32kctrl::{RegisterBlock, XOSC32K};
+use crate::pac::OSC32KCTRL;
+use crate::time::Hertz;
+use crate::typelevel::Sealed;
+
+pub use crate::pac::osc32kctrl::rtcctrl::RTCSEL_A as RtcClockSource;
+pub use crate::pac::osc32kctrl::xosc32k::CGM_A as Xosc32kGain;
+pub use crate::pac::osc32kctrl::xosc32k::STARTUP_A as Xosc32kStartUpDelay;
+
+use super::{ClockSource, Source, XoscMode, OSC32K_FREQ};
+
+/// Frequency of the 1 kHz outputs
+pub const OSC1K_FREQ: Hertz = Hertz(1024);
+
+//==============================================================================
+// Xosc32kConfig
+//==============================================================================
+
+/// Configuration of the XOSC32K
+#[derive(Clone, Copy, Debug)]
+pub struct Xosc32kConfig {
+    mode: XoscMode,
+    start_up: Xosc32kStartUpDelay,
+    gain: Xosc32kGain,
+    output_1k: bool,
+    on_demand: bool,
+    run_standby: bool,
+    cfd: Option<bool>,
+}
+
+impl Xosc32kConfig {
+    /// Configuration for a 32.768 kHz crystal
+    #[inline]
+    pub fn crystal() -> Self {
+        Self::new(XoscMode::Crystal)
+    }
+
+    /// Configuration for an external 32.768 kHz clock signal on XIN32
+    #[inline]
+    pub fn external_clock() -> Self {
+        Self::new(XoscMode::ExternalClock)
+    }
+
+    #[inline]
+    fn new(mode: XoscMode) -> Self {
+        Self {
+            mode,
+            start_up: Xosc32kStartUpDelay::CYCLE65536,
+            gain: Xosc32kGain::XT,
+            output_1k: true,
+            on_demand: false,
+            run_standby: false,
+            cfd: None,
+        }
+    }
+
+    /// Set the number of oscillator cycles to wait before the XOSC32K is
+    /// reported ready
+    #[inline]
+    pub fn start_up_delay(mut self, delay: Xosc32kStartUpDelay) -> Self {
+        self.start_up = delay;
+        self
+    }
+
+    /// Set the control gain mode of the crystal driver
+    ///
+    /// The default, `XT`, suits standard crystals. `HS` drives crystals with
+    /// a higher equivalent series resistance, at a higher current.
+    #[inline]
+    pub fn gain(mut self, gain: Xosc32kGain) -> Self {
+        self.gain = gain;
+        self
+    }
+
+    /// Enable the 1 kHz output, which can clock the RTC
+    ///
+    /// It is enabled by default.
+    #[inline]
+    pub fn output_1k(mut self, enable: bool) -> Self {
+        self.output_1k = enable;
+        self
+    }
+
+    /// Only run the oscillator when a peripheral requests it
+    #[inline]
+    pub fn on_demand(mut self, on_demand: bool) -> Self {
+        self.on_demand = on_demand;
+        self
+    }
+
+    /// Keep the oscillator running in standby sleep mode
+    ///
+    /// It is disabled by default.
+    #[inline]
+    pub fn run_standby(mut self, run_standby: bool) -> Self {
+        self.run_standby = run_standby;
+        self
+    }
+
+    /// Enable the clock failure detector
+    ///
+    /// On failure, the XOSC32K output is replaced by the OSCULP32K, divided
+    /// by two if `safe_clock_div2` is set.
+    #[inline]
+    pub fn clock_failure_detection(mut self, safe_clock_div2: bool) -> Self {
+        self.cfd = Some(safe_clock_div2);
+        self
+    }
+}
+
+//==============================================================================
+// Xosc32kToken
+//==============================================================================
+
+/// Singleton token granting the right to configure the XOSC32K
+pub struct Xosc32kToken {
+    _private: (),
+}
+
+impl Xosc32kToken {
+    /// Create a new token
+    ///
+    /// # Safety
+    ///
+    /// There must never be more than one instance of the token at any time.
+    #[inline]
+    pub(super) unsafe fn new() -> Self {
+        Self { _private: () }
+    }
+
+    #[inline]
+    fn osc32kctrl(&self) -> &RegisterBlock {
+        // Safety: the token has exclusive access to the XOSC32K registers
+        unsafe { &*OSC32KCTRL::ptr() }
+    }
+
+    #[inline]
+    fn xosc32k(&self) -> &XOSC32K {
+        &self.osc32kctrl().xosc32k
+    }
+
+    fn enable(&mut self, config: &Xosc32kConfig) {
+        self.xosc32k().write(|w| {
+            w.startup().variant(config.start_up);
+            w.xtalen().bit(config.mode == XoscMode::Crystal);
+            w.cgm().variant(config.gain);
+            w.en32k().set_bit();
+            w.en1k().bit(config.output_1k);
+            w.ondemand().bit(config.on_demand);
+            w.runstdby().bit(config.run_standby);
+            w.enable().set_bit()
+        });
+    }
+
+    fn is_ready(&self) -> bool {
+        self.osc32kctrl().status.read().xosc32krdy().bit_is_set()
+    }
+
+    /// The CFD can only be enabled once the oscillator is ready
+    fn enable_cfd(&mut self, config: &Xosc32kConfig) {
+        if let Some(div2) = config.cfd {
+            self.osc32kctrl().cfdctrl.write(|w| {
+                w.cfdpresc().bit(div2);
+                w.cfden().set_bit()
+            });
+        }
+    }
+
+    fn disable(&mut self) {
+        let osc32kctrl = self.osc32kctrl();
+        osc32kctrl.intenclr.write(|w| w.xosc32kfail().set_bit());
+        osc32kctrl.cfdctrl.write(|w| w.cfden().clear_bit());
+        self.xosc32k().modify(|_, w| w.enable().clear_bit());
+    }
+}
+
+/// Returns true if the output of the XOSC32K has been switched to the safe
+/// clock by its clock failure detector
+pub(super) fn xosc32k_on_safe_clock() -> bool {
+    // Safety: read-only access to a status register
+    unsafe { (*OSC32KCTRL::ptr()).status.read().xosc32ksw().bit_is_set() }
+}
+
+/// Returns true if the 1 kHz output of the XOSC32K is enabled
+pub(super) fn xosc32k_1k_enabled() -> bool {
+    // Safety: read-only access to a configuration register
+    unsafe { (*OSC32KCTRL::ptr()).xosc32k.read().en1k().bit_is_set() }
+}
+
+//==============================================================================
+// Xosc32k
+//==============================================================================
+
+/// The enabled XOSC32K
+///
+/// Create it with
+/// [`GenericClockController::enable_xosc32k`](super::GenericClockController::enable_xosc32k).
+pub struct Xosc32k {
+    token: Xosc32kToken,
+    output_1k: bool,
+}
+
+impl Xosc32k {
+    /// Enable the oscillator and wait until it is ready
+    pub(super) fn enable(mut token: Xosc32kToken, config: Xosc32kConfig) -> Self {
+        token.enable(&config);
+        while !token.is_ready() {}
+        token.enable_cfd(&config);
+        Self {
+            token,
+            output_1k: config.output_1k,
+        }
+    }
+
+    /// Disable the oscillator and return the token
+    pub(super) fn disable(mut self) -> Xosc32kToken {
+        self.token.disable();
+        self.token
+    }
+
+    /// Return the oscillator frequency
+    #[inline]
+    pub fn freq(&self) -> Hertz {
+        OSC32K_FREQ
+    }
+
+    /// Return true if the 1 kHz output is enabled
+    #[inline]
+    pub fn has_1k_output(&self) -> bool {
+        self.output_1k
+    }
+
+    /// Enable the `XOSC32KFAIL` interrupt of the clock failure detector
+    #[inline]
+    pub fn enable_failure_interrupt(&mut self) {
+        self.token
+            .osc32kctrl()
+            .intenset
+            .write(|w| w.xosc32kfail().set_bit());
+    }
+
+    /// Disable the `XOSC32KFAIL` interrupt of the clock failure detector
+    #[inline]
+    pub fn disable_failure_interrupt(&mut self) {
+        self.token
+            .osc32kctrl()
+            .intenclr
+            .write(|w| w.xosc32kfail().set_bit());
+    }
+
+    /// Return true if the clock failure detector has flagged a failure since
+    /// the flag was last cleared
+    #[inline]
+    pub fn has_failed(&self) -> bool {
+        self.token
+            .osc32kctrl()
+            .intflag
+            .read()
+            .xosc32kfail()
+            .bit_is_set()
+    }
+
+    /// Clear the failure flag, and the pending interrupt
+    #[inline]
+    pub fn clear_failure(&mut self) {
+        self.token
+            .osc32kctrl()
+            .intflag
+            .write(|w| w.xosc32kfail().set_bit());
+    }
+
+    /// Return true if the oscillator is currently detected as failing
+    #[inline]
+    pub fn is_failing(&self) -> bool {
+        self.token
+            .osc32kctrl()
+            .status
+            .read()
+            .xosc32kfail()
+            .bit_is_set()
+    }
+
+    /// Return true if the output is currently switched to the safe clock
+    #[inline]
+    pub fn is_on_safe_clock(&self) -> bool {
+        xosc32k_on_safe_clock()
+    }
+
+    /// Switch the output back from the safe clock to the XOSC32K
+    ///
+    /// This should only be done once the oscillator runs again, i.e. when
+    /// [`is_failing`](Self::is_failing) returns false.
+    #[inline]
+    pub fn switch_back(&mut self) {
+        self.token
+            .osc32kctrl()
+            .cfdctrl
+            .modify(|_, w| w.swback().set_bit());
+    }
+}
+
+impl Sealed for Xosc32k {}
+
+impl Source for Xosc32k {
+    #[inline]
+    fn source(&self) -> ClockSource {
+        ClockSource::XOSC32K
+    }
+
+    #[inline]
+    fn freq(&self) -> Hertz {
+        OSC32K_FREQ
+    }
+}
+
+//==============================================================================
+// OscUlp32k
+//==============================================================================
+
+/// The always-on ultra low power 32 kHz oscillator
+#[derive(Clone, Copy)]
+pub struct OscUlp32k {
+    _private: (),
+}
+
+impl OscUlp32k {
+    #[inline]
+    pub(super) fn new() -> Self {
+        Self { _private: () }
+    }
+
+    /// Return the oscillator frequency
+    #[inline]
+    pub fn freq(&self) -> Hertz {
+        OSC32K_FREQ
+    }
+}
+
+impl Sealed for OscUlp32k {}
+
+impl Source for OscUlp32k {
+    #[inline]
+    fn source(&self) -> ClockSource {
+        ClockSource::OSCULP32K
+    }
+
+    #[inline]
+    fn freq(&self) -> Hertz {
+        OSC32K_FREQ
+    }
+}
+
+//==============================================================================
+// RtcClock
+//==============================================================================
+
+/// Singleton token granting the right to select the RTC clock
+pub struct RtcClockToken {
+    _private: (),
+}
+
+impl RtcClockToken {
+    /// Create a new token
+    ///
+    /// # Safety
+    ///
+    /// There must never be more than one instance of the token at any time.
+    #[inline]
+    pub(super) unsafe fn new() -> Self {
+        Self { _private: () }
+    }
+
+    #[inline]
+    fn osc32kctrl(&self) -> &RegisterBlock {
+        // Safety: the token has exclusive access to the RTCCTRL register
+        unsafe { &*OSC32KCTRL::ptr() }
+    }
+}
+
+/// The selected RTC clock
+///
+/// Create it with
+/// [`GenericClockController::enable_rtc_clock`](super::GenericClockController::enable_rtc_clock),
+/// and pass it to the RTC constructor. Its frequency is the one actually fed
+/// to the RTC.
+pub struct RtcClock {
+    token: RtcClockToken,
+    source: RtcClockSource,
+}
+
+impl RtcClock {
+    /// Select the RTC clock
+    pub(super) fn enable(token: RtcClockToken, source: RtcClockSource) -> Self {
+        token
+            .osc32kctrl()
+            .rtcctrl
+            .write(|w| w.rtcsel().variant(source));
+        Self { token, source }
+    }
+
+    /// Return the token
+    pub(super) fn disable(self) -> RtcClockToken {
+        self.token
+    }
+
+    /// Return the selected oscillator output
+    #[inline]
+    pub fn source(&self) -> RtcClockSource {
+        self.source
+    }
+
+    /// Return the frequency of the selected output
+    #[inline]
+    pub fn freq(&self) -> Hertz {
+        rtc_clock_freq(self.source)
+    }
+}
+
+/// Return the frequency of an RTC clock output
+fn rtc_clock_freq(source: RtcClockSource) -> Hertz {
+    match source {
+        RtcClockSource::ULP1K | RtcClockSource::XOSC1K => OSC1K_FREQ,
+        RtcClockSource::ULP32K | RtcClockSource::XOSC32K => OSC32K_FREQ,
+    }
+}
@@ -0,0 +1,390 @@
+//! External multipurpose crystal oscillators (XOSC0 & XOSC1)
+//!
+//! Each XOSC can be driven either by a crystal connected between XIN and XOUT,
+//! or by an external clock signal applied to XIN. The XIN/XOUT pins are taken
+//! over by the oscillator while it is enabled; they are PA14/PA15 for XOSC0
+//! and PB22/PB23 for XOSC1.
+//!
+//! Each XOSC has a clock failure detector (CFD). When enabled, it monitors
+//! the oscillator against the DFLL48M and, if the XOSC stops, replaces its
+//! output with a safe clock derived from the DFLL48M and flags the failure.
+//! See [`XoscConfig::clock_failure_detection`].
+
+use core::marker::PhantomData;
+
+use crate::pac::oscctrl::{RegisterBlock, XOSCCTRL};
+use crate::pac::OSCCTRL;
+use crate::time::Hertz;
+use crate::typelevel::Sealed;
+
+pub use crate::pac::oscctrl::xoscctrl::CFDPRESC_A as CfdPrescaler;
+pub use crate::pac::oscctrl::xoscctrl::STARTUP_A as StartUpDelay;
+
+use super::{ClockError, ClockSource, Source};
+
+//==============================================================================
+// XoscId
+//==============================================================================
+
+/// Type-level enum identifying one of the two XOSC instances
+pub trait XoscId: Sealed {
+    /// Index of the `OSCCTRL.XOSCCTRL` register
+    const NUM: usize;
+    /// Corresponding [`ClockSource`] variant
+    const SOURCE: ClockSource;
+}
+
+/// Type-level variant of [`XoscId`] for XOSC0
+pub enum Xosc0Id {}
+impl Sealed for Xosc0Id {}
+impl XoscId for Xosc0Id {
+    const NUM: usize = 0;
+    const SOURCE: ClockSource = ClockSource::XOSC0;
+}
+
+/// Type-level variant of [`XoscId`] for XOSC1
+pub enum Xosc1Id {}
+impl Sealed for Xosc1Id {}
+impl XoscId for Xosc1Id {
+    const NUM: usize = 1;
+    const SOURCE: ClockSource = ClockSource::XOSC1;
+}
+
+//==============================================================================
+// XoscConfig
+//==============================================================================
+
+/// Signal connected to the XOSC
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum XoscMode {
+    /// A crystal between XIN and XOUT
+    Crystal,
+    /// An external clock signal on XIN, XOUT is free to be used as GPIO
+    ExternalClock,
+}
+
+/// Configuration of an XOSC
+///
+/// Build it with [`XoscConfig::crystal`] or [`XoscConfig::external_clock`]
+/// and adjust the defaults with the builder methods.
+#[derive(Clone, Copy, Debug)]
+pub struct XoscConfig {
+    freq: Hertz,
+    mode: XoscMode,
+    start_up: StartUpDelay,
+    on_demand: bool,
+    run_standby: bool,
+    cfd: Option<(CfdPrescaler, bool)>,
+}
+
+impl XoscConfig {
+    /// Configuration for a crystal of the given frequency
+    ///
+    /// Crystals between 8 MHz and 48 MHz are supported.
+    #[inline]
+    pub fn crystal(freq: impl Into<Hertz>) -> Self {
+        Self::new(freq.into(), XoscMode::Crystal)
+    }
+
+    /// Configuration for an external clock signal of the given frequency
+    ///
+    /// External clocks up to 48 MHz are supported.
+    #[inline]
+    pub fn external_clock(freq: impl Into<Hertz>) -> Self {
+        Self::new(freq.into(), XoscMode::ExternalClock)
+    }
+
+    #[inline]
+    fn new(freq: Hertz, mode: XoscMode) -> Self {
+        Self {
+            freq,
+            mode,
+            start_up: StartUpDelay::CYCLE8192,
+            on_demand: false,
+            run_standby: false,
+            cfd: None,
+        }
+    }
+
+    /// Set the number of oscillator cycles to wait before the XOSC is reported
+    /// ready
+    #[inline]
+    pub fn start_up_delay(mut self, delay: StartUpDelay) -> Self {
+        self.start_up = delay;
+        self
+    }
+
+    /// Only run the oscillator when a peripheral requests it
+    #[inline]
+    pub fn on_demand(mut self, on_demand: bool) -> Self {
+        self.on_demand = on_demand;
+        self
+    }
+
+    /// Keep the oscillator running in standby sleep mode
+    ///
+    /// It is disabled by default.
+    #[inline]
+    pub fn run_standby(mut self, run_standby: bool) -> Self {
+        self.run_standby = run_standby;
+        self
+    }
+
+    /// Enable the clock failure detector
+    ///
+    /// On failure, the XOSC output is replaced by the DFLL48M divided by
+    /// `safe_clock`. With `switch_back`, the XOSC output is restored
+    /// automatically once the oscillator runs again.
+    #[inline]
+    pub fn clock_failure_detection(mut self, safe_clock: CfdPrescaler, switch_back: bool) -> Self {
+        self.cfd = Some((safe_clock, switch_back));
+        self
+    }
+
+    /// Return the configured frequency
+    #[inline]
+    pub fn freq(&self) -> Hertz {
+        self.freq
+    }
+
+    /// Check the frequency against the limits of the selected mode
+    fn validate(&self) -> Result<(), ClockError> {
+        let range = match self.mode {
+            XoscMode::Crystal => 8_000_000..=48_000_000,
+            XoscMode::ExternalClock => 1..=48_000_000,
+        };
+        if range.contains(&self.freq.0) {
+            Ok(())
+        } else {
+            Err(ClockError::FrequencyOutOfRange)
+        }
+    }
+
+    /// Return the `(IMULT, IPTAT)` current settings recommended for the
+    /// crystal frequency (see the XOSC electrical characteristics)
+    fn current(&self) -> (u8, u8) {
+        match self.freq.0 {
+            f if f <= 8_000_000 => (3, 2),
+            f if f <= 16_000_000 => (4, 3),
+            f if f <= 24_000_000 => (5, 3),
+            _ => (6, 3),
+        }
+    }
+}
+
+//==============================================================================
+// XoscToken
+//==============================================================================
+
+/// Singleton token granting the right to configure the XOSC `X`
+pub struct XoscToken<X: XoscId> {
+    xosc: PhantomData<X>,
+}
+
+impl<X: XoscId> XoscToken<X> {
+    /// Create a new token
+    ///
+    /// # Safety
+    ///
+    /// There must never be more than one instance of a token for a given XOSC
+    /// at any time.
+    #[inline]
+    pub(super) unsafe fn new() -> Self {
+        Self { xosc: PhantomData }
+    }
+
+    #[inline]
+    fn oscctrl(&self) -> &RegisterBlock {
+        // Safety: the token has exclusive access to the registers of its XOSC
+        unsafe { &*OSCCTRL::ptr() }
+    }
+
+    #[inline]
+    fn xoscctrl(&self) -> &XOSCCTRL {
+        &self.oscctrl().xoscctrl[X::NUM]
+    }
+
+    fn enable(&mut self, config: &XoscConfig) {
+        let (imult, iptat) = config.current();
+        let crystal = config.mode == XoscMode::Crystal;
+        self.xoscctrl().write(|w| unsafe {
+            w.startup().variant(config.start_up);
+            w.xtalen().bit(crystal);
+            w.enalc().bit(crystal);
+            w.imult().bits(imult);
+            w.iptat().bits(iptat);
+            w.ondemand().bit(config.on_demand);
+            w.runstdby().bit(config.run_standby);
+            w.enable().set_bit()
+        });
+    }
+
+    /// The CFD can only be enabled once the oscillator is ready
+    fn enable_cfd(&mut self, config: &XoscConfig) {
+        if let Some((presc, switch_back)) = config.cfd {
+            self.xoscctrl().modify(|_, w| {
+                w.cfdpresc().variant(presc);
+                w.swben().bit(switch_back);
+                w.cfden().set_bit()
+            });
+        }
+    }
+
+    fn is_ready(&self) -> bool {
+        let status = self.oscctrl().status.read();
+        match X::NUM {
+            0 => status.xoscrdy0().bit_is_set(),
+            _ => status.xoscrdy1().bit_is_set(),
+        }
+    }
+
+    fn disable(&mut self) {
+        self.set_failure_interrupt(false);
+        self.xoscctrl().modify(|_, w| {
+            w.cfden().clear_bit();
+            w.enable().clear_bit()
+        });
+    }
+
+    fn set_failure_interrupt(&mut self, enable: bool) {
+        let oscctrl = self.oscctrl();
+        match (X::NUM, enable) {
+            (0, true) => oscctrl.intenset.write(|w| w.xoscfail0().set_bit()),
+            (0, false) => oscctrl.intenclr.write(|w| w.xoscfail0().set_bit()),
+            (_, true) => oscctrl.intenset.write(|w| w.xoscfail1().set_bit()),
+            (_, false) => oscctrl.intenclr.write(|w| w.xoscfail1().set_bit()),
+        }
+    }
+
+    fn has_failed(&self) -> bool {
+        let flags = self.oscctrl().intflag.read();
+        match X::NUM {
+            0 => flags.xoscfail0().bit_is_set(),
+            _ => flags.xoscfail1().bit_is_set(),
+        }
+    }
+
+    fn clear_failure(&mut self) {
+        self.oscctrl().intflag.write(|w| match X::NUM {
+            0 => w.xoscfail0().set_bit(),
+            _ => w.xoscfail1().set_bit(),
+        });
+    }
+
+    fn is_failing(&self) -> bool {
+        let status = self.oscctrl().status.read();
+        match X::NUM {
+            0 => status.xoscfail0().bit_is_set(),
+            _ => status.xoscfail1().bit_is_set(),
+        }
+    }
+}
+
+/// Returns true if the output of the XOSC `num` has been switched to the
+/// safe clock by its clock failure detector
+pub(super) fn xosc_on_safe_clock(num: usize) -> bool {
+    // Safety: read-only access to a status register
+    let status = unsafe { (*OSCCTRL::ptr()).status.read() };
+    match num {
+        0 => status.xosccksw0().bit_is_set(),
+        _ => status.xosccksw1().bit_is_set(),
+    }
+}
+
+//==============================================================================
+// Xosc
+//==============================================================================
+
+/// An enabled XOSC
+///
+/// Create it with
+/// [`GenericClockController::enable_xosc`](super::GenericClockController::enable_xosc).
+pub struct Xosc<X: XoscId> {
+    token: XoscToken<X>,
+    freq: Hertz,
+}
+
+impl<X: XoscId> Xosc<X> {
+    /// Enable the oscillator and wait until it is ready
+    pub(super) fn enable(
+        mut token: XoscToken<X>,
+        config: XoscConfig,
+    ) -> Result<Self, (XoscToken<X>, ClockError)> {
+        if let Err(err) = config.validate() {
+            return Err((token, err));
+        }
+        token.enable(&config);
+        while !token.is_ready() {}
+        token.enable_cfd(&config);
+        Ok(Self {
+            token,
+            freq: config.freq,
+        })
+    }
+
+    /// Disable the oscillator and return the token
+    pub(super) fn disable(mut self) -> XoscToken<X> {
+        self.token.disable();
+        self.token
+    }
+
+    /// Return the oscillator frequency
+    ///
+    /// This is the nominal frequency, even while the output is switched to
+    /// the safe clock.
+    #[inline]
+    pub fn freq(&self) -> Hertz {
+        self.freq
+    }
+
+    /// Enable the `XOSCFAIL` interrupt of the clock failure detector
+    #[inline]
+    pub fn enable_failure_interrupt(&mut self) {
+        self.token.set_failure_interrupt(true);
+    }
+
+    /// Disable the `XOSCFAIL` interrupt of the clock failure detector
+    #[inline]
+    pub fn disable_failure_interrupt(&mut self) {
+        self.token.set_failure_interrupt(false);
+    }
+
+    /// Return true if the clock failure detector has flagged a failure since
+    /// the flag was last cleared
+    #[inline]
+    pub fn has_failed(&self) -> bool {
+        self.token.has_failed()
+    }
+
+    /// Clear the failure flag, and the pending interrupt
+    #[inline]
+    pub fn clear_failure(&mut self) {
+        self.token.clear_failure();
+    }
+
+    /// Return true if the oscillator is currently detected as failing
+    #[inline]
+    pub fn is_failing(&self) -> bool {
+        self.token.is_failing()
+    }
+
+    /// Return true if the output is currently switched to the safe clock
+    #[inline]
+    pub fn is_on_safe_clock(&self) -> bool {
+        xosc_on_safe_clock(X::NUM)
+    }
+}
+
+impl<X: XoscId> Sealed for Xosc<X> {}
+
+impl<X: XoscId> Source for Xosc<X> {
+    #[inline]
+    fn source(&self) -> ClockSource {
+        X::SOURCE
+    }
+
+    #[inline]
+    fn freq(&self) -> Hertz {
+        self.freq
+    }
+}
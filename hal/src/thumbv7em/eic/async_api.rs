@@ -0,0 +1,91 @@
+//! Async waits on the external interrupt lines
+
+use core::future::poll_fn;
+use core::task::Poll;
+
+use crate::pac;
+use crate::waker::WakerCell;
+
+use super::pin::ExternalInterruptID;
+
+/// Number of external interrupt lines
+const NUM_LINES: usize = 16;
+
+#[allow(clippy::declare_interior_mutable_const)]
+const NEW_WAKER: WakerCell = WakerCell::new();
+static EIC_WAKERS: [WakerCell; NUM_LINES] = [NEW_WAKER; NUM_LINES];
+
+/// Wake the tasks waiting on an external interrupt line
+///
+/// This must be called from the `EIC` interrupt handler(s). The interrupts of
+/// the lines with a pending interrupt are disabled; their flags are cleared by
+/// the woken task.
+pub fn on_interrupt() {
+    // SAFETY: writing INTENCLR only disables interrupts
+    let eic = unsafe { &*pac::EIC::ptr() };
+    let pending = eic.intflag.read().bits() & eic.intenset.read().bits();
+    eic.intenclr.write(|w| unsafe { w.bits(pending) });
+    for (id, waker) in EIC_WAKERS.iter().enumerate() {
+        if pending & (1 << id) != 0 {
+            waker.wake();
+        }
+    }
+}
+
+#[inline]
+fn clear_flag(id: ExternalInterruptID) {
+    // SAFETY: INTFLAG is cleared by writing ones
+    unsafe { &*pac::EIC::ptr() }
+        .intflag
+        .write(|w| unsafe { w.bits(1 << id) });
+}
+
+/// Wait until the interrupt flag of the line `id` is set
+async fn wait_flag(id: ExternalInterruptID) {
+    poll_fn(|cx| {
+        EIC_WAKERS[id].register(cx.waker());
+        // SAFETY: INTFLAG is only read, and writing INTENSET only enables the
+        // interrupt of the line `id`
+        let eic = unsafe { &*pac::EIC::ptr() };
+        if eic.intflag.read().bits() & (1 << id) != 0 {
+            Poll::Ready(())
+        } else {
+            eic.intenset.write(|w| unsafe { w.bits(1 << id) });
+            Poll::Pending
+        }
+    })
+    .await
+}
+
+/// Wait until `is_high` returns `high`, checking it again after each edge
+/// detected on the line `id`
+pub(super) async fn wait_for_level(
+    id: ExternalInterruptID,
+    is_high: impl Fn() -> bool,
+    high: bool,
+) {
+    loop {
+        clear_flag(id);
+        if is_high() == high {
+            return;
+        }
+        wait_flag(id).await;
+    }
+}
+
+/// Wait for an edge detected on the line `id`. If `rising` is set, wait
+/// until `is_high` returns it after the edge.
+pub(super) async fn wait_for_edge(
+    id: ExternalInterruptID,
+    is_high: impl Fn() -> bool,
+    rising: Option<bool>,
+) {
+    loop {
+        clear_flag(id);
+        wait_flag(id).await;
+        match rising {
+            Some(rising) if is_high() != rising => (),
+            _ => return,
+        }
+    }
+}
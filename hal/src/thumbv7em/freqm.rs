@@ -0,0 +1,248 @@
+//! # Frequency meter (FREQM)
+//!
+//! The FREQM counts the cycles of a measured clock (`GCLK_FREQM_MSR`) during a
+//! window of `REFNUM` cycles of a reference clock (`GCLK_FREQM_REF`). The
+//! measured frequency is `VALUE * f_ref / REFNUM`, with a resolution of
+//! `f_ref / REFNUM`.
+//!
+//! The reference channel is set up once, through the [`FreqmRefClock`] passed
+//! to [`Freqm::new`]. The measured channel can either be configured by the
+//! user ([`Freqm::measure`]) or be routed to any generator by the driver
+//! ([`Freqm::measure_gclk`]). [`Freqm::check_gclks`] measures every configured
+//! generator of the [`GenericClockController`] and compares the result with
+//! the frequency it is expected to run at.
+//!
+//! ```no_run
+//! # use atsamd_hal::clock::GenericClockController;
+//! # use atsamd_hal::freqm::Freqm;
+//! # use atsamd_hal::pac::Peripherals;
+//! # use atsamd_hal::time::U32Ext;
+//! let mut peripherals = Peripherals::take().unwrap();
+//! let mut clocks = GenericClockController::with_external_32kosc(
+//!     peripherals.GCLK,
+//!     &mut peripherals.MCLK,
+//!     &mut peripherals.OSC32KCTRL,
+//!     &mut peripherals.OSCCTRL,
+//!     &mut peripherals.NVMCTRL,
+//! );
+//! // Measure against the 32.768kHz crystal, with a 7.8ms window
+//! let gclk1 = clocks.gclk1();
+//! let reference = clocks.freq_m_ref(&gclk1).unwrap();
+//! let mut freqm = Freqm::new(&mut peripherals.MCLK, peripherals.FREQM, reference, 255);
+//! freqm.check_gclks(&mut clocks, 10.khz()).unwrap();
+//! ```
+
+use core::convert::Infallible;
+
+use crate::clock::{ClockGenId, FreqmMsrClock, FreqmRefClock, GenericClockController};
+use crate::pac::{FREQM, MCLK};
+use crate::time::{Hertz, Nanoseconds};
+
+/// Result of a frequency measurement
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Measurement {
+    /// Measured frequency
+    pub freq: Hertz,
+    /// Raw number of measured clock cycles counted during the window
+    pub value: u32,
+    /// Duration of the measurement window
+    pub window: Nanoseconds,
+    /// The counter overflowed during the window, so `value` and `freq` are
+    /// too low
+    pub overflow: bool,
+}
+
+/// Result of the measurement of a generator by [`Freqm::check_gclks`]
+#[derive(Debug, Clone, Copy)]
+pub struct GclkCheck {
+    /// Generator that was measured
+    pub gclk: ClockGenId,
+    /// Frequency recorded for the generator by the [`GenericClockController`]
+    pub expected: Hertz,
+    /// Measurement of the generator
+    pub measurement: Measurement,
+}
+
+impl GclkCheck {
+    /// Returns the absolute difference between the measured and expected
+    /// frequencies
+    pub fn error(&self) -> Hertz {
+        let measured = self.measurement.freq.0;
+        let expected = self.expected.0;
+        Hertz(if measured > expected {
+            measured - expected
+        } else {
+            expected - measured
+        })
+    }
+}
+
+/// All generators, in `ClockGenId` order
+const GCLKS: [ClockGenId; 12] = [
+    ClockGenId::GCLK0,
+    ClockGenId::GCLK1,
+    ClockGenId::GCLK2,
+    ClockGenId::GCLK3,
+    ClockGenId::GCLK4,
+    ClockGenId::GCLK5,
+    ClockGenId::GCLK6,
+    ClockGenId::GCLK7,
+    ClockGenId::GCLK8,
+    ClockGenId::GCLK9,
+    ClockGenId::GCLK10,
+    ClockGenId::GCLK11,
+];
+
+/// The FREQM peripheral
+pub struct Freqm {
+    freqm: FREQM,
+    reference: FreqmRefClock,
+    refnum: u8,
+}
+
+impl Freqm {
+    /// Enable the FREQM, measuring against the clock of the reference
+    /// channel during `refnum` of its cycles.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `refnum` is zero.
+    pub fn new(mclk: &mut MCLK, freqm: FREQM, reference: FreqmRefClock, refnum: u8) -> Self {
+        assert!(refnum != 0, "the measurement window must not be empty");
+        mclk.apbamask.modify(|_, w| w.freqm_().set_bit());
+
+        freqm.ctrla.write(|w| w.swrst().set_bit());
+        while freqm.syncbusy.read().swrst().bit_is_set() {}
+        freqm.cfga.write(|w| unsafe { w.refnum().bits(refnum) });
+        freqm.ctrla.write(|w| w.enable().set_bit());
+        while freqm.syncbusy.read().enable().bit_is_set() {}
+
+        Self {
+            freqm,
+            reference,
+            refnum,
+        }
+    }
+
+    /// Returns the resolution of a measurement, `f_ref / REFNUM`
+    pub fn resolution(&self) -> Hertz {
+        Hertz(self.reference.freq().0 / self.refnum as u32)
+    }
+
+    /// Measure the clock of the measurement channel, blocking until the end
+    /// of the window.
+    pub fn measure(&mut self, _msr: &FreqmMsrClock) -> Measurement {
+        self.start();
+        nb::block!(self.read()).unwrap()
+    }
+
+    /// Connect the measurement channel to the generator `gclk` and measure
+    /// it, blocking until the end of the window. The channel is disconnected
+    /// afterwards.
+    ///
+    /// Returns `None` if the generator is not configured, or if the channel
+    /// is in use, i.e. a [`FreqmMsrClock`] has been created.
+    pub fn measure_gclk(
+        &mut self,
+        clocks: &mut GenericClockController,
+        gclk: ClockGenId,
+    ) -> Option<Measurement> {
+        clocks.connect_freqm_msr(gclk)?;
+        self.start();
+        let measurement = nb::block!(self.read()).unwrap();
+        clocks.release_freqm_msr();
+        Some(measurement)
+    }
+
+    /// Measure every configured generator, and compare the result with the
+    /// frequency recorded by the [`GenericClockController`].
+    ///
+    /// Returns the first generator whose measurement overflowed or differs
+    /// from the expected frequency by more than `tolerance`. The tolerance
+    /// should be larger than the [`resolution`](Self::resolution).
+    ///
+    /// Generators are skipped if they are not configured, or if the
+    /// measurement channel is in use (see [`measure_gclk`](Self::measure_gclk)).
+    pub fn check_gclks(
+        &mut self,
+        clocks: &mut GenericClockController,
+        tolerance: impl Into<Hertz>,
+    ) -> Result<(), GclkCheck> {
+        let tolerance = tolerance.into();
+        for gclk in GCLKS {
+            let expected = match clocks.get_gclk(gclk) {
+                Some(generator) => generator.into(),
+                None => continue,
+            };
+            let measurement = match self.measure_gclk(clocks, gclk) {
+                Some(measurement) => measurement,
+                None => continue,
+            };
+            let check = GclkCheck {
+                gclk,
+                expected,
+                measurement,
+            };
+            if measurement.overflow || check.error().0 > tolerance.0 {
+                return Err(check);
+            }
+        }
+        Ok(())
+    }
+
+    /// Start a measurement
+    ///
+    /// Use [`read`](Self::read) to retrieve the result, or wait for the
+    /// `DONE` interrupt.
+    pub fn start(&mut self) {
+        self.freqm.intflag.write(|w| w.done().set_bit());
+        self.freqm.status.write(|w| w.ovf().set_bit());
+        self.freqm.ctrlb.write(|w| w.start().set_bit());
+    }
+
+    /// Returns true while a measurement is ongoing
+    pub fn is_busy(&self) -> bool {
+        self.freqm.status.read().busy().bit_is_set()
+    }
+
+    /// Returns the result of the last measurement, or `WouldBlock` if it is
+    /// not finished yet
+    ///
+    /// This also clears the `DONE` interrupt flag.
+    pub fn read(&mut self) -> nb::Result<Measurement, Infallible> {
+        if self.freqm.intflag.read().done().bit_is_clear() {
+            return Err(nb::Error::WouldBlock);
+        }
+        self.freqm.intflag.write(|w| w.done().set_bit());
+
+        let value = self.freqm.value.read().value().bits();
+        let overflow = self.freqm.status.read().ovf().bit_is_set();
+        let ref_freq = self.reference.freq().0 as u64;
+        let refnum = self.refnum as u64;
+        Ok(Measurement {
+            freq: Hertz((value as u64 * ref_freq / refnum) as u32),
+            value,
+            window: Nanoseconds((refnum * 1_000_000_000 / ref_freq) as u32),
+            overflow,
+        })
+    }
+
+    /// Enable the `DONE` interrupt
+    pub fn enable_interrupt(&mut self) {
+        self.freqm.intenset.write(|w| w.done().set_bit());
+    }
+
+    /// Disable the `DONE` interrupt
+    pub fn disable_interrupt(&mut self) {
+        self.freqm.intenclr.write(|w| w.done().set_bit());
+    }
+
+    /// Disable the FREQM and return the underlying peripheral and reference
+    /// clock
+    pub fn free(self, mclk: &mut MCLK) -> (FREQM, FreqmRefClock) {
+        self.freqm.ctrla.write(|w| w.enable().clear_bit());
+        while self.freqm.syncbusy.read().enable().bit_is_set() {}
+        mclk.apbamask.modify(|_, w| w.freqm_().clear_bit());
+        (self.freqm, self.reference)
+    }
+}
@@ -1,9 +1,13 @@
 pub mod aes;
+pub mod backup_ram;
 pub mod calibration;
 pub mod clock;
 pub mod eic;
+pub mod freqm;
+pub mod pm;
 pub mod pukcc;
 pub mod qspi;
+pub mod supc;
 pub mod timer;
 pub mod trng;
 
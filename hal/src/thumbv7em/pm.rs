@@ -0,0 +1,332 @@
+//! # Power manager (PM)
+//!
+//! The PM selects the sleep mode entered by the `WFI` instruction, from the
+//! lightest to the deepest:
+//!
+//! * `IDLE`: the CPU is stopped, all clocks and peripherals keep running.
+//! * `STANDBY`: all clocks are stopped, except those requested by a
+//!   peripheral with `RUNSTDBY` set. RAM is retained according to
+//!   [`StandbyConfig`].
+//! * `HIBERNATE`: the core power domain is switched off, only the backup
+//!   domain (RTC, backup RAM) and the retained RAM keep running, see
+//!   [`HibernateConfig`].
+//! * `BACKUP`: only the backup domain is powered, see [`BackupConfig`].
+//! * `OFF`: everything is off, only a reset wakes the device up.
+//!
+//! IDLE and STANDBY return from [`Pm::sleep`] when an interrupt is pending.
+//! HIBERNATE, BACKUP and OFF wake up through a reset; the cause of the exit
+//! from HIBERNATE or BACKUP is reported by [`backup_exit`].
+//!
+//! Unlike the SAML2x, SAMx5x has no performance levels: there is no
+//! `PM.PLCFG` register, and the core voltage does not depend on the CPU
+//! frequency. The active consumption is lowered by slowing down the clock tree
+//! and by selecting the buck converter with
+//! [`Supc::set_regulator`](crate::supc::Supc::set_regulator).
+//!
+//! ```no_run
+//! # use atsamd_hal::pac::Peripherals;
+//! # use atsamd_hal::pm::*;
+//! let mut peripherals = Peripherals::take().unwrap();
+//! if let Some(exit) = backup_exit(&peripherals.RSTC) {
+//!     // Woken up from BACKUP by the RTC
+//!     assert!(exit.rtc);
+//! }
+//! let mut pm = Pm::new(peripherals.PM);
+//! // Keep the backup RAM only, for the lowest consumption
+//! pm.configure_backup(BackupConfig::new().backup_ram(RamRetention::All));
+//! pm.sleep(SleepMode::BACKUP);
+//! ```
+
+use cortex_m::asm;
+use cortex_m::interrupt;
+use cortex_m::peripheral::NVIC;
+
+use crate::pac::{PM, RSTC};
+
+pub use crate::pac::pm::sleepcfg::SLEEPMODE_A as SleepMode;
+pub use crate::pac::pm::stdbycfg::FASTWKUP_A as FastWakeup;
+
+/// Number of interrupt lines tracked by the NVIC `ISPR` registers
+const NUM_ISPR: usize = 16;
+
+/// RAM retention in a sleep mode
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RamRetention {
+    /// The whole RAM is retained
+    All,
+    /// Only the RAM areas configured in the PRAM power domain settings are
+    /// retained
+    Partial,
+    /// The RAM is not retained
+    Off,
+}
+
+//==============================================================================
+// Sleep mode configurations
+//==============================================================================
+
+/// Configuration of the STANDBY sleep mode (`STDBYCFG`)
+#[derive(Clone, Copy, Debug)]
+pub struct StandbyConfig {
+    ram: RamRetention,
+    fast_wakeup: FastWakeup,
+}
+
+impl StandbyConfig {
+    /// Default configuration: the RAM is retained and fast wakeup is disabled
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            ram: RamRetention::All,
+            fast_wakeup: FastWakeup::NO,
+        }
+    }
+
+    /// Set the RAM retention
+    #[inline]
+    pub fn ram(mut self, ram: RamRetention) -> Self {
+        self.ram = ram;
+        self
+    }
+
+    /// Keep the NVM and/or the main voltage regulator ready during STANDBY,
+    /// for a faster wake up at the cost of a higher consumption
+    #[inline]
+    pub fn fast_wakeup(mut self, fast_wakeup: FastWakeup) -> Self {
+        self.fast_wakeup = fast_wakeup;
+        self
+    }
+}
+
+impl Default for StandbyConfig {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Configuration of the HIBERNATE sleep mode (`HIBCFG`)
+#[derive(Clone, Copy, Debug)]
+pub struct HibernateConfig {
+    ram: RamRetention,
+    backup_ram: RamRetention,
+}
+
+impl HibernateConfig {
+    /// Default configuration: the RAM and the backup RAM are retained
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            ram: RamRetention::All,
+            backup_ram: RamRetention::All,
+        }
+    }
+
+    /// Set the RAM retention
+    #[inline]
+    pub fn ram(mut self, ram: RamRetention) -> Self {
+        self.ram = ram;
+        self
+    }
+
+    /// Set the backup RAM retention
+    #[inline]
+    pub fn backup_ram(mut self, backup_ram: RamRetention) -> Self {
+        self.backup_ram = backup_ram;
+        self
+    }
+}
+
+impl Default for HibernateConfig {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Configuration of the BACKUP sleep mode (`BKUPCFG`)
+#[derive(Clone, Copy, Debug)]
+pub struct BackupConfig {
+    backup_ram: RamRetention,
+}
+
+impl BackupConfig {
+    /// Default configuration: the backup RAM is retained
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            backup_ram: RamRetention::All,
+        }
+    }
+
+    /// Set the backup RAM retention
+    #[inline]
+    pub fn backup_ram(mut self, backup_ram: RamRetention) -> Self {
+        self.backup_ram = backup_ram;
+        self
+    }
+}
+
+impl Default for BackupConfig {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+//==============================================================================
+// Pm
+//==============================================================================
+
+/// Source of the wake up from IDLE or STANDBY
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WakeSource {
+    /// The pending interrupt with the lowest number, to compare with
+    /// `pac::Interrupt::X as u16`
+    Interrupt(u16),
+    /// No interrupt was pending, e.g. the wake up was requested by the
+    /// debugger
+    Unknown,
+}
+
+/// The PM peripheral
+pub struct Pm {
+    pm: PM,
+}
+
+impl Pm {
+    /// Take the PM peripheral
+    #[inline]
+    pub fn new(pm: PM) -> Self {
+        Self { pm }
+    }
+
+    /// Configure the STANDBY sleep mode
+    pub fn configure_standby(&mut self, config: StandbyConfig) {
+        self.pm.stdbycfg.write(|w| {
+            match config.ram {
+                RamRetention::All => w.ramcfg().ret(),
+                RamRetention::Partial => w.ramcfg().partial(),
+                RamRetention::Off => w.ramcfg().off(),
+            };
+            w.fastwkup().variant(config.fast_wakeup)
+        });
+    }
+
+    /// Configure the HIBERNATE sleep mode
+    pub fn configure_hibernate(&mut self, config: HibernateConfig) {
+        self.pm.hibcfg.write(|w| {
+            match config.ram {
+                RamRetention::All => w.ramcfg().ret(),
+                RamRetention::Partial => w.ramcfg().partial(),
+                RamRetention::Off => w.ramcfg().off(),
+            };
+            match config.backup_ram {
+                RamRetention::All => w.bramcfg().ret(),
+                RamRetention::Partial => w.bramcfg().partial(),
+                RamRetention::Off => w.bramcfg().off(),
+            }
+        });
+    }
+
+    /// Configure the BACKUP sleep mode
+    pub fn configure_backup(&mut self, config: BackupConfig) {
+        self.pm.bkupcfg.write(|w| match config.backup_ram {
+            RamRetention::All => w.bramcfg().ret(),
+            RamRetention::Partial => w.bramcfg().partial(),
+            RamRetention::Off => w.bramcfg().off(),
+        });
+    }
+
+    /// Keep the I/O lines in their current state during HIBERNATE and BACKUP
+    ///
+    /// The retention is kept after the wake up, until this is called with
+    /// `false` once the pins have been configured again.
+    #[inline]
+    pub fn set_io_retention(&mut self, enable: bool) {
+        self.pm.ctrla.write(|w| w.ioret().bit(enable));
+    }
+
+    /// Returns the sleep mode entered by `WFI`
+    #[inline]
+    pub fn sleep_mode(&self) -> Option<SleepMode> {
+        self.pm.sleepcfg.read().sleepmode().variant()
+    }
+
+    /// Select the sleep mode entered by `WFI`
+    ///
+    /// This waits until the PM accepts a new mode, and until the write is
+    /// effective, so that a following `WFI` enters the right mode.
+    pub fn set_sleep_mode(&mut self, mode: SleepMode) {
+        while self.pm.intflag.read().sleeprdy().bit_is_clear() {}
+        self.pm.sleepcfg.write(|w| w.sleepmode().variant(mode));
+        while self.pm.sleepcfg.read().sleepmode().bits() != u8::from(mode) {}
+    }
+
+    /// Enter the sleep mode `mode`
+    ///
+    /// Interrupts are masked while sleeping: the device wakes up on any
+    /// enabled interrupt, which is reported as the [`WakeSource`] and then
+    /// handled as soon as this returns.
+    ///
+    /// HIBERNATE, BACKUP and OFF only return if an interrupt was already
+    /// pending; otherwise the device wakes up through a reset.
+    pub fn sleep(&mut self, mode: SleepMode) -> WakeSource {
+        self.set_sleep_mode(mode);
+        interrupt::free(|_| {
+            asm::dsb();
+            asm::wfi();
+            pending_interrupt()
+        })
+    }
+
+    /// Release the PM peripheral
+    #[inline]
+    pub fn free(self) -> PM {
+        self.pm
+    }
+}
+
+/// Returns the lowest pending interrupt number
+fn pending_interrupt() -> WakeSource {
+    // Safety: read-only access to the NVIC pending registers
+    let nvic = unsafe { &*NVIC::PTR };
+    for i in 0..NUM_ISPR {
+        let pending = nvic.ispr[i].read();
+        if pending != 0 {
+            let irqn = i as u32 * 32 + pending.trailing_zeros();
+            return WakeSource::Interrupt(irqn as u16);
+        }
+    }
+    WakeSource::Unknown
+}
+
+//==============================================================================
+// Backup exit
+//==============================================================================
+
+/// Cause of the exit from HIBERNATE or BACKUP (`RSTC.BKUPEXIT`)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BackupExit {
+    /// The device was in HIBERNATE rather than BACKUP
+    pub hibernate: bool,
+    /// Woken up by an RTC event
+    pub rtc: bool,
+    /// Woken up by the battery backup power switch, when the main supply came
+    /// back
+    pub battery_switch: bool,
+}
+
+/// Returns the cause of the exit from HIBERNATE or BACKUP, or `None` if the
+/// last reset has another cause.
+pub fn backup_exit(rstc: &RSTC) -> Option<BackupExit> {
+    if rstc.rcause.read().backup().bit_is_clear() {
+        return None;
+    }
+    let exit = rstc.bkupexit.read();
+    Some(BackupExit {
+        hibernate: exit.hib().bit_is_set(),
+        rtc: exit.rtc().bit_is_set(),
+        battery_switch: exit.bbps().bit_is_set(),
+    })
+}
@@ -198,6 +198,23 @@ impl<I: PinId> PwmPin for $TYPE<I> {
     }
 }
 
+#[cfg(feature = "ehal-1")]
+impl<I: PinId> crate::ehal_1::pwm::ErrorType for $TYPE<I> {
+    type Error = core::convert::Infallible;
+}
+
+#[cfg(feature = "ehal-1")]
+impl<I: PinId> crate::ehal_1::pwm::SetDutyCycle for $TYPE<I> {
+    fn max_duty_cycle(&self) -> u16 {
+        self.get_max_duty()
+    }
+
+    fn set_duty_cycle(&mut self, duty: u16) -> Result<(), Self::Error> {
+        self.set_duty(duty);
+        Ok(())
+    }
+}
+
 )+}}
 
 pwm! {
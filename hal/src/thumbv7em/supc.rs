@@ -0,0 +1,490 @@
+//! # Supply controller (SUPC)
+//!
+//! The SUPC manages the supplies of the device:
+//!
+//! * The BOD33 monitors `VDD` and, in battery backup, `VBAT`. When the supply
+//!   falls below the threshold, it can reset the device, raise the `BOD33DET`
+//!   interrupt or switch to the backup domain. An interrupt leaves some time
+//!   to save data before the supply is too low, then the firmware can reset
+//!   the device itself.
+//! * The main voltage regulator (`VREG`) is either the internal LDO or the
+//!   buck converter. The buck converter lowers the consumption, but needs an
+//!   external inductor on `VSW`.
+//! * The voltage reference (`VREF`) used by the ADC and DAC, and the output of
+//!   the temperature sensors.
+//! * The battery backup power switch (`BBPS`), selecting when the backup
+//!   domain is supplied by `VBAT`.
+//! * The backup output pins `OUT[1:0]`, driven even in BACKUP, and the backup
+//!   input pins.
+//!
+//! The BOD33 detection raises the `SUPC_BODDET` interrupt, the other flags
+//! raise `SUPC_OTHER`.
+//!
+//! ```no_run
+//! # use atsamd_hal::pac::Peripherals;
+//! # use atsamd_hal::supc::*;
+//! let peripherals = Peripherals::take().unwrap();
+//! let mut supc = Supc::new(peripherals.SUPC);
+//! supc.set_regulator(Regulator::BUCK);
+//! supc.configure_bod33(
+//!     Bod33Config::new()
+//!         .level(0x1C)
+//!         .action(Bod33Action::INT),
+//! );
+//! supc.enable_interrupts(Flags::BOD33DET);
+//! ```
+
+use bitflags::bitflags;
+
+use crate::pac::SUPC;
+
+pub use crate::pac::supc::bbps::CONF_A as BatteryBackup;
+pub use crate::pac::supc::bod33::ACTION_A as Bod33Action;
+pub use crate::pac::supc::bod33::PSEL_A as Bod33Prescaler;
+pub use crate::pac::supc::vref::SEL_A as VrefVoltage;
+pub use crate::pac::supc::vreg::SEL_A as Regulator;
+
+bitflags! {
+    /// Interrupt bit flags of the SUPC
+    ///
+    /// The binary format of the underlying bits exactly matches the `INTFLAG`
+    /// bits.
+    pub struct Flags: u32 {
+        /// BOD33 ready
+        const BOD33RDY = 1 << 0;
+        /// `VDD` crossed the BOD33 threshold
+        const BOD33DET = 1 << 1;
+        /// BOD33 synchronization ready
+        const B33SRDY = 1 << 2;
+        /// Voltage regulator ready
+        const VREGRDY = 1 << 8;
+        /// `VDDCORE` ready
+        const VCORERDY = 1 << 10;
+    }
+}
+
+//==============================================================================
+// Configurations
+//==============================================================================
+
+/// Configuration of the BOD33
+#[derive(Clone, Copy, Debug)]
+pub struct Bod33Config {
+    level: u8,
+    vbat_level: u8,
+    hysteresis: u8,
+    action: Bod33Action,
+    prescaler: Bod33Prescaler,
+    standby_sampling: bool,
+    run_in_standby: bool,
+    run_in_hibernate: bool,
+    run_in_backup: bool,
+}
+
+impl Bod33Config {
+    /// Default configuration: the lowest thresholds, no hysteresis, reset on
+    /// detection, and a continuous monitoring in active and standby modes only
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            level: 0,
+            vbat_level: 0,
+            hysteresis: 0,
+            action: Bod33Action::RESET,
+            prescaler: Bod33Prescaler::NODIV,
+            standby_sampling: false,
+            run_in_standby: true,
+            run_in_hibernate: false,
+            run_in_backup: false,
+        }
+    }
+
+    /// Set the threshold on `VDD`
+    ///
+    /// See the BOD33 electrical characteristics in the datasheet for the
+    /// voltage of each level. The factory setting is in the NVM user row.
+    #[inline]
+    pub fn level(mut self, level: u8) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// Set the threshold on `VBAT`, used when the backup domain is supplied
+    /// by the battery
+    #[inline]
+    pub fn vbat_level(mut self, level: u8) -> Self {
+        self.vbat_level = level;
+        self
+    }
+
+    /// Set the hysteresis added to the threshold for the rising edge
+    ///
+    /// Only the low four bits are used.
+    #[inline]
+    pub fn hysteresis(mut self, hysteresis: u8) -> Self {
+        self.hysteresis = hysteresis & 0xF;
+        self
+    }
+
+    /// Set the action taken when the supply falls below the threshold
+    #[inline]
+    pub fn action(mut self, action: Bod33Action) -> Self {
+        self.action = action;
+        self
+    }
+
+    /// Sample the supply in standby, every `prescaler` cycles of the
+    /// OSCULP32K, instead of monitoring it continuously
+    #[inline]
+    pub fn standby_sampling(mut self, prescaler: Bod33Prescaler) -> Self {
+        self.standby_sampling = true;
+        self.prescaler = prescaler;
+        self
+    }
+
+    /// Keep the BOD33 running in STANDBY
+    #[inline]
+    pub fn run_in_standby(mut self, enable: bool) -> Self {
+        self.run_in_standby = enable;
+        self
+    }
+
+    /// Keep the BOD33 running in HIBERNATE
+    #[inline]
+    pub fn run_in_hibernate(mut self, enable: bool) -> Self {
+        self.run_in_hibernate = enable;
+        self
+    }
+
+    /// Keep the BOD33 running in BACKUP
+    ///
+    /// This is needed for the battery backup power switch to go back to
+    /// `VDD`, see [`Supc::set_battery_backup`].
+    #[inline]
+    pub fn run_in_backup(mut self, enable: bool) -> Self {
+        self.run_in_backup = enable;
+        self
+    }
+}
+
+impl Default for Bod33Config {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Configuration of the voltage reference and of the temperature sensors
+#[derive(Clone, Copy, Debug)]
+pub struct VrefConfig {
+    voltage: VrefVoltage,
+    output: bool,
+    temperature_sensor: bool,
+    run_in_standby: bool,
+    on_demand: bool,
+}
+
+impl VrefConfig {
+    /// Default configuration: 1.0V, only running when requested by a
+    /// peripheral, not routed to the ADC
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            voltage: VrefVoltage::_1V0,
+            output: false,
+            temperature_sensor: false,
+            run_in_standby: false,
+            on_demand: true,
+        }
+    }
+
+    /// Set the reference voltage
+    #[inline]
+    pub fn voltage(mut self, voltage: VrefVoltage) -> Self {
+        self.voltage = voltage;
+        self
+    }
+
+    /// Route the reference to the ADC input `INTREF`
+    #[inline]
+    pub fn output(mut self, enable: bool) -> Self {
+        self.output = enable;
+        self
+    }
+
+    /// Enable the temperature sensors, read through the ADC `PTAT` and `CTAT`
+    /// inputs
+    #[inline]
+    pub fn temperature_sensor(mut self, enable: bool) -> Self {
+        self.temperature_sensor = enable;
+        self
+    }
+
+    /// Keep the reference running in STANDBY
+    #[inline]
+    pub fn run_in_standby(mut self, enable: bool) -> Self {
+        self.run_in_standby = enable;
+        self
+    }
+
+    /// Only run the reference when a peripheral requests it, otherwise it
+    /// runs continuously
+    #[inline]
+    pub fn on_demand(mut self, enable: bool) -> Self {
+        self.on_demand = enable;
+        self
+    }
+}
+
+impl Default for VrefConfig {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Backup output pin
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BackupPin {
+    /// `OUT0`
+    Out0,
+    /// `OUT1`
+    Out1,
+}
+
+/// Read/write bits of `BKOUT`: `EN` and `RTCTGL`. The other bits are write-only
+/// strobes that set or clear the outputs.
+const BKOUT_RW: u32 = 0x3 | 0x3 << 24;
+
+impl BackupPin {
+    #[inline]
+    fn mask(self) -> u32 {
+        match self {
+            BackupPin::Out0 => 1,
+            BackupPin::Out1 => 2,
+        }
+    }
+}
+
+//==============================================================================
+// Supc
+//==============================================================================
+
+/// The SUPC peripheral
+pub struct Supc {
+    supc: SUPC,
+}
+
+impl Supc {
+    /// Take the SUPC peripheral
+    ///
+    /// The SUPC is always clocked, and the BOD33 keeps the configuration
+    /// loaded from the NVM user row until [`configure_bod33`] is called.
+    ///
+    /// [`configure_bod33`]: Self::configure_bod33
+    #[inline]
+    pub fn new(supc: SUPC) -> Self {
+        Self { supc }
+    }
+
+    //==========================================================================
+    // BOD33
+    //==========================================================================
+
+    fn wait_bod33_sync(&self) {
+        while self.supc.status.read().b33srdy().bit_is_clear() {}
+    }
+
+    /// Configure and enable the BOD33
+    ///
+    /// The BOD33 is disabled while it is reconfigured, and this waits until
+    /// it is ready again.
+    pub fn configure_bod33(&mut self, config: Bod33Config) {
+        self.disable_bod33();
+        self.supc.bod33.write(|w| unsafe {
+            w.level().bits(config.level);
+            w.vbatlevel().bits(config.vbat_level);
+            w.hyst().bits(config.hysteresis);
+            w.action().variant(config.action);
+            w.psel().variant(config.prescaler);
+            w.stdbycfg().bit(config.standby_sampling);
+            w.runstdby().bit(config.run_in_standby);
+            w.runhib().bit(config.run_in_hibernate);
+            w.runbkup().bit(config.run_in_backup)
+        });
+        self.wait_bod33_sync();
+        self.supc.bod33.modify(|_, w| w.enable().set_bit());
+        self.wait_bod33_sync();
+        while self.supc.status.read().bod33rdy().bit_is_clear() {}
+    }
+
+    /// Disable the BOD33
+    pub fn disable_bod33(&mut self) {
+        self.supc.bod33.modify(|_, w| w.enable().clear_bit());
+        self.wait_bod33_sync();
+    }
+
+    /// Returns true if the BOD33 is enabled
+    #[inline]
+    pub fn bod33_enabled(&self) -> bool {
+        self.supc.bod33.read().enable().bit_is_set()
+    }
+
+    /// Returns true if the supply is currently below the BOD33 threshold
+    #[inline]
+    pub fn bod33_detected(&self) -> bool {
+        self.supc.status.read().bod33det().bit_is_set()
+    }
+
+    //==========================================================================
+    // Voltage regulator
+    //==========================================================================
+
+    /// Select the main voltage regulator
+    ///
+    /// This waits until the new regulator is ready. [`Regulator::BUCK`] must
+    /// only be selected if an inductor is mounted on `VSW`.
+    pub fn set_regulator(&mut self, regulator: Regulator) {
+        self.supc.vreg.modify(|_, w| w.sel().variant(regulator));
+        while self.supc.status.read().vregrdy().bit_is_clear() {}
+    }
+
+    /// Returns the main voltage regulator
+    #[inline]
+    pub fn regulator(&self) -> Regulator {
+        self.supc.vreg.read().sel().variant()
+    }
+
+    /// Keep the main voltage regulator running in BACKUP
+    #[inline]
+    pub fn set_regulator_run_in_backup(&mut self, enable: bool) {
+        self.supc.vreg.modify(|_, w| w.runbkup().bit(enable));
+    }
+
+    //==========================================================================
+    // Voltage reference
+    //==========================================================================
+
+    /// Configure the voltage reference and the temperature sensors
+    pub fn configure_vref(&mut self, config: VrefConfig) {
+        self.supc.vref.write(|w| {
+            w.sel().variant(config.voltage);
+            w.vrefoe().bit(config.output);
+            w.tsen().bit(config.temperature_sensor);
+            w.runstdby().bit(config.run_in_standby);
+            w.ondemand().bit(config.on_demand)
+        });
+    }
+
+    /// Returns the selected reference voltage
+    #[inline]
+    pub fn vref_voltage(&self) -> Option<VrefVoltage> {
+        self.supc.vref.read().sel().variant()
+    }
+
+    //==========================================================================
+    // Battery backup
+    //==========================================================================
+
+    /// Configure the battery backup power switch
+    ///
+    /// With [`BatteryBackup::BOD33`], the backup domain switches to `VBAT`
+    /// when the BOD33 detects that `VDD` is too low, and back to `VDD` when it
+    /// returns; the BOD33 must then be enabled with the
+    /// [`Bod33Action::BKUP`] action. With [`BatteryBackup::FORCED`], the
+    /// backup domain is always supplied by `VBAT` in BACKUP.
+    ///
+    /// If `wake` is true, the device wakes up from BACKUP when the switch goes
+    /// back to `VDD`.
+    pub fn set_battery_backup(&mut self, conf: BatteryBackup, wake: bool) {
+        self.supc.bbps.write(|w| {
+            w.conf().variant(conf);
+            w.wakeen().bit(wake)
+        });
+    }
+
+    //==========================================================================
+    // Backup pins
+    //==========================================================================
+
+    /// Enable the backup output `pin`, driving it with its current level
+    ///
+    /// The pin is driven by the SUPC instead of the PORT, and keeps its level
+    /// in every sleep mode.
+    pub fn enable_backup_output(&mut self, pin: BackupPin) {
+        self.supc
+            .bkout
+            .modify(|r, w| unsafe { w.bits(r.bits() & BKOUT_RW | pin.mask()) });
+    }
+
+    /// Disable the backup output `pin`, handing it back to the PORT
+    pub fn disable_backup_output(&mut self, pin: BackupPin) {
+        self.supc
+            .bkout
+            .modify(|r, w| unsafe { w.bits(r.bits() & BKOUT_RW & !pin.mask()) });
+    }
+
+    /// Set the level of the backup output `pin`
+    pub fn set_backup_output(&mut self, pin: BackupPin, high: bool) {
+        // Only write the read/write bits back, so that no other pin is set or
+        // cleared
+        let shift = if high { 16 } else { 8 };
+        self.supc
+            .bkout
+            .modify(|r, w| unsafe { w.bits(r.bits() & BKOUT_RW | pin.mask() << shift) });
+    }
+
+    /// Toggle the backup output `pin` on each RTC event, e.g. to wake up an
+    /// external device
+    pub fn set_backup_output_rtc_toggle(&mut self, pin: BackupPin, enable: bool) {
+        let mask = pin.mask() << 24;
+        self.supc.bkout.modify(|r, w| unsafe {
+            let bits = r.bits() & BKOUT_RW;
+            w.bits(if enable { bits | mask } else { bits & !mask })
+        });
+    }
+
+    /// Returns the level of the backup input `pin`
+    #[inline]
+    pub fn backup_input(&self, pin: BackupPin) -> bool {
+        self.supc.bkin.read().bits() & pin.mask() != 0
+    }
+
+    //==========================================================================
+    // Interrupts
+    //==========================================================================
+
+    /// Read the interrupt flags
+    #[inline]
+    pub fn read_flags(&self) -> Flags {
+        Flags::from_bits_truncate(self.supc.intflag.read().bits())
+    }
+
+    /// Clear the interrupt flags
+    #[inline]
+    pub fn clear_flags(&mut self, flags: Flags) {
+        self.supc.intflag.write(|w| unsafe { w.bits(flags.bits()) });
+    }
+
+    /// Enable the interrupts
+    #[inline]
+    pub fn enable_interrupts(&mut self, flags: Flags) {
+        self.supc
+            .intenset
+            .write(|w| unsafe { w.bits(flags.bits()) });
+    }
+
+    /// Disable the interrupts
+    #[inline]
+    pub fn disable_interrupts(&mut self, flags: Flags) {
+        self.supc
+            .intenclr
+            .write(|w| unsafe { w.bits(flags.bits()) });
+    }
+
+    /// Release the SUPC peripheral
+    #[inline]
+    pub fn free(self) -> SUPC {
+        self.supc
+    }
+}
@@ -9,20 +9,19 @@ use crate::timer_params::TimerParams;
 use crate::pac::{TC4, TC5};
 use crate::timer_traits::InterruptDrivenTimer;
 
-use crate::clock;
+use crate::clock::{self, ClockId, PeripheralClock, Reclock};
 use crate::time::{Hertz, Nanoseconds};
 use void::Void;
 
 // Note:
-// TC3 + TC4 can be paired to make a 32-bit counter
-// TC5 + TC6 can be paired to make a 32-bit counter
+// TC0 + TC1, TC2 + TC3, TC4 + TC5 and TC6 + TC7 can be paired to make a
+// 32-bit counter, see `crate::tc`
 
 /// A generic hardware timer counter.
 /// The counters are exposed in 16-bit mode only.
-/// The hardware allows configuring the 8-bit mode
-/// and pairing up some instances to run in 32-bit
-/// mode, but that functionality is not currently
-/// exposed by this hal implementation.
+/// The 8-bit and 32-bit modes, compare/capture channels
+/// and one-shot counting are available through
+/// [`Counter`](crate::tc::Counter).
 /// TimerCounter implements both the `Periodic` and
 /// the `CountDown` embedded_hal timer traits.
 /// Before a hardware timer can be used, it must first
@@ -30,12 +29,22 @@ use void::Void;
 pub struct TimerCounter<TC> {
     freq: Hertz,
     tc: TC,
+    clock: ClockId,
+    timeout: Option<Nanoseconds>,
 }
 
 /// This is a helper trait to make it easier to make most of the
 /// TimerCounter impl generic.  It doesn't make too much sense to
 /// to try to implement this trait outside of this module.
 pub trait Count16 {
+    /// Index of the waker of the async delays
+    #[cfg(feature = "async")]
+    #[doc(hidden)]
+    const NUM: usize;
+    /// Position of the TC on its peripheral channel, see
+    /// [`Reclock::channel_users`]
+    #[doc(hidden)]
+    const CHANNEL_USERS: u8;
     fn count_16(&self) -> &COUNT16;
 }
 
@@ -50,6 +59,8 @@ where
     where
         T: Into<Self::Time>,
     {
+        let timeout = timeout.into();
+        self.timeout = Some(timeout);
         let params = TimerParams::new_us(timeout, self.freq.0);
         let divider = params.divider;
         let cycles = params.cycles;
@@ -110,6 +121,40 @@ where
     }
 }
 
+impl<TC> Reclock for TimerCounter<TC>
+where
+    TC: Count16,
+{
+    fn clock_id(&self) -> ClockId {
+        self.clock
+    }
+
+    /// Store the new clock frequency, and reconfigure a started timer with
+    /// the prescaler and period recomputed for its last timeout. The timer
+    /// keeps its interrupts, and is only restarted if it was running.
+    fn reclock(&mut self, freq: Hertz) {
+        self.freq = freq;
+        if let Some(timeout) = self.timeout {
+            let count = self.tc.count_16();
+            let enabled = count.ctrla.read().enable().bit_is_set();
+            let interrupts = count.intenset.read().bits();
+            self.start(timeout);
+
+            // `start` resets the TC and enables it
+            let count = self.tc.count_16();
+            count.intenset.write(|w| unsafe { w.bits(interrupts) });
+            if !enabled {
+                count.ctrla.modify(|_, w| w.enable().clear_bit());
+                while count.status.read().perbufv().bit_is_set() {}
+            }
+        }
+    }
+
+    fn channel_users(&self) -> u8 {
+        TC::CHANNEL_USERS
+    }
+}
+
 impl<TC> InterruptDrivenTimer for TimerCounter<TC>
 where
     TC: Count16,
@@ -131,12 +176,78 @@ where
     }
 }
 
+/// Number of wakers of the async delays, indexed by the TC number
+#[cfg(feature = "async")]
+const NUM_TC: usize = 6;
+
+#[cfg(feature = "async")]
+#[allow(clippy::declare_interior_mutable_const)]
+const NEW_WAKER: crate::waker::WakerCell = crate::waker::WakerCell::new();
+#[cfg(feature = "async")]
+static TC_WAKERS: [crate::waker::WakerCell; NUM_TC] = [NEW_WAKER; NUM_TC];
+
+/// Longest period of an async delay, short enough to fit in the 16-bit
+/// counter with the largest prescaler for any clock up to 650 MHz
+#[cfg(feature = "async")]
+const MAX_DELAY_NS: u32 = 100_000_000;
+
+/// Async delays, waiting for the overflows of the timer
+///
+/// The interrupt handler of the timer must call `on_interrupt`, e.g.
+/// `TimerCounter3::on_interrupt()`. The timer is stopped after each delay, and
+/// when the returned future is dropped before completion.
+#[cfg(feature = "async")]
+impl<TC> embedded_hal_async::delay::DelayNs for TimerCounter<TC>
+where
+    TC: Count16,
+{
+    async fn delay_ns(&mut self, ns: u32) {
+        let timer = StopOnDrop(self);
+        let mut remaining = ns;
+        while remaining > 0 {
+            let period = remaining.min(MAX_DELAY_NS);
+            timer.0.start(Nanoseconds(period));
+            core::future::poll_fn(|cx| {
+                TC_WAKERS[TC::NUM].register(cx.waker());
+                if timer.0.wait().is_ok() {
+                    core::task::Poll::Ready(())
+                } else {
+                    timer.0.enable_interrupt();
+                    core::task::Poll::Pending
+                }
+            })
+            .await;
+            remaining -= period;
+        }
+    }
+}
+
+/// Stop the timer of an async delay, when its future completes or is dropped
+#[cfg(feature = "async")]
+struct StopOnDrop<'a, TC: Count16>(&'a mut TimerCounter<TC>);
+
+#[cfg(feature = "async")]
+impl<TC: Count16> Drop for StopOnDrop<'_, TC> {
+    fn drop(&mut self) {
+        self.0.disable_interrupt();
+        self.0.timeout = None;
+        self.0
+            .tc
+            .count_16()
+            .ctrla
+            .modify(|_, w| w.enable().clear_bit());
+    }
+}
+
 macro_rules! tc {
-    ($($TYPE:ident: ($TC:ident, $mclk:ident, $clock:ident, $apmask:ident),)+) => {
+    ($($TYPE:ident: ($TC:ident, $num:literal, $users:literal, $mclk:ident, $clock:ident, $apmask:ident),)+) => {
         $(
 pub type $TYPE = TimerCounter<$TC>;
 
 impl Count16 for $TC {
+    #[cfg(feature = "async")]
+    const NUM: usize = $num;
+    const CHANNEL_USERS: u8 = $users;
     fn count_16(&self) -> &COUNT16 {
         self.count16()
     }
@@ -163,6 +274,24 @@ impl TimerCounter<$TC>
         Self {
             freq: clock.freq(),
             tc,
+            clock: <clock::$clock as PeripheralClock>::ID,
+            timeout: None,
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl TimerCounter<$TC> {
+    /// Wake the task waiting on an async delay of this timer
+    ///
+    /// This must be called from the interrupt handler of the timer. The
+    /// overflow interrupt is disabled; its flag is cleared by the woken task.
+    pub fn on_interrupt() {
+        // SAFETY: writing INTENCLR only disables the overflow interrupt
+        let count = unsafe { &*$TC::ptr() }.count16();
+        if count.intflag.read().ovf().bit_is_set() && count.intenset.read().ovf().bit_is_set() {
+            count.intenclr.write(|w| w.ovf().set_bit());
+            TC_WAKERS[$num].wake();
         }
     }
 }
@@ -171,13 +300,13 @@ impl TimerCounter<$TC>
 }
 
 tc! {
-    TimerCounter2: (TC2, tc2_, Tc2Tc3Clock, apbbmask),
-    TimerCounter3: (TC3, tc3_, Tc2Tc3Clock, apbbmask),
+    TimerCounter2: (TC2, 2, 0b01, tc2_, Tc2Tc3Clock, apbbmask),
+    TimerCounter3: (TC3, 3, 0b10, tc3_, Tc2Tc3Clock, apbbmask),
 }
 
 // Only the G variants are missing these timers
 #[cfg(feature = "min-samd51j")]
 tc! {
-    TimerCounter4: (TC4, tc4_, Tc4Tc5Clock, apbcmask),
-    TimerCounter5: (TC5, tc5_, Tc4Tc5Clock, apbcmask),
+    TimerCounter4: (TC4, 4, 0b01, tc4_, Tc4Tc5Clock, apbcmask),
+    TimerCounter5: (TC5, 5, 0b10, tc5_, Tc4Tc5Clock, apbcmask),
 }
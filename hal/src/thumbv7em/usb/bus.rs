@@ -515,6 +515,13 @@ impl Inner {
 }
 
 impl UsbBus {
+    /// Enable the USB peripheral clocks and take over the D-/D+ pins.
+    ///
+    /// The USB peripheral must be clocked at 48MHz. Without a 32khz crystal,
+    /// create the controller with
+    /// [`GenericClockController::with_usb_clock_recovery`](clock::GenericClockController::with_usb_clock_recovery)
+    /// and clock the USB from a generator fed by the DFLL48M: the DFLL48M then locks on the start of
+    /// frame packets of the host once the bus is enabled.
     pub fn new(
         _clock: &clock::UsbClock,
         mclk: &mut MCLK,
@@ -1,4 +1,25 @@
 //! Time units
+//!
+//! The HAL is moving to the [`fugit`] rates and durations, which are shared
+//! with RTIC and other crates. In the meantime, the HAL keeps the types below,
+//! and converts the `fugit` types into them: every API taking an
+//! `impl Into<Hertz>`, `impl Into<Nanoseconds>`, etc. also accepts the
+//! equivalent `fugit` rate or duration. The types below convert into the
+//! `fugit` ones as well, e.g. the frequency of a clock.
+//!
+//! The `fugit` extension traits are part of the [prelude](crate::prelude):
+//!
+//! ```
+//! # use atsamd_hal::fugit::{ExtU32, MegahertzU32, MillisDurationU32, RateExtU32};
+//! # use atsamd_hal::time::*;
+//! let freq: Hertz = 2_u32.kHz::<1, 1>().into();
+//! assert_eq!(freq, 2_000.hz());
+//! let timeout: Nanoseconds = 3_u32.millis::<1, 1_000>().into();
+//! assert_eq!(timeout, 3.ms().into());
+//! let freq: MegahertzU32 = 48.mhz().into();
+//! assert_eq!(freq.to_Hz(), 48_000_000);
+//! let _: MillisDurationU32 = 5.ms().into();
+//! ```
 
 // Frequency based
 
@@ -244,6 +265,115 @@ impl From<MegaHertz> for Nanoseconds {
     }
 }
 
+// fugit
+
+/// Scale `ticks` of a `NOM / DENOM` fugit unit by `mul / div`, saturating
+/// instead of overflowing
+fn from_fugit<const NOM: u32, const DENOM: u32>(ticks: u32, mul: u32, div: u32) -> u32 {
+    let nom = NOM as u64 * mul as u64;
+    let denom = DENOM as u64 * div as u64;
+    match (ticks as u64).checked_mul(nom) {
+        Some(scaled) => (scaled / denom).min(u32::MAX as u64) as u32,
+        None => u32::MAX,
+    }
+}
+
+impl<const NOM: u32, const DENOM: u32> From<fugit::Rate<u32, NOM, DENOM>> for Hertz {
+    fn from(item: fugit::Rate<u32, NOM, DENOM>) -> Self {
+        Hertz(from_fugit::<NOM, DENOM>(item.raw(), 1, 1))
+    }
+}
+
+impl<const NOM: u32, const DENOM: u32> From<fugit::Rate<u32, NOM, DENOM>> for KiloHertz {
+    fn from(item: fugit::Rate<u32, NOM, DENOM>) -> Self {
+        KiloHertz(from_fugit::<NOM, DENOM>(item.raw(), 1, 1_000))
+    }
+}
+
+impl<const NOM: u32, const DENOM: u32> From<fugit::Rate<u32, NOM, DENOM>> for MegaHertz {
+    fn from(item: fugit::Rate<u32, NOM, DENOM>) -> Self {
+        MegaHertz(from_fugit::<NOM, DENOM>(item.raw(), 1, 1_000_000))
+    }
+}
+
+impl<const NOM: u32, const DENOM: u32> From<fugit::Rate<u32, NOM, DENOM>> for Microseconds {
+    fn from(item: fugit::Rate<u32, NOM, DENOM>) -> Self {
+        Hertz::from(item).into()
+    }
+}
+
+impl<const NOM: u32, const DENOM: u32> From<fugit::Rate<u32, NOM, DENOM>> for Nanoseconds {
+    fn from(item: fugit::Rate<u32, NOM, DENOM>) -> Self {
+        Hertz::from(item).into()
+    }
+}
+
+impl<const NOM: u32, const DENOM: u32> From<fugit::Duration<u32, NOM, DENOM>> for Seconds {
+    fn from(item: fugit::Duration<u32, NOM, DENOM>) -> Self {
+        Seconds(from_fugit::<NOM, DENOM>(item.ticks(), 1, 1))
+    }
+}
+
+impl<const NOM: u32, const DENOM: u32> From<fugit::Duration<u32, NOM, DENOM>> for Milliseconds {
+    fn from(item: fugit::Duration<u32, NOM, DENOM>) -> Self {
+        Milliseconds(from_fugit::<NOM, DENOM>(item.ticks(), 1_000, 1))
+    }
+}
+
+impl<const NOM: u32, const DENOM: u32> From<fugit::Duration<u32, NOM, DENOM>> for Microseconds {
+    fn from(item: fugit::Duration<u32, NOM, DENOM>) -> Self {
+        Microseconds(from_fugit::<NOM, DENOM>(item.ticks(), 1_000_000, 1))
+    }
+}
+
+impl<const NOM: u32, const DENOM: u32> From<fugit::Duration<u32, NOM, DENOM>> for Nanoseconds {
+    fn from(item: fugit::Duration<u32, NOM, DENOM>) -> Self {
+        Nanoseconds(from_fugit::<NOM, DENOM>(item.ticks(), 1_000_000_000, 1))
+    }
+}
+
+impl From<Hertz> for fugit::HertzU32 {
+    fn from(item: Hertz) -> Self {
+        Self::from_raw(item.0)
+    }
+}
+
+impl From<KiloHertz> for fugit::KilohertzU32 {
+    fn from(item: KiloHertz) -> Self {
+        Self::from_raw(item.0)
+    }
+}
+
+impl From<MegaHertz> for fugit::MegahertzU32 {
+    fn from(item: MegaHertz) -> Self {
+        Self::from_raw(item.0)
+    }
+}
+
+impl From<Seconds> for fugit::SecsDurationU32 {
+    fn from(item: Seconds) -> Self {
+        Self::from_ticks(item.0)
+    }
+}
+
+impl From<Milliseconds> for fugit::MillisDurationU32 {
+    fn from(item: Milliseconds) -> Self {
+        Self::from_ticks(item.0)
+    }
+}
+
+impl From<Microseconds> for fugit::MicrosDurationU32 {
+    fn from(item: Microseconds) -> Self {
+        Self::from_ticks(item.0)
+    }
+}
+
+impl From<Nanoseconds> for fugit::NanosDurationU32 {
+    fn from(item: Nanoseconds) -> Self {
+        Self::from_ticks(item.0)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::time::*;
@@ -277,4 +407,36 @@ mod tests {
         let as_ns: Nanoseconds = 2.mhz().into();
         assert_eq!(as_ns.0, 500_u32);
     }
+
+    #[test]
+    fn convert_fugit_khz_to_hz() {
+        let as_hz: Hertz = fugit::KilohertzU32::from_raw(48).into();
+        assert_eq!(as_hz.0, 48_000_u32);
+    }
+
+    #[test]
+    fn convert_fugit_hz_to_ns() {
+        let as_ns: Nanoseconds = fugit::HertzU32::from_raw(3_000_000).into();
+        assert_eq!(as_ns.0, 333_u32);
+    }
+
+    #[test]
+    fn convert_fugit_ms_to_us() {
+        let as_us: Microseconds = fugit::MillisDurationU32::from_ticks(3).into();
+        assert_eq!(as_us.0, 3_000_u32);
+    }
+
+    #[test]
+    fn convert_fugit_saturates() {
+        let as_hz: Hertz = fugit::MegahertzU32::from_raw(5_000).into();
+        assert_eq!(as_hz.0, u32::MAX);
+        let as_ns: Nanoseconds = fugit::SecsDurationU32::from_ticks(5).into();
+        assert_eq!(as_ns.0, u32::MAX);
+    }
+
+    #[test]
+    fn convert_mhz_to_fugit() {
+        let as_fugit: fugit::MegahertzU32 = MegaHertz(48).into();
+        assert_eq!(as_fugit.to_Hz(), 48_000_000_u32);
+    }
 }
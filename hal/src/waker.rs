@@ -0,0 +1,37 @@
+//! Waker storage shared by the async drivers and their interrupt handlers
+//!
+//! `atomic-waker` needs compare-and-swap, which the Cortex-M0+ lacks, so the
+//! waker is stored behind a critical section instead.
+
+use core::cell::Cell;
+use core::task::Waker;
+
+use cortex_m::interrupt::{self, Mutex};
+
+/// Storage for the waker of a single task
+pub(crate) struct WakerCell(Mutex<Cell<Option<Waker>>>);
+
+impl WakerCell {
+    pub(crate) const fn new() -> Self {
+        Self(Mutex::new(Cell::new(None)))
+    }
+
+    /// Register the waker of the current task, to be woken by [`Self::wake`]
+    pub(crate) fn register(&self, waker: &Waker) {
+        interrupt::free(|cs| {
+            let cell = self.0.borrow(cs);
+            let waker = match cell.take() {
+                Some(old) if old.will_wake(waker) => old,
+                _ => waker.clone(),
+            };
+            cell.set(Some(waker));
+        });
+    }
+
+    /// Wake the registered task, if any
+    pub(crate) fn wake(&self) {
+        if let Some(waker) = interrupt::free(|cs| self.0.borrow(cs).take()) {
+            waker.wake();
+        }
+    }
+}